@@ -0,0 +1,230 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use dashu::Decimal;
+use num_traits::ToPrimitive;
+
+use super::error::BcError;
+use super::number::BcNumber;
+use super::posix::{PosixExpr, PosixParser};
+use super::runtime::Param;
+use super::statistics::STATISTICS;
+
+/// The largest index an array takes, GNU bc's `BC_DIM_MAX`.
+const MAX_INDEX: usize = 16_777_215;
+
+/// What a user function is called with: a number, or a whole array passed
+/// as `a[]`.
+pub(super) enum Argument {
+    Value(Box<BcNumber>),
+    Array(String),
+}
+
+impl super::BcExecuter {
+    /// Whether `expr` indexes an array or passes one whole.
+    pub(super) fn mentions_arrays(&self, expr: &str) -> bool {
+        PosixParser::mentions_arrays(expr, self.runtime.ibase())
+    }
+
+    /// Reads an element, or calls a function handed whole arrays, for any of
+    /// the expression evaluators; `eval` evaluates indices and the other
+    /// arguments the way that evaluator does. Elements never written read
+    /// as 0.
+    pub(super) fn eval_array_node(
+        &mut self,
+        node: &PosixExpr,
+        mut eval: impl FnMut(&mut Self, &PosixExpr) -> Result<BcNumber, BcError>,
+    ) -> Result<BcNumber, BcError> {
+        match node {
+            PosixExpr::Element(name, index) => {
+                let index = array_index(&eval(self, index)?.value)?;
+                let element = self
+                    .runtime
+                    .get_array(name)
+                    .and_then(|array| array.borrow().get(&index).cloned());
+                Ok(element.unwrap_or_else(BcNumber::zero))
+            }
+            PosixExpr::Array(name) => Err(BcError::Error(format!(
+                "{name}[] is a whole array; index it as {name}[i]"
+            ))),
+            PosixExpr::ArrayCall(name, args) => {
+                let mut arguments = Vec::with_capacity(args.len());
+                for arg in args {
+                    arguments.push(match arg {
+                        PosixExpr::Array(array) => Argument::Array(array.clone()),
+                        _ => Argument::Value(Box::new(eval(self, arg)?)),
+                    });
+                }
+                if STATISTICS.contains(&name.as_str()) {
+                    let values = self.spread_arrays(arguments);
+                    return Ok(BcNumber::natural(self.builtin_statistic(name, &values)?));
+                }
+                match self.call_function_with(name, arguments)? {
+                    Some(result) => Ok(result),
+                    None => Err(BcError::Error(format!("{name}() does not take arrays"))),
+                }
+            }
+            _ => eval(self, node),
+        }
+    }
+
+    /// The statistics builtins' values, with each array spread out over
+    /// indices 0 up to its last element.
+    fn spread_arrays(&self, arguments: Vec<Argument>) -> Vec<Decimal> {
+        let mut values = Vec::new();
+        for argument in arguments {
+            match argument {
+                Argument::Value(number) => values.push(self.promote_precision(number.value)),
+                Argument::Array(name) => {
+                    let Some(array) = self.runtime.get_array(&name) else {
+                        continue;
+                    };
+                    let array = array.borrow();
+                    let length = array.keys().next_back().map_or(0, |last| last + 1);
+                    values.extend((0..length).map(|index| {
+                        array
+                            .get(&index)
+                            .map_or(Decimal::ZERO, |element| element.value.clone())
+                    }));
+                }
+            }
+        }
+        values
+    }
+
+    /// Splits an assignment target such as `a[i + 1]` into the array name
+    /// and the index expression.
+    pub(super) fn split_element(target: &str) -> Option<(&str, &str)> {
+        let inner = target.strip_suffix(']')?;
+        let (name, index) = inner.split_once('[')?;
+        let name = name.trim();
+        Self::is_valid_identifier(name).then_some((name, index))
+    }
+
+    /// `name[index] = expr`, growing the array as needed.
+    pub(super) fn assign_element(
+        &mut self,
+        name: &str,
+        index: &str,
+        expr: &str,
+    ) -> Result<BcNumber, BcError> {
+        // Word mode would wrap the subscript along with the value.
+        let index = if self.runtime.word().is_some() {
+            self.eval_posix_expression(index)?
+        } else {
+            self.eval_number(index)?
+        };
+        let index = array_index(&index.value)?;
+        let value = self.eval_number(expr)?;
+        let value = self.round_for_assignment(value);
+        self.runtime
+            .array(name)
+            .borrow_mut()
+            .insert(index, value.clone());
+        Ok(value)
+    }
+
+    /// `auto x, t[]`: fresh locals for the running function, so they hide
+    /// the caller's variables and arrays of the same name.
+    pub(super) fn eval_auto(&mut self, stmt: &str) -> Result<(), BcError> {
+        if !self.runtime.in_function() {
+            return Err(BcError::Error(
+                "auto is only allowed inside a function".to_string(),
+            ));
+        }
+        let list = stmt.trim_start()["auto".len()..].trim();
+        for local in list.split(',') {
+            match Self::parse_param(local.trim())? {
+                Param::Scalar(name) => {
+                    if let Some(scope) = self.runtime.current_scope_mut() {
+                        scope.insert(name, BcNumber::zero());
+                    }
+                }
+                Param::Array(name) => self.runtime.declare_array(&name, Default::default()),
+                Param::ArrayRef(name) => {
+                    return Err(BcError::Error(format!(
+                        "auto {name}[] cannot be a reference"
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads one entry of a parameter or `auto` list: `x`, `a[]` or `*a[]`.
+    pub(super) fn parse_param(spelling: &str) -> Result<Param, BcError> {
+        let (reference, spelling) = match spelling.strip_prefix('*') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, spelling),
+        };
+        let array = spelling
+            .strip_suffix(']')
+            .and_then(|rest| rest.trim_end().strip_suffix('['))
+            .map(str::trim_end);
+        let name = array.unwrap_or(spelling);
+        if !Self::is_valid_identifier(name) || (reference && array.is_none()) {
+            return Err(BcError::Error(format!("Invalid parameter: {spelling}")));
+        }
+        Self::ensure_not_constant(name)?;
+        let name = name.to_string();
+        Ok(match (array.is_some(), reference) {
+            (false, _) => Param::Scalar(name),
+            (true, false) => Param::Array(name),
+            (true, true) => Param::ArrayRef(name),
+        })
+    }
+
+    /// Binds a call's arguments to the function's parameters: numbers and
+    /// `a[]` arrays by value, `*a[]` arrays by reference.
+    pub(super) fn bind_arguments(
+        &mut self,
+        name: &str,
+        params: &[Param],
+        args: Vec<Argument>,
+    ) -> Result<(), BcError> {
+        let mut scope = BTreeMap::new();
+        let mut arrays = BTreeMap::new();
+        for (param, arg) in params.iter().zip(args) {
+            match (param, arg) {
+                (Param::Scalar(param), Argument::Value(value)) => {
+                    scope.insert(param.clone(), *value);
+                }
+                (Param::Array(param), Argument::Array(array)) => {
+                    let copy = self
+                        .runtime
+                        .get_array(&array)
+                        .map(|array| array.borrow().clone())
+                        .unwrap_or_default();
+                    arrays.insert(param.clone(), Rc::new(RefCell::new(copy)));
+                }
+                (Param::ArrayRef(param), Argument::Array(array)) => {
+                    arrays.insert(param.clone(), self.runtime.array(&array));
+                }
+                (Param::Scalar(param), Argument::Array(_)) => {
+                    return Err(BcError::Error(format!(
+                        "{name}() takes {param} as a number, not an array"
+                    )))
+                }
+                (param, Argument::Value(_)) => {
+                    return Err(BcError::Error(format!(
+                        "{name}() takes {}[] as an array",
+                        param.name()
+                    )))
+                }
+            }
+        }
+        self.runtime.push_scope(scope, arrays);
+        Ok(())
+    }
+}
+
+/// An index as bc reads it: truncated to an integer, and out of range when
+/// negative or past `MAX_INDEX`.
+fn array_index(value: &Decimal) -> Result<usize, BcError> {
+    value
+        .trunc()
+        .to_usize()
+        .filter(|index| *index <= MAX_INDEX)
+        .ok_or_else(|| BcError::Error("Array index out of range".to_string()))
+}
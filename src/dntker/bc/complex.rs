@@ -1,10 +1,12 @@
 use std::str::FromStr;
 
 use dashu::Decimal;
-use libm::{cos, cosh, sin, sinh, sqrt};
-use num_traits::{ToPrimitive, Zero};
+use num_traits::Zero;
 
 use super::error::BcError;
+use super::literals::LiteralTable;
+use super::mathlib;
+use super::number::BcNumber;
 
 impl super::BcExecuter {
     pub(super) fn try_eval_complex_expression(
@@ -26,13 +28,11 @@ impl super::BcExecuter {
                 Err(ComplexParseError::NotComplex) => return Ok(None),
                 Err(ComplexParseError::Invalid(msg)) => return Err(BcError::Error(msg)),
             };
-            let magnitude = match parsed.value.magnitude() {
-                Ok(value) => value,
-                Err(ComplexParseError::Invalid(msg)) => return Err(BcError::Error(msg)),
-                Err(ComplexParseError::NotComplex) => unreachable!(),
-            };
-            let decimal = self.decimal_from_f64(magnitude, "complex abs overflowed")?;
-            return Ok(Some(self.format_result_decimal(&decimal)));
+            let value = parsed.value;
+            let magnitude = self.eval_precise(&[&value.real, &value.imag], |precision| {
+                mathlib::hypot(&value.real, &value.imag, precision)
+            })?;
+            return Ok(Some(self.format_result_decimal(&magnitude)));
         }
 
         if let Some(inner) = Self::strip_wrapped_function(trimmed, "sin")? {
@@ -41,12 +41,7 @@ impl super::BcExecuter {
                 Err(ComplexParseError::NotComplex) => return Ok(None),
                 Err(ComplexParseError::Invalid(msg)) => return Err(BcError::Error(msg)),
             };
-            let (real_f, imag_f) = parsed
-                .value
-                .sin_components()
-                .map_err(|err| BcError::Error(err.into_message()))?;
-            let real = self.decimal_from_f64(real_f, "complex sin overflowed")?;
-            let imag = self.decimal_from_f64(imag_f, "complex sin overflowed")?;
+            let (real, imag) = self.complex_sin_components(&parsed.value)?;
             return Ok(Some(self.format_complex_result(real, imag)));
         }
 
@@ -75,7 +70,28 @@ impl super::BcExecuter {
         }
     }
 
-    fn strip_wrapped_function<'a>(input: &'a str, name: &str) -> Result<Option<&'a str>, BcError> {
+    /// sin(a+bi) = sin(a)cosh(b) + cos(a)sinh(b)i, each part at the current
+    /// scale, with a+bi read in the current `angle` unit.
+    pub(super) fn complex_sin_components(
+        &self,
+        value: &ComplexNumber,
+    ) -> Result<(Decimal, Decimal), BcError> {
+        let (a, b) = (&value.real, &value.imag);
+        let real = self.eval_precise(&[a, b], |precision| {
+            let (a, b) = (self.radians_of(a, precision), self.radians_of(b, precision));
+            Ok(mathlib::sin(&a, precision)? * mathlib::cosh(&b, precision)?)
+        })?;
+        let imag = self.eval_precise(&[a, b], |precision| {
+            let (a, b) = (self.radians_of(a, precision), self.radians_of(b, precision));
+            Ok(mathlib::cos(&a, precision)? * mathlib::sinh(&b, precision)?)
+        })?;
+        Ok((real, imag))
+    }
+
+    pub(super) fn strip_wrapped_function<'a>(
+        input: &'a str,
+        name: &str,
+    ) -> Result<Option<&'a str>, BcError> {
         let trimmed = input.trim();
         if !trimmed.starts_with(name) {
             return Ok(None);
@@ -98,7 +114,8 @@ struct ComplexExpression {
 
 impl ComplexExpression {
     fn parse(exec: &super::BcExecuter, expr: &str) -> Result<Self, ComplexParseError> {
-        let (tokens, seen_imaginary, saw_number) = ComplexLexer::tokenize(expr)?;
+        let (tokens, seen_imaginary, saw_number) =
+            ComplexLexer::tokenize(expr, exec.runtime.ibase())?;
         if tokens.is_empty() || !seen_imaginary || !saw_number {
             return Err(ComplexParseError::NotComplex);
         }
@@ -256,7 +273,10 @@ enum ComplexToken {
 struct ComplexLexer;
 
 impl ComplexLexer {
-    fn tokenize(expr: &str) -> Result<(Vec<ComplexToken>, bool, bool), ComplexParseError> {
+    fn tokenize(
+        expr: &str,
+        ibase: u32,
+    ) -> Result<(Vec<ComplexToken>, bool, bool), ComplexParseError> {
         let mut tokens = Vec::new();
         let mut seen_imaginary = false;
         let mut saw_number = false;
@@ -300,8 +320,8 @@ impl ComplexLexer {
                     tokens.push(ComplexToken::ImaginaryUnit);
                     remaining = &remaining[ch.len_utf8()..];
                 }
-                '0'..='9' | '.' => {
-                    let (number, rest) = ComplexLexer::parse_number(remaining)?;
+                c if c.is_ascii_digit() || c == '.' || (ibase != 10 && c.is_ascii_uppercase()) => {
+                    let (number, rest) = ComplexLexer::parse_number(remaining, ibase)?;
                     tokens.push(ComplexToken::Number(number));
                     saw_number = true;
                     remaining = rest;
@@ -327,7 +347,15 @@ impl ComplexLexer {
         Ok((tokens, seen_imaginary, saw_number))
     }
 
-    fn parse_number(input: &str) -> Result<(Decimal, &str), ComplexParseError> {
+    fn parse_number(input: &str, ibase: u32) -> Result<(Decimal, &str), ComplexParseError> {
+        if let Some(len) = LiteralTable::radix_literal_len(input, ibase) {
+            let number = BcNumber::parse_in_base(&input[..len], ibase)
+                .map_err(|_| ComplexParseError::NotComplex)?;
+            return Ok((number.value, &input[len..]));
+        }
+        if ibase != 10 {
+            return Err(ComplexParseError::NotComplex);
+        }
         let mut len = 0usize;
         let mut has_digit = false;
         let mut has_decimal = false;
@@ -452,24 +480,6 @@ impl ComplexNumber {
         }
     }
 
-    pub(super) fn magnitude(&self) -> Result<f64, ComplexParseError> {
-        let real = ToPrimitive::to_f64(&self.real)
-            .ok_or_else(|| ComplexParseError::invalid("complex abs real part out of range"))?;
-        let imag = ToPrimitive::to_f64(&self.imag)
-            .ok_or_else(|| ComplexParseError::invalid("complex abs imaginary part out of range"))?;
-        Ok(sqrt(real * real + imag * imag))
-    }
-
-    pub(super) fn sin_components(&self) -> Result<(f64, f64), ComplexParseError> {
-        let real = ToPrimitive::to_f64(&self.real)
-            .ok_or_else(|| ComplexParseError::invalid("complex sin real part out of range"))?;
-        let imag = ToPrimitive::to_f64(&self.imag)
-            .ok_or_else(|| ComplexParseError::invalid("complex sin imaginary part out of range"))?;
-        let real_part = sin(real) * cosh(imag);
-        let imag_part = cos(real) * sinh(imag);
-        Ok((real_part, imag_part))
-    }
-
     pub(super) fn is_zero(&self) -> bool {
         self.real.is_zero() && self.imag.is_zero()
     }
@@ -0,0 +1,81 @@
+use dashu::Decimal;
+
+use super::error::BcError;
+use super::mathlib;
+use super::number::BcNumber;
+
+/// Mathematical constants, computed at the current `scale` on every use.
+const MATHEMATICAL: [&str; 4] = ["pi", "tau", "e", "phi"];
+
+/// Physical constants in SI units: the exact 2019 SI defining values and
+/// the CODATA 2018 recommended values for the measured ones.
+const PHYSICAL: [(&str, &str); 16] = [
+    ("c", "299792458"),
+    ("h", "6.62607015e-34"),
+    ("k_B", "1.380649e-23"),
+    ("N_A", "6.02214076e23"),
+    ("eV", "1.602176634e-19"),
+    ("q_e", "1.602176634e-19"),
+    ("R", "8.31446261815324"),
+    ("G", "6.67430e-11"),
+    ("g_0", "9.80665"),
+    ("m_e", "9.1093837015e-31"),
+    ("m_p", "1.67262192369e-27"),
+    ("m_u", "1.66053906660e-27"),
+    ("epsilon_0", "8.8541878128e-12"),
+    ("mu_0", "1.25663706212e-6"),
+    ("sigma_SB", "5.670374419e-8"),
+    ("atm", "101325"),
+];
+
+/// Whether `name` is a read-only constant that no assignment, parameter or
+/// `auto` variable may shadow.
+pub(super) fn is_constant(name: &str) -> bool {
+    name == "hbar"
+        || MATHEMATICAL.contains(&name)
+        || PHYSICAL.iter().any(|(constant, _)| *constant == name)
+}
+
+fn physical(name: &str) -> Option<Decimal> {
+    PHYSICAL
+        .iter()
+        .find(|(constant, _)| *constant == name)
+        .and_then(|(_, literal)| BcNumber::parse_in_base(literal, 10).ok())
+        .map(|number| number.value)
+}
+
+impl super::BcExecuter {
+    /// The value of the constant `name`, or `None` when it is not one.
+    /// `hbar` is derived from the exact `h`, so it follows `scale` as well.
+    pub(super) fn lookup_constant(&self, name: &str) -> Option<Result<Decimal, BcError>> {
+        let value = match name {
+            "pi" => self.eval_precise(&[], |precision| Ok(mathlib::pi(precision))),
+            "tau" => self.eval_precise(&[], |precision| {
+                Ok(Decimal::from(2) * mathlib::pi(precision))
+            }),
+            "e" => self.eval_precise(&[], |precision| mathlib::exp(&Decimal::from(1), precision)),
+            "phi" => self.eval_precise(&[], |precision| {
+                let root = mathlib::sqrt(&Decimal::from(5), precision)?;
+                Ok((Decimal::from(1) + root) / Decimal::from(2))
+            }),
+            "hbar" => {
+                let planck = physical("h")?;
+                self.eval_precise(&[], |precision| {
+                    let planck = mathlib::at_precision(&planck, precision);
+                    Ok(planck / (Decimal::from(2) * mathlib::pi(precision)))
+                })
+            }
+            _ => Ok(physical(name)?),
+        };
+        Some(value)
+    }
+
+    /// Rejects `name` as an assignment target or local variable when it
+    /// names a constant.
+    pub(super) fn ensure_not_constant(name: &str) -> Result<(), BcError> {
+        if is_constant(name) {
+            return Err(BcError::Error(format!("Cannot assign to constant {name}")));
+        }
+        Ok(())
+    }
+}
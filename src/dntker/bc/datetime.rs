@@ -0,0 +1,625 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dashu::base::Sign;
+use dashu::Decimal;
+use num_traits::{ToPrimitive, Zero};
+
+use super::error::BcError;
+use super::number::BcNumber;
+use super::posix::{PosixExpr, PosixOp, PosixParser};
+use super::units;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Largest UTC offset accepted, in minutes.
+const MAX_OFFSET_MINUTES: i64 = 18 * 60;
+
+/// A point in time or a length of time, both counted in seconds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Temporal {
+    /// Seconds since 1970-01-01T00:00Z, shown at a UTC offset in minutes.
+    Instant {
+        epoch: Decimal,
+        offset: i64,
+    },
+    Duration(Decimal),
+}
+
+impl Temporal {
+    /// Parses a literal `scan_date_literal` or `scan_duration_literal`
+    /// delimited.
+    pub(crate) fn parse(literal: &str) -> Result<Self, BcError> {
+        if literal.len() >= 10 && literal.as_bytes()[4] == b'-' {
+            Self::parse_date(literal)
+        } else {
+            Self::parse_duration(literal)
+        }
+    }
+
+    fn parse_date(literal: &str) -> Result<Self, BcError> {
+        let invalid = || BcError::Error(format!("Invalid date: {literal}"));
+        let field = |text: &str| text.parse::<i64>().map_err(|_| invalid());
+        let (date, time) = literal.split_once('T').unwrap_or((literal, ""));
+        let (year, month, day) = (
+            field(&date[0..4])?,
+            field(&date[5..7])?,
+            field(&date[8..10])?,
+        );
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(invalid());
+        }
+        let (clock, offset) = match time.find(['Z', '+', '-']) {
+            Some(at) if &time[at..] == "Z" => (&time[..at], 0),
+            Some(at) => {
+                let hours = field(&time[at + 1..at + 3])?;
+                let minutes = field(&time[at + 4..])?;
+                let offset = hours * 60 + minutes;
+                if minutes > 59 || offset > MAX_OFFSET_MINUTES {
+                    return Err(invalid());
+                }
+                let sign = if time[at..].starts_with('-') { -1 } else { 1 };
+                (&time[..at], sign * offset)
+            }
+            None => (time, 0),
+        };
+        let mut seconds = Decimal::ZERO;
+        if !clock.is_empty() {
+            let hours = field(&clock[0..2])?;
+            let minutes = field(&clock[3..5])?;
+            let second = match clock.get(6..) {
+                Some(second) => BcNumber::parse_literal(second).ok_or_else(invalid)?.value,
+                None => Decimal::ZERO,
+            };
+            if hours > 23 || minutes > 59 || second >= Decimal::from(60) {
+                return Err(invalid());
+            }
+            seconds = shift(&second, hours * 3600 + minutes * 60);
+        }
+        let midnight = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+        Ok(Temporal::Instant {
+            epoch: shift(&seconds, midnight - offset * 60),
+            offset,
+        })
+    }
+
+    fn parse_duration(literal: &str) -> Result<Self, BcError> {
+        let invalid = || BcError::Error(format!("Invalid duration: {literal}"));
+        let mut total = BcNumber::zero();
+        let mut rest = literal;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .ok_or_else(invalid)?;
+            let amount = BcNumber::parse_literal(&rest[..digits]).ok_or_else(invalid)?;
+            rest = &rest[digits..];
+            let (unit, seconds) = [
+                ("min", 60),
+                ("w", 7 * SECONDS_PER_DAY),
+                ("d", SECONDS_PER_DAY),
+                ("h", 3600),
+                ("m", 60),
+                ("s", 1),
+            ]
+            .iter()
+            .copied()
+            .find(|(unit, _)| rest.starts_with(unit))
+            .ok_or_else(invalid)?;
+            let length = amount.mul(&BcNumber::new(Decimal::from(seconds), 0), amount.scale);
+            total = total.add(&length);
+            rest = &rest[unit.len()..];
+        }
+        Ok(Temporal::Duration(total.value))
+    }
+
+    /// The instant's seconds since the epoch, or the duration's length.
+    pub fn seconds(&self) -> &Decimal {
+        match self {
+            Temporal::Instant { epoch, .. } => epoch,
+            Temporal::Duration(seconds) => seconds,
+        }
+    }
+
+    /// The opposite duration; instants have none.
+    pub fn negate(&self) -> Option<Self> {
+        match self {
+            Temporal::Instant { .. } => None,
+            Temporal::Duration(seconds) => Some(Temporal::Duration(-seconds.clone())),
+        }
+    }
+}
+
+/// What the date evaluator works on: instants, durations and the plain
+/// numbers that scale them.
+#[derive(Clone, Debug)]
+enum TimeValue {
+    Plain(Decimal),
+    Time(Temporal),
+}
+
+impl TimeValue {
+    fn instant(epoch: Decimal, offset: i64) -> Self {
+        TimeValue::Time(Temporal::Instant { epoch, offset })
+    }
+
+    fn duration(seconds: Decimal) -> Self {
+        TimeValue::Time(Temporal::Duration(seconds))
+    }
+
+    /// The value as a bc number, the way variables hold it.
+    fn number(self) -> BcNumber {
+        match self {
+            TimeValue::Plain(value) => BcNumber::natural(value),
+            TimeValue::Time(time) => BcNumber::temporal(time),
+        }
+    }
+
+    fn seconds(&self) -> &Decimal {
+        match self {
+            TimeValue::Plain(value) => value,
+            TimeValue::Time(time) => time.seconds(),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            TimeValue::Plain(_) => "a number",
+            TimeValue::Time(Temporal::Instant { .. }) => "a date",
+            TimeValue::Time(Temporal::Duration(_)) => "a duration",
+        }
+    }
+
+    fn same_kind(&self, other: &Self) -> bool {
+        self.kind() == other.kind()
+    }
+}
+
+impl super::BcExecuter {
+    /// Whether `expr` has a date or duration literal, calls `now()` or
+    /// `fromepoch()`, or reads a variable holding a date or duration, and
+    /// so has to go through `eval_time_expression`.
+    pub(super) fn mentions_time(&self, expr: &str) -> bool {
+        if !expr.contains(|c: char| c.is_ascii_digit() || c.is_alphabetic()) {
+            return false;
+        }
+        if PosixParser::mentions_time(expr, self.runtime.ibase()) {
+            return true;
+        }
+        expr.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|word| !word.is_empty())
+            .any(|word| self.runtime.holds(word, |number| number.time.is_some()))
+    }
+
+    /// Evaluates `expr` on instants and durations. A duration result can be
+    /// converted with a trailing `in`, as in `1h30m in min`.
+    pub(super) fn eval_time_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let (source, target) = match units::split_conversion(expr) {
+            Some((source, target)) => (source, Some(target)),
+            None => (expr, None),
+        };
+        let tree = PosixParser::parse_expression(source, self.runtime.ibase())?;
+        let result = self.eval_time_node(&tree)?;
+        match (result, target) {
+            (TimeValue::Time(Temporal::Duration(seconds)), Some(unit)) => {
+                self.express_seconds(seconds, unit)
+            }
+            (result, Some(unit)) => Err(BcError::Error(format!(
+                "Cannot convert {} to {}",
+                result.kind(),
+                unit.symbol
+            ))),
+            (result, None) => Ok(result.number()),
+        }
+    }
+
+    fn eval_time_node(&mut self, node: &PosixExpr) -> Result<TimeValue, BcError> {
+        match node {
+            PosixExpr::Number(number) => self.number_time(number),
+            PosixExpr::Time(time) => Ok(TimeValue::Time(self.promote_time(time.clone()))),
+            PosixExpr::Quantity(..) => Err(BcError::Error(
+                "Units cannot be combined with dates and durations".to_string(),
+            )),
+            PosixExpr::Address(_) => Err(BcError::Error(
+                "Dates and durations cannot be combined with IP addresses".to_string(),
+            )),
+            node @ (PosixExpr::Element(..) | PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                let number = self.eval_array_node(node, |exec, node| {
+                    exec.eval_time_node(node).map(TimeValue::number)
+                })?;
+                self.number_time(&number)
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => self.number_time(&number),
+                None => self.eval_time_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_time_node(arg)?);
+                }
+                self.eval_time_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => match self.eval_time_node(operand)? {
+                TimeValue::Plain(value) => Ok(TimeValue::Plain(-value)),
+                TimeValue::Time(time) => time
+                    .negate()
+                    .map(TimeValue::Time)
+                    .ok_or_else(|| BcError::Error("Cannot negate a date".to_string())),
+            },
+            PosixExpr::Not(operand) => {
+                let value = self.eval_time_node(operand)?;
+                Ok(Self::time_bool(value.seconds().is_zero()))
+            }
+            node @ (PosixExpr::Complement(_) | PosixExpr::Bitwise(..)) => {
+                let number = self.eval_bitwise_node(node, |exec, node| {
+                    exec.eval_time_node(node).map(TimeValue::number)
+                })?;
+                self.number_time(&number)
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_time_node(lhs)?.seconds().is_zero() {
+                    return Ok(Self::time_bool(false));
+                }
+                let right = self.eval_time_node(rhs)?;
+                Ok(Self::time_bool(!right.seconds().is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_time_node(lhs)?.seconds().is_zero() {
+                    return Ok(Self::time_bool(true));
+                }
+                let right = self.eval_time_node(rhs)?;
+                Ok(Self::time_bool(!right.seconds().is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let left = self.eval_time_node(lhs)?;
+                let right = self.eval_time_node(rhs)?;
+                self.time_binary(*op, left, right)
+            }
+        }
+    }
+
+    fn time_binary(
+        &self,
+        op: PosixOp,
+        left: TimeValue,
+        right: TimeValue,
+    ) -> Result<TimeValue, BcError> {
+        use Temporal::{Duration, Instant};
+        use TimeValue::{Plain, Time};
+
+        let mismatch = |verb: &str, left: &TimeValue, right: &TimeValue| {
+            BcError::Error(format!(
+                "Cannot {verb} {} and {}",
+                left.kind(),
+                right.kind()
+            ))
+        };
+        match (op, &left, &right) {
+            (PosixOp::Add, Time(Instant { epoch, offset }), Time(Duration(span)))
+            | (PosixOp::Add, Time(Duration(span)), Time(Instant { epoch, offset })) => {
+                Ok(TimeValue::instant(epoch.clone() + span, *offset))
+            }
+            (PosixOp::Add, Time(Duration(a)), Time(Duration(b))) => {
+                Ok(TimeValue::duration(a.clone() + b))
+            }
+            (PosixOp::Sub, Time(Instant { epoch, offset }), Time(Duration(span))) => {
+                Ok(TimeValue::instant(epoch.clone() - span, *offset))
+            }
+            (PosixOp::Sub, Time(Instant { epoch: a, .. }), Time(Instant { epoch: b, .. }))
+            | (PosixOp::Sub, Time(Duration(a)), Time(Duration(b))) => {
+                Ok(TimeValue::duration(a.clone() - b))
+            }
+            (PosixOp::Mul, Time(Duration(span)), Plain(factor))
+            | (PosixOp::Mul, Plain(factor), Time(Duration(span))) => {
+                Ok(TimeValue::duration(span.clone() * factor))
+            }
+            (PosixOp::Div | PosixOp::Mod, _, _) if right.seconds().is_zero() => {
+                Err(BcError::Error("Division by zero".to_string()))
+            }
+            (PosixOp::Div, Time(Duration(span)), Plain(divisor)) => {
+                Ok(TimeValue::duration(span.clone() / divisor))
+            }
+            (PosixOp::Div, Time(Duration(a)), Time(Duration(b))) => Ok(Plain(a.clone() / b)),
+            (PosixOp::Mod, Time(Duration(a)), Time(Duration(b))) => {
+                Ok(TimeValue::duration(a.clone() % b))
+            }
+            (_, Plain(a), Plain(b)) => {
+                let value = match op {
+                    PosixOp::Add => a.clone() + b,
+                    PosixOp::Sub => a.clone() - b,
+                    PosixOp::Mul => a.clone() * b,
+                    PosixOp::Div => a.clone() / b,
+                    PosixOp::Mod => a.clone() % b,
+                    PosixOp::Pow => self.power_decimal(a, b)?,
+                    _ => return Ok(Self::time_compare(op, a, b)),
+                };
+                Ok(Plain(value))
+            }
+            (PosixOp::Add, ..) => Err(mismatch("add", &left, &right)),
+            (PosixOp::Sub, ..) => Err(mismatch("subtract", &left, &right)),
+            (PosixOp::Mul, ..) => Err(mismatch("multiply", &left, &right)),
+            (PosixOp::Div | PosixOp::Mod, ..) => Err(mismatch("divide", &left, &right)),
+            (PosixOp::Pow, ..) => Err(mismatch("raise", &left, &right)),
+            _ if left.same_kind(&right) => {
+                Ok(Self::time_compare(op, left.seconds(), right.seconds()))
+            }
+            _ => Err(mismatch("compare", &left, &right)),
+        }
+    }
+
+    fn time_compare(op: PosixOp, a: &Decimal, b: &Decimal) -> TimeValue {
+        Self::time_bool(match op {
+            PosixOp::Lt => a < b,
+            PosixOp::Le => a <= b,
+            PosixOp::Gt => a > b,
+            PosixOp::Ge => a >= b,
+            PosixOp::Eq => a == b,
+            PosixOp::Ne => a != b,
+            _ => unreachable!("arithmetic handled by the caller"),
+        })
+    }
+
+    /// The date builtins; every other builtin needs plain numbers, while
+    /// user functions get dates and durations as they are.
+    fn eval_time_call(&mut self, name: &str, args: Vec<TimeValue>) -> Result<TimeValue, BcError> {
+        use Temporal::{Duration, Instant};
+        use TimeValue::{Plain, Time};
+
+        let wrong_args = || BcError::Error(format!("Wrong arguments to {name}()"));
+        match (name, args.as_slice()) {
+            ("now", []) => {
+                let elapsed = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|_| BcError::Error("System clock is before 1970".to_string()))?;
+                let nanos = Decimal::from(elapsed.as_nanos());
+                let epoch = self.promote_precision(nanos) / Decimal::from(1_000_000_000);
+                return Ok(TimeValue::instant(epoch, 0));
+            }
+            ("fromepoch", [Plain(seconds)]) => {
+                return Ok(TimeValue::instant(seconds.clone(), 0));
+            }
+            ("epoch", [Time(Instant { epoch, .. })]) | ("seconds", [Time(Duration(epoch))]) => {
+                return Ok(Plain(epoch.clone()));
+            }
+            ("tz", [Time(Instant { epoch, .. }), Plain(hours)]) => {
+                let minutes = hours.clone() * Decimal::from(60);
+                let offset = minutes
+                    .to_i64()
+                    .filter(|offset| {
+                        minutes.fract().is_zero() && offset.abs() <= MAX_OFFSET_MINUTES
+                    })
+                    .ok_or_else(|| {
+                        BcError::Error("tz() offset must be whole minutes within ±18h".to_string())
+                    })?;
+                return Ok(TimeValue::instant(epoch.clone(), offset));
+            }
+            (
+                "weekday" | "year" | "month" | "day" | "hour" | "minute" | "second",
+                [Time(Instant { epoch, offset })],
+            ) => {
+                let fields = CivilTime::at(epoch, *offset)?;
+                let value = match name {
+                    "weekday" => Decimal::from(fields.weekday),
+                    "year" => Decimal::from(fields.year),
+                    "month" => Decimal::from(fields.month),
+                    "day" => Decimal::from(fields.day),
+                    "hour" => Decimal::from(fields.hour),
+                    "minute" => Decimal::from(fields.minute),
+                    _ => fields.second,
+                };
+                return Ok(Plain(value));
+            }
+            ("now" | "fromepoch" | "epoch" | "seconds" | "tz", _) => return Err(wrong_args()),
+            ("xnpv", [Plain(_), ..]) => {
+                // Only the gaps between dates matter, so instants become day numbers.
+                let mut values = Vec::with_capacity(args.len());
+                for arg in &args {
+                    values.push(match arg {
+                        Plain(value) => value.clone(),
+                        Time(Instant { epoch, .. }) => {
+                            self.promote_precision(epoch.clone()) / Decimal::from(SECONDS_PER_DAY)
+                        }
+                        Time(Duration(_)) => return Err(wrong_args()),
+                    });
+                }
+                return self.builtin_finance("xnpv", &values).map(Plain);
+            }
+            _ => {}
+        }
+
+        if args.iter().all(|arg| matches!(arg, Plain(_))) {
+            let values: Vec<Decimal> = args.iter().map(|arg| arg.seconds().clone()).collect();
+            if let Some(result) = self.call_builtin_function(name, &values) {
+                return Ok(Plain(result?));
+            }
+        } else if self.runtime.get_function(name).is_none() {
+            return Err(BcError::Error(format!(
+                "{name}() needs plain numbers, not dates or durations"
+            )));
+        }
+
+        let numbers = args.into_iter().map(TimeValue::number).collect();
+        if let Some(result) = self.call_function(name, numbers)? {
+            return self.number_time(&result);
+        }
+
+        Err(BcError::Error(format!("Undefined identifier: {name}")))
+    }
+
+    fn number_time(&self, number: &BcNumber) -> Result<TimeValue, BcError> {
+        if number.unit.is_some() {
+            return Err(BcError::Error(
+                "Units cannot be combined with dates and durations".to_string(),
+            ));
+        }
+        if number.address.is_some() {
+            return Err(BcError::Error(
+                "Dates and durations cannot be combined with IP addresses".to_string(),
+            ));
+        }
+        Ok(match &number.time {
+            Some(time) => TimeValue::Time(self.promote_time(time.clone())),
+            None => TimeValue::Plain(self.promote_precision(number.value.clone())),
+        })
+    }
+
+    fn promote_time(&self, time: Temporal) -> Temporal {
+        match time {
+            Temporal::Instant { epoch, offset } => Temporal::Instant {
+                epoch: self.promote_precision(epoch),
+                offset,
+            },
+            Temporal::Duration(seconds) => Temporal::Duration(self.promote_precision(seconds)),
+        }
+    }
+
+    fn time_bool(value: bool) -> TimeValue {
+        TimeValue::Plain(if value { Decimal::ONE } else { Decimal::ZERO })
+    }
+
+    /// `2026-10-18T09:30:00Z` at the instant's own offset, or a duration as
+    /// `1d2h30m15s`, which reads back as the same duration.
+    pub(super) fn format_temporal(&self, time: &Temporal) -> String {
+        match time {
+            Temporal::Instant { epoch, offset } => match CivilTime::at(epoch, *offset) {
+                Ok(fields) => {
+                    let zone = if *offset == 0 {
+                        "Z".to_string()
+                    } else {
+                        let sign = if *offset < 0 { '-' } else { '+' };
+                        format!("{sign}{:02}:{:02}", offset.abs() / 60, offset.abs() % 60)
+                    };
+                    format!(
+                        "{:04}-{:02}-{:02}T{:02}:{:02}:{}{zone}",
+                        fields.year,
+                        fields.month,
+                        fields.day,
+                        fields.hour,
+                        fields.minute,
+                        self.format_seconds(&fields.second, 2)
+                    )
+                }
+                Err(error) => error.to_string(),
+            },
+            Temporal::Duration(seconds) => {
+                let negative = seconds.sign() == Sign::Negative && !seconds.is_zero();
+                let mut rest = if negative {
+                    -seconds.clone()
+                } else {
+                    seconds.clone()
+                };
+                let mut formatted = String::from(if negative { "-" } else { "" });
+                // `min` rather than `m`, which alone reads back as milli.
+                for (unit, size) in [("d", SECONDS_PER_DAY), ("h", 3600), ("min", 60)] {
+                    let count = (rest.clone() / Decimal::from(size))
+                        .trunc()
+                        .to_i64()
+                        .unwrap_or(0);
+                    if count != 0 {
+                        formatted.push_str(&format!("{count}{unit}"));
+                        rest = shift(&rest, -count * size);
+                    }
+                }
+                let seconds = self.format_seconds(&rest, 1);
+                if seconds != "0" || formatted.len() <= usize::from(negative) {
+                    formatted.push_str(&seconds);
+                    formatted.push('s');
+                }
+                formatted
+            }
+        }
+    }
+
+    /// Seconds cut to `scale` fractional digits, trailing zeros dropped, and
+    /// the integer part zero-padded to `width` digits.
+    fn format_seconds(&self, seconds: &Decimal, width: usize) -> String {
+        let truncated = Self::truncate_decimal_to_scale(seconds, self.runtime.scale());
+        let plain = Self::decimal_to_plain_string(&truncated);
+        let plain = if plain.contains('.') {
+            plain.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &plain
+        };
+        let integer_len = plain.find('.').unwrap_or(plain.len());
+        format!("{}{plain}", "0".repeat(width.saturating_sub(integer_len)))
+    }
+}
+
+/// The calendar fields of an instant at a UTC offset.
+struct CivilTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    /// ISO weekday: 1 for Monday through 7 for Sunday.
+    weekday: i64,
+    hour: i64,
+    minute: i64,
+    second: Decimal,
+}
+
+impl CivilTime {
+    fn at(epoch: &Decimal, offset: i64) -> Result<Self, BcError> {
+        let local = shift(epoch, offset * 60);
+        let days = (local.clone() / Decimal::from(SECONDS_PER_DAY))
+            .floor()
+            .to_i64()
+            .filter(|days| days.abs() < 1_000_000_000)
+            .ok_or_else(|| BcError::Error("Date out of supported range".to_string()))?;
+        let of_day = shift(&local, -days * SECONDS_PER_DAY);
+        let whole = of_day.trunc().to_i64().unwrap_or(0);
+        let (year, month, day) = civil_from_days(days);
+        Ok(Self {
+            year,
+            month,
+            day,
+            weekday: (days + 3).rem_euclid(7) + 1,
+            hour: whole / 3600,
+            minute: whole % 3600 / 60,
+            second: shift(&of_day, -(whole / 60 * 60)),
+        })
+    }
+}
+
+/// `value` moved by a whole number of `seconds`, keeping every digit.
+fn shift(value: &Decimal, seconds: i64) -> Decimal {
+    BcNumber::natural(value.clone())
+        .add(&BcNumber::new(Decimal::from(seconds), 0))
+        .value
+}
+
+/// Days from 1970-01-01 to the given proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
@@ -0,0 +1,463 @@
+use dashu::base::{Abs, Sign};
+use dashu::integer::IBig;
+use dashu::Decimal;
+use num_traits::{ToPrimitive, Zero};
+use rand::RngCore;
+
+use super::error::BcError;
+use super::mathlib::{self, WORK_DIGITS};
+use super::number::number;
+use super::numtheory;
+use super::special;
+
+/// Distribution families; each offers `<family>pdf`, `<family>cdf`,
+/// `<family>inv` and `<family>rnd`, after MATLAB's names.
+const FAMILIES: [&str; 7] = ["norm", "t", "chi2", "bino", "poiss", "exp", "unif"];
+const KINDS: [&str; 4] = ["pdf", "cdf", "inv", "rnd"];
+
+/// Whether `name` is one of the builtins `builtin_distribution` handles.
+pub(super) fn is_distribution(name: &str) -> bool {
+    split(name).is_some()
+}
+
+fn split(name: &str) -> Option<(&str, &str)> {
+    let kind = KINDS.iter().find(|kind| name.ends_with(*kind))?;
+    let family = &name[..name.len() - kind.len()];
+    FAMILIES.contains(&family).then_some((family, *kind))
+}
+
+impl super::BcExecuter {
+    /// Densities, cumulative probabilities, quantiles and random draws. A
+    /// draw takes one value from the seeded generator and maps it through
+    /// the quantile function, so `srand` makes every sample reproducible.
+    pub(super) fn builtin_distribution(
+        &mut self,
+        name: &str,
+        args: &[Decimal],
+    ) -> Result<Decimal, BcError> {
+        let (family, kind) =
+            split(name).ok_or_else(|| BcError::Error(format!("Unknown distribution {name}")))?;
+        let offset = usize::from(kind != "rnd");
+        let counts: &[usize] = match family {
+            "norm" => &[0, 2],
+            "bino" | "unif" => &[2],
+            _ => &[1],
+        };
+        if args.len() < offset || !counts.contains(&(args.len() - offset)) {
+            let expected: Vec<String> = counts
+                .iter()
+                .map(|count| (count + offset).to_string())
+                .collect();
+            let expected = expected.join(" or ");
+            let plural = if expected == "1" {
+                "argument"
+            } else {
+                "arguments"
+            };
+            return Err(BcError::Error(format!(
+                "{name}() expects {expected} {plural}, got {}",
+                args.len()
+            )));
+        }
+        let distribution = Distribution::new(name, family, &args[offset..])?;
+        let point = if kind == "rnd" {
+            self.uniform_draw()
+        } else {
+            args[0].clone()
+        };
+        if kind == "inv" && (point.sign() == Sign::Negative || point > Decimal::ONE) {
+            return Err(BcError::Error(format!(
+                "{name}() needs a probability between 0 and 1"
+            )));
+        }
+        let inputs: Vec<&Decimal> = args.iter().collect();
+        self.eval_precise(&inputs, |precision| {
+            let work = precision + WORK_DIGITS;
+            let point = mathlib::at_precision(&point, work);
+            match kind {
+                "pdf" => distribution.density(&point, work),
+                "cdf" => distribution.cdf(&point, work),
+                _ => distribution.quantile(name, &point, precision, work),
+            }
+        })
+    }
+
+    /// The midpoint of one of 2^53 equal slices of (0, 1), never 0 or 1.
+    fn uniform_draw(&mut self) -> Decimal {
+        let bits = self.runtime.rng_mut().next_u64() >> 11;
+        Decimal::from_parts(IBig::from(2 * bits + 1) * IBig::from(5).pow(54), -54)
+    }
+}
+
+enum Distribution {
+    Normal { mean: Decimal, sd: Decimal },
+    StudentT { df: Decimal },
+    ChiSquared { df: Decimal },
+    Binomial { trials: u64, p: Decimal },
+    Poisson { rate: Decimal },
+    Exponential { rate: Decimal },
+    Uniform { low: Decimal, high: Decimal },
+}
+
+impl Distribution {
+    /// Checks the parameters of `family`, whose count the caller has checked.
+    fn new(name: &str, family: &str, params: &[Decimal]) -> Result<Self, BcError> {
+        let positive = |value: &Decimal, what: &str| {
+            if value.sign() == Sign::Positive && !value.is_zero() {
+                Ok(value.clone())
+            } else {
+                Err(BcError::Error(format!("{name}() needs {what} > 0")))
+            }
+        };
+        Ok(match family {
+            "norm" if params.is_empty() => Distribution::Normal {
+                mean: Decimal::ZERO,
+                sd: Decimal::ONE,
+            },
+            "norm" => Distribution::Normal {
+                mean: params[0].clone(),
+                sd: positive(&params[1], "sigma")?,
+            },
+            "t" => Distribution::StudentT {
+                df: positive(&params[0], "degrees of freedom")?,
+            },
+            "chi2" => Distribution::ChiSquared {
+                df: positive(&params[0], "degrees of freedom")?,
+            },
+            "bino" => {
+                let trials = numtheory::integer_arg(name, &params[0])?
+                    .to_u64()
+                    .ok_or_else(|| BcError::Error(format!("{name}() needs 0 <= trials < 2^64")))?;
+                let p = params[1].clone();
+                if p.sign() == Sign::Negative || p > Decimal::ONE {
+                    return Err(BcError::Error(format!(
+                        "{name}() needs a probability between 0 and 1"
+                    )));
+                }
+                Distribution::Binomial { trials, p }
+            }
+            "poiss" => Distribution::Poisson {
+                rate: positive(&params[0], "lambda")?,
+            },
+            "exp" => Distribution::Exponential {
+                rate: positive(&params[0], "lambda")?,
+            },
+            _ => {
+                if params[0] >= params[1] {
+                    return Err(BcError::Error(format!("{name}() needs a < b")));
+                }
+                Distribution::Uniform {
+                    low: params[0].clone(),
+                    high: params[1].clone(),
+                }
+            }
+        })
+    }
+
+    /// The probability density, or the probability mass of a discrete family.
+    fn density(&self, x: &Decimal, work: usize) -> Result<Decimal, BcError> {
+        let one = number(1, work);
+        match self {
+            Distribution::Normal { mean, sd } => {
+                let sd = mathlib::at_precision(sd, work);
+                let z = (x.clone() - mean) / &sd;
+                let root = mathlib::sqrt(&(Decimal::from(2) * mathlib::pi(work)), work)?;
+                Ok(mathlib::exp(&(-(z.clone() * z) / Decimal::from(2)), work)? / (sd * root))
+            }
+            Distribution::StudentT { df } => {
+                let df = mathlib::at_precision(df, work);
+                let upper = (df.clone() + Decimal::ONE) / Decimal::from(2);
+                let log = special::lgamma(&upper, work)?
+                    - special::lgamma(&(df.clone() / Decimal::from(2)), work)?
+                    - upper * mathlib::ln(&(one + x.clone() * x / &df), work)?;
+                let root = mathlib::sqrt(&(df * mathlib::pi(work)), work)?;
+                Ok(mathlib::exp(&log, work)? / root)
+            }
+            Distribution::ChiSquared { df } => {
+                if x.sign() == Sign::Negative {
+                    return Ok(Decimal::ZERO);
+                }
+                let half = mathlib::at_precision(df, work) / Decimal::from(2);
+                if x.is_zero() {
+                    return match half.cmp(&one) {
+                        std::cmp::Ordering::Less => {
+                            Err(BcError::Error("chi2pdf() is infinite at 0".to_string()))
+                        }
+                        std::cmp::Ordering::Equal => Ok(one / Decimal::from(2)),
+                        std::cmp::Ordering::Greater => Ok(Decimal::ZERO),
+                    };
+                }
+                let log = (half.clone() - one) * mathlib::ln(x, work)?
+                    - x.clone() / Decimal::from(2)
+                    - half.clone() * mathlib::ln(&number(2, work), work)?
+                    - special::lgamma(&half, work)?;
+                mathlib::exp(&log, work)
+            }
+            Distribution::Binomial { trials, p } => {
+                let k = match count(x).filter(|k| k <= trials) {
+                    Some(k) => k,
+                    None => return Ok(Decimal::ZERO),
+                };
+                let ways = numtheory::binomial(&[Decimal::from(*trials), Decimal::from(k)])?;
+                let p = mathlib::at_precision(p, work);
+                let miss = one - &p;
+                Ok(mathlib::at_precision(&ways, work)
+                    * p.powi(IBig::from(k))
+                    * miss.powi(IBig::from(trials - k)))
+            }
+            Distribution::Poisson { rate } => {
+                let k = match count(x) {
+                    Some(k) => k,
+                    None => return Ok(Decimal::ZERO),
+                };
+                let rate = mathlib::at_precision(rate, work);
+                let factorial = special::gamma(&Decimal::from(k + 1), work)?;
+                Ok(rate.powi(IBig::from(k)) * mathlib::exp(&-rate, work)?
+                    / mathlib::at_precision(&factorial, work))
+            }
+            Distribution::Exponential { rate } => {
+                if x.sign() == Sign::Negative {
+                    return Ok(Decimal::ZERO);
+                }
+                let rate = mathlib::at_precision(rate, work);
+                Ok(rate.clone() * mathlib::exp(&-(rate * x), work)?)
+            }
+            Distribution::Uniform { low, high } => {
+                if x < low || x > high {
+                    return Ok(Decimal::ZERO);
+                }
+                Ok(one / (mathlib::at_precision(high, work) - low))
+            }
+        }
+    }
+
+    /// The probability of a value at most `x`.
+    fn cdf(&self, x: &Decimal, work: usize) -> Result<Decimal, BcError> {
+        let one = number(1, work);
+        match self {
+            Distribution::Normal { mean, sd } => {
+                let z = (x.clone() - mean) / mathlib::at_precision(sd, work);
+                let root = mathlib::sqrt(&number(2, work), work)?;
+                Ok(special::erfc(&(-z / root), work)? / Decimal::from(2))
+            }
+            Distribution::StudentT { df } => {
+                if x.is_zero() {
+                    return Ok(one / Decimal::from(2));
+                }
+                let df = mathlib::at_precision(df, work);
+                let share = df.clone() / (df.clone() + x.clone() * x);
+                let half = one.clone() / Decimal::from(2);
+                let tail = special::betainc(&(df / Decimal::from(2)), &half, &share, work)?
+                    / Decimal::from(2);
+                Ok(if x.sign() == Sign::Negative {
+                    tail
+                } else {
+                    one - tail
+                })
+            }
+            Distribution::ChiSquared { df } => {
+                if x.sign() == Sign::Negative || x.is_zero() {
+                    return Ok(Decimal::ZERO);
+                }
+                let half = mathlib::at_precision(df, work) / Decimal::from(2);
+                special::gammainc(&half, &(x.clone() / Decimal::from(2)), work)
+            }
+            Distribution::Binomial { trials, p } => {
+                if x.sign() == Sign::Negative {
+                    return Ok(Decimal::ZERO);
+                }
+                let k = x.floor();
+                if k >= Decimal::from(*trials) {
+                    return Ok(one);
+                }
+                let miss = one.clone() - mathlib::at_precision(p, work);
+                let rest = mathlib::at_precision(&(Decimal::from(*trials) - &k), work);
+                special::betainc(&rest, &(k + one), &miss, work)
+            }
+            Distribution::Poisson { rate } => {
+                if x.sign() == Sign::Negative {
+                    return Ok(Decimal::ZERO);
+                }
+                let k = mathlib::at_precision(&(x.floor() + Decimal::ONE), work);
+                special::gammaincc(&k, &mathlib::at_precision(rate, work), work)
+            }
+            Distribution::Exponential { rate } => {
+                if x.sign() == Sign::Negative || x.is_zero() {
+                    return Ok(Decimal::ZERO);
+                }
+                let rate = mathlib::at_precision(rate, work);
+                Ok(-mathlib::expm1(&-(rate * x), work)?)
+            }
+            Distribution::Uniform { low, high } => Ok(if x <= low {
+                Decimal::ZERO
+            } else if x >= high {
+                one
+            } else {
+                (x.clone() - low) / (mathlib::at_precision(high, work) - low)
+            }),
+        }
+    }
+
+    /// The smallest value whose cumulative probability reaches `p`.
+    fn quantile(
+        &self,
+        name: &str,
+        p: &Decimal,
+        precision: usize,
+        work: usize,
+    ) -> Result<Decimal, BcError> {
+        let one = number(1, work);
+        let infinite = |end: &str| Err(BcError::Error(format!("{name}() is infinite at {end}")));
+        let unbounded = matches!(
+            self,
+            Distribution::Normal { .. } | Distribution::StudentT { .. }
+        );
+        if *p == one
+            && !matches!(
+                self,
+                Distribution::Binomial { .. } | Distribution::Uniform { .. }
+            )
+        {
+            return infinite("p = 1");
+        }
+        if p.is_zero() && unbounded {
+            return infinite("p = 0");
+        }
+        match self {
+            Distribution::Normal { mean, sd } => {
+                let standard = Distribution::Normal {
+                    mean: Decimal::ZERO,
+                    sd: Decimal::ONE,
+                };
+                let z = standard.solve(p, None, precision, work)?;
+                Ok(mathlib::at_precision(mean, work) + mathlib::at_precision(sd, work) * z)
+            }
+            Distribution::StudentT { .. } => self.solve(p, None, precision, work),
+            Distribution::ChiSquared { df } => {
+                if p.is_zero() {
+                    return Ok(Decimal::ZERO);
+                }
+                self.solve(p, Some(df), precision, work)
+            }
+            Distribution::Binomial { trials, .. } => self.search(p, Some(*trials), precision, work),
+            Distribution::Poisson { .. } => self.search(p, None, precision, work),
+            Distribution::Exponential { rate } => {
+                let rate = mathlib::at_precision(rate, work);
+                Ok(-mathlib::ln(&(one - p), work)? / rate)
+            }
+            Distribution::Uniform { low, high } => {
+                let low = mathlib::at_precision(low, work);
+                Ok(low.clone() + p.clone() * (mathlib::at_precision(high, work) - low))
+            }
+        }
+    }
+
+    /// Inverts a continuous cdf by Newton's method, falling back to
+    /// bisection whenever a step leaves the bracket around the root. A
+    /// `start` marks a family that lives on the positive half-line.
+    fn solve(
+        &self,
+        p: &Decimal,
+        start: Option<&Decimal>,
+        precision: usize,
+        work: usize,
+    ) -> Result<Decimal, BcError> {
+        let tolerance = mathlib::epsilon(precision);
+        let (mut low, mut high) = match start {
+            Some(start) => (Decimal::ZERO, mathlib::at_precision(start, work)),
+            None => (number(-1, work), number(1, work)),
+        };
+        for _ in 0..MAX_DOUBLINGS {
+            if start.is_some() || self.cdf(&low, work)? <= *p {
+                break;
+            }
+            low *= Decimal::from(2);
+        }
+        for _ in 0..MAX_DOUBLINGS {
+            if self.cdf(&high, work)? >= *p {
+                break;
+            }
+            high *= Decimal::from(2);
+        }
+        let mut x = (low.clone() + &high) / Decimal::from(2);
+        for _ in 0..MAX_STEPS + 4 * precision {
+            let error = self.cdf(&x, work)? - p;
+            if error.is_zero() {
+                return Ok(x);
+            }
+            if error.sign() == Sign::Positive {
+                high = x.clone();
+            } else {
+                low = x.clone();
+            }
+            let slope = self.density(&x, work)?;
+            let mut next = if slope.is_zero() {
+                high.clone()
+            } else {
+                x.clone() - error / slope
+            };
+            if next <= low || next >= high {
+                next = (low.clone() + &high) / Decimal::from(2);
+            }
+            if (next.clone() - &x).abs() <= tolerance {
+                return Ok(next);
+            }
+            x = next;
+        }
+        Err(BcError::Error(
+            "Quantile search did not converge".to_string(),
+        ))
+    }
+
+    /// The smallest count whose cdf reaches `p`, by bisection up to `upper`,
+    /// or up to a doubled guess when the family has no largest value.
+    fn search(
+        &self,
+        p: &Decimal,
+        upper: Option<u64>,
+        precision: usize,
+        work: usize,
+    ) -> Result<Decimal, BcError> {
+        // Allow for rounding in the cdf so that exact probabilities such as
+        // binocdf(1, 2, 0.5) = 0.75 invert to the count that produced them.
+        let target = p.clone() - mathlib::epsilon(precision);
+        let reaches = |k: u64| -> Result<bool, BcError> {
+            Ok(self.cdf(&mathlib::at_precision(&Decimal::from(k), work), work)? >= target)
+        };
+        let mut high = match upper {
+            Some(upper) => upper,
+            None => {
+                let mut high = 1u64;
+                while !reaches(high)? {
+                    high = high.checked_mul(2).ok_or_else(|| {
+                        BcError::Error("Quantile search did not converge".to_string())
+                    })?;
+                }
+                high
+            }
+        };
+        let mut low = 0;
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if reaches(middle)? {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        Ok(Decimal::from(low))
+    }
+}
+
+/// How often a quantile bracket may double before the search gives up.
+const MAX_DOUBLINGS: usize = 400;
+/// Newton steps allowed on top of four bisections per digit of precision.
+const MAX_STEPS: usize = 200;
+
+/// `x` as a count of events, or `None` when it is negative or fractional.
+fn count(x: &Decimal) -> Option<u64> {
+    if x.sign() == Sign::Negative || x.trunc() != *x {
+        return None;
+    }
+    x.to_u64()
+}
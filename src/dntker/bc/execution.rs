@@ -1,4 +1,5 @@
 use std::fmt;
+use std::io::BufRead;
 use std::str::FromStr;
 
 use dashu::base::{Abs, Sign};
@@ -9,13 +10,22 @@ use num_traits::{ToPrimitive, Zero};
 use super::complex::ComplexNumber;
 use super::error::BcError;
 use super::literals::LiteralTable;
-use super::runtime::{FunctionDef, Runtime, StatementOutcome};
+use super::number::{split_radix_prefix, BcNumber};
+use super::runtime::{
+    AngleUnit, FunctionDef, IntervalMode, Locale, Notation, Param, RationalMode, RoundingMode,
+    Runtime, StatementOutcome, WordType,
+};
 use super::util;
 
 pub struct BcExecuter {
     pub(crate) parser: Parser,
     pub(crate) runtime: Runtime,
     pub(crate) literals: LiteralTable,
+    pub(super) last_result: Option<BcNumber>,
+    /// What `print` and string statements wrote during the current `exec`.
+    pub(super) printed: String,
+    /// Where `read()` takes its lines from; it fails when `None`.
+    pub(super) input: Option<Box<dyn BufRead>>,
 }
 
 impl fmt::Debug for BcExecuter {
@@ -27,10 +37,38 @@ impl fmt::Debug for BcExecuter {
 impl Default for BcExecuter {
     fn default() -> Self {
         let scale = util::DNTK_OPT.scale as u32;
+        let mut runtime = Runtime::with_defaults(scale);
+        runtime.set_posix_scale(util::DNTK_OPT.posix_scale);
+        if util::DNTK_OPT.rational {
+            runtime.set_rational(RationalMode::Fraction);
+        }
+        if util::DNTK_OPT.interval {
+            runtime.set_interval(IntervalMode::Bounds);
+        }
+        if let Some(mode) = RoundingMode::from_name(&util::DNTK_OPT.round) {
+            runtime.set_round(mode);
+        }
+        if let Some(notation) = Notation::from_name(&util::DNTK_OPT.notation) {
+            runtime.set_notation(notation);
+        }
+        if let Some(locale) = Locale::from_name(&util::DNTK_OPT.locale) {
+            runtime.set_locale(locale);
+        }
+        if let Some(angle) = AngleUnit::from_name(&util::DNTK_OPT.angle) {
+            runtime.set_angle(angle);
+        }
+        if let Some(word) = util::DNTK_OPT.word.as_deref().and_then(WordType::from_name) {
+            runtime.set_word(word.bits);
+            runtime.set_signed(word.signed);
+        }
+        runtime.set_multibase(util::DNTK_OPT.multibase);
         BcExecuter {
             parser: Parser::new(),
-            runtime: Runtime::with_defaults(scale),
+            runtime,
             literals: LiteralTable::default(),
+            last_result: None,
+            printed: String::new(),
+            input: None,
         }
     }
 }
@@ -38,23 +76,34 @@ impl Default for BcExecuter {
 impl BcExecuter {
     pub fn exec(&mut self, statement: &str) -> Result<String, BcError> {
         let trimmed = statement.trim();
+        self.last_result = None;
+        self.printed.clear();
         if trimmed.is_empty() {
             return Err(BcError::NoResult);
         }
         if trimmed == "limits" {
             return Ok(self.show_limits());
         }
-        if let Some(result) = self.try_eval_complex_expression(trimmed)? {
-            return Ok(result);
-        }
-        if let Some(result) = self.try_eval_matrix_expression(trimmed)? {
-            return Ok(result);
-        }
 
         let statements = self.split_statements(trimmed);
-        let mut last_value: Option<Decimal> = None;
+        let mut last_value: Option<BcNumber> = None;
+        // Whether bc would show the value of the last statement.
+        let mut shown = false;
 
         for stmt in statements {
+            shown = false;
+            // Rewritten per statement, so that a `locale=` assignment
+            // already applies to the statements after it.
+            let localized;
+            let locale = self.runtime.locale();
+            let stmt = if let Some(group) =
+                locale.group_separator().filter(|_| locale.decimal_comma())
+            {
+                localized = Self::rewrite_decimal_commas(&Self::strip_digit_groups(stmt, group));
+                localized.as_str()
+            } else {
+                stmt
+            };
             match self.eval_statement(stmt)? {
                 StatementOutcome::Return(value) => {
                     last_value = Some(value);
@@ -62,15 +111,46 @@ impl BcExecuter {
                 }
                 StatementOutcome::Value(value) => {
                     last_value = Some(value);
+                    shown = Self::shows_value(stmt);
                 }
                 StatementOutcome::None => {}
             }
         }
 
-        let value = last_value.unwrap_or(Decimal::ZERO);
+        if !self.printed.is_empty() {
+            let shown = last_value.filter(|_| shown);
+            return Ok(self.printed_output(shown));
+        }
+        let value = last_value.unwrap_or_else(BcNumber::zero);
+        self.last_result = Some(value.clone());
         Ok(self.format_result(value))
     }
 
+    /// Lets `read()` take lines from `input`. Only piped or file input
+    /// should be attached: the live preview would block on a terminal.
+    pub fn attach_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = Some(input);
+    }
+
+    /// The last result as `[dec, hex, oct, bin]` when `multibase` is on and
+    /// it is an integer; the binary form is grouped by nibble.
+    pub fn base_views(&self) -> Option<Vec<String>> {
+        if !self.runtime.multibase() {
+            return None;
+        }
+        let value = self.last_result.as_ref()?;
+        if value.bounds.is_some()
+            || value.uncertainty.is_some()
+            || value.unit.is_some()
+            || value.time.is_some()
+            || value.address.is_some()
+            || !value.to_rational().is_int()
+        {
+            return None;
+        }
+        Some(self.format_bases(&value.units(0)))
+    }
+
     fn eval_statement(&mut self, stmt: &str) -> Result<StatementOutcome, BcError> {
         let trimmed = stmt.trim();
         if trimmed.is_empty() {
@@ -107,13 +187,46 @@ impl BcExecuter {
         if Self::starts_with_keyword(trimmed, "for") {
             return self.eval_for_loop(trimmed);
         }
+        if Self::starts_with_keyword(trimmed, "print") {
+            self.eval_print(trimmed)?;
+            return Ok(StatementOutcome::None);
+        }
+        if trimmed.starts_with('"') {
+            self.eval_string(trimmed)?;
+            return Ok(StatementOutcome::None);
+        }
+        if Self::starts_with_keyword(trimmed, "auto") {
+            self.eval_auto(trimmed)?;
+            return Ok(StatementOutcome::None);
+        }
+
+        if let Some(text) = self.try_eval_complex_expression(trimmed)? {
+            self.write_line(&text);
+            return Ok(StatementOutcome::None);
+        }
+        if let Some(text) = self.try_eval_matrix_expression(trimmed)? {
+            self.write_line(&text);
+            return Ok(StatementOutcome::None);
+        }
+        if let Some(text) = self.try_eval_factorization(trimmed)? {
+            self.write_line(&text);
+            return Ok(StatementOutcome::None);
+        }
+        if let Some(text) = self.try_eval_float_inspection(trimmed)? {
+            self.write_line(&text);
+            return Ok(StatementOutcome::None);
+        }
+        if let Some(text) = self.try_eval_amortization(trimmed)? {
+            self.write_line(&text);
+            return Ok(StatementOutcome::None);
+        }
 
         if let Some((name, expr)) = Self::detect_assignment(trimmed) {
             let value = self.eval_assignment(name, expr)?;
             return Ok(StatementOutcome::value(value));
         }
 
-        let value = self.eval_expression(trimmed)?;
+        let value = self.eval_number(trimmed)?;
         Ok(StatementOutcome::value(value))
     }
 
@@ -288,14 +401,14 @@ impl BcExecuter {
     fn eval_return(&mut self, stmt: &str) -> Result<StatementOutcome, BcError> {
         let rest = stmt.trim_start()["return".len()..].trim_start();
         if rest.is_empty() {
-            return Ok(StatementOutcome::ret(Decimal::ZERO));
+            return Ok(StatementOutcome::ret(BcNumber::zero()));
         }
         let expression = if rest.starts_with('(') && rest.ends_with(')') {
             &rest[1..rest.len() - 1]
         } else {
             rest
         };
-        let value = self.eval_expression(expression)?;
+        let value = self.eval_number(expression)?;
         Ok(StatementOutcome::ret(value))
     }
 
@@ -319,13 +432,13 @@ impl BcExecuter {
         }
         let params_end = Self::find_matching(rest, 0, '(', ')')?;
         let params_str = &rest[1..params_end];
-        let params: Vec<String> = if params_str.trim().is_empty() {
+        let params: Vec<Param> = if params_str.trim().is_empty() {
             Vec::new()
         } else {
             params_str
                 .split(',')
-                .map(|p| p.trim().to_string())
-                .collect()
+                .map(|p| Self::parse_param(p.trim()))
+                .collect::<Result<_, _>>()?
         };
         rest = rest[params_end + 1..].trim_start();
 
@@ -360,15 +473,20 @@ impl BcExecuter {
         }
     }
 
-    fn eval_assignment(&mut self, name: &str, expr: &str) -> Result<Decimal, BcError> {
+    fn eval_assignment(&mut self, name: &str, expr: &str) -> Result<BcNumber, BcError> {
+        if let Some((array, index)) = Self::split_element(name) {
+            return self.assign_element(array, index, expr);
+        }
         if !Self::is_valid_identifier(name) {
             return Err(BcError::Error(format!("Invalid identifier: {name}")));
         }
+        Self::ensure_not_constant(name)?;
 
-        let value = self.eval_expression(expr)?;
-        if self.apply_special_assignment(name, &value)? {
+        let value = self.eval_number(expr)?;
+        if self.apply_special_assignment(name, &value.value)? {
             return Ok(value);
         }
+        let value = self.round_for_assignment(value);
 
         if let Some(scope) = self.runtime.find_scope_mut(name) {
             scope.insert(name.to_string(), value.clone());
@@ -390,28 +508,135 @@ impl BcExecuter {
                 self.runtime.set_scale(new_scale);
                 Ok(true)
             }
+            "ibase" => {
+                if value.sign() == Sign::Negative {
+                    return Err(BcError::Error("ibase must be positive".to_string()));
+                }
+                let new_ibase = ToPrimitive::to_u32(&value.trunc())
+                    .ok_or_else(|| BcError::Error("ibase out of range".to_string()))?;
+                if !(2..=36).contains(&new_ibase) {
+                    return Err(BcError::Error("ibase must be between 2 and 36".to_string()));
+                }
+                self.runtime.set_ibase(new_ibase);
+                Ok(true)
+            }
             "obase" => {
                 if value.sign() == Sign::Negative {
                     return Err(BcError::Error("obase must be positive".to_string()));
                 }
                 let new_obase = ToPrimitive::to_u32(&value.trunc())
                     .ok_or_else(|| BcError::Error("obase out of range".to_string()))?;
-                if !(2..=36).contains(&new_obase) {
-                    return Err(BcError::Error("obase must be between 2 and 36".to_string()));
+                if new_obase < 2 {
+                    return Err(BcError::Error("obase must be at least 2".to_string()));
                 }
                 self.runtime.set_obase(new_obase);
                 Ok(true)
             }
+            "rational" => {
+                let mode = ToPrimitive::to_u32(&value.trunc())
+                    .and_then(RationalMode::from_level)
+                    .ok_or_else(|| BcError::Error("rational must be 0, 1 or 2".to_string()))?;
+                self.runtime.set_rational(mode);
+                Ok(true)
+            }
+            "interval" => {
+                let mode = ToPrimitive::to_u32(&value.trunc())
+                    .and_then(IntervalMode::from_level)
+                    .ok_or_else(|| BcError::Error("interval must be 0, 1 or 2".to_string()))?;
+                self.runtime.set_interval(mode);
+                Ok(true)
+            }
+            "round" => {
+                let mode = ToPrimitive::to_u32(&value.trunc())
+                    .and_then(RoundingMode::from_level)
+                    .ok_or_else(|| BcError::Error("round must be between 0 and 4".to_string()))?;
+                self.runtime.set_round(mode);
+                Ok(true)
+            }
+            "notation" => {
+                let notation = ToPrimitive::to_u32(&value.trunc())
+                    .and_then(Notation::from_level)
+                    .ok_or_else(|| {
+                        BcError::Error("notation must be between 0 and 3".to_string())
+                    })?;
+                self.runtime.set_notation(notation);
+                Ok(true)
+            }
+            "locale" => {
+                let locale = ToPrimitive::to_u32(&value.trunc())
+                    .and_then(Locale::from_level)
+                    .ok_or_else(|| BcError::Error("locale must be between 0 and 4".to_string()))?;
+                self.runtime.set_locale(locale);
+                Ok(true)
+            }
+            "angle" => {
+                let angle = ToPrimitive::to_u32(&value.trunc())
+                    .and_then(AngleUnit::from_level)
+                    .ok_or_else(|| BcError::Error("angle must be 0, 1 or 2".to_string()))?;
+                self.runtime.set_angle(angle);
+                Ok(true)
+            }
+            "word" => {
+                let bits = ToPrimitive::to_u32(&value.trunc())
+                    .filter(|bits| *bits == 0 || WordType::WIDTHS.contains(bits))
+                    .ok_or_else(|| {
+                        BcError::Error("word must be 0, 8, 16, 32, 64 or 128".to_string())
+                    })?;
+                self.runtime.set_word(bits);
+                Ok(true)
+            }
+            "multibase" => {
+                let enabled = match ToPrimitive::to_u32(&value.trunc()) {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(BcError::Error("multibase must be 0 or 1".to_string())),
+                };
+                self.runtime.set_multibase(enabled);
+                Ok(true)
+            }
+            "signed" => {
+                let signed = match ToPrimitive::to_u32(&value.trunc()) {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(BcError::Error("signed must be 0 or 1".to_string())),
+                };
+                self.runtime.set_signed(signed);
+                Ok(true)
+            }
             _ => Ok(false),
         }
     }
 
+    /// Under any rounding mode other than bc's truncation, stored values are
+    /// rounded to `scale` digits. Exact, interval and measured values keep
+    /// what they carry, as do dates, durations and addresses, and quantities
+    /// keep their unit.
+    pub(super) fn round_for_assignment(&self, number: BcNumber) -> BcNumber {
+        let mode = self.runtime.round();
+        let scale = self.runtime.scale();
+        if mode == RoundingMode::Truncate
+            || number.scale <= scale
+            || number.exact.is_some()
+            || number.bounds.is_some()
+            || number.uncertainty.is_some()
+            || number.time.is_some()
+            || number.address.is_some()
+        {
+            return number;
+        }
+        let rounded = Self::round_decimal_to_scale(&number.value, scale, mode);
+        BcNumber {
+            unit: number.unit,
+            ..BcNumber::new(rounded, scale)
+        }
+    }
+
     pub(super) fn format_complex_result(&self, real: Decimal, imag: Decimal) -> String {
         if imag.is_zero() {
-            return self.format_result_decimal(&real);
+            return self.localize(&self.format_result_decimal(&real));
         }
         let imag_abs = imag.clone().abs();
-        let imag_str = self.format_result_decimal(&imag_abs);
+        let imag_str = self.localize(&self.format_result_decimal(&imag_abs));
         if real.is_zero() {
             if imag.sign() == Sign::Negative {
                 format!("-{imag_str}i")
@@ -419,7 +644,7 @@ impl BcExecuter {
                 format!("{imag_str}i")
             }
         } else {
-            let real_str = self.format_result_decimal(&real);
+            let real_str = self.localize(&self.format_result_decimal(&real));
             let sign = if imag.sign() == Sign::Negative {
                 "-"
             } else {
@@ -434,15 +659,9 @@ impl BcExecuter {
         for row in matrix {
             let formatted: Vec<String> = row
                 .iter()
-                .map(|value| {
-                    if value.imag.is_zero() {
-                        self.format_result_decimal(&value.real)
-                    } else {
-                        self.format_complex_result(value.real.clone(), value.imag.clone())
-                    }
-                })
+                .map(|value| self.format_complex_result(value.real.clone(), value.imag.clone()))
                 .collect();
-            rows.push(format!("[{}]", formatted.join(", ")));
+            rows.push(format!("[{}]", formatted.join(self.list_separator())));
         }
         format!("[{}]", rows.join("; "))
     }
@@ -629,14 +848,28 @@ impl BcExecuter {
         match self.parse_complex_literal(entry) {
             Ok(Some(value)) => Ok(value),
             Ok(None) => {
-                let decimal = Decimal::from_str(entry)
-                    .map_err(|_| BcError::Error("matrix entry is not a number".to_string()))?;
+                let decimal = self
+                    .parse_matrix_scalar(entry)
+                    .ok_or_else(|| BcError::Error("matrix entry is not a number".to_string()))?;
                 Ok(ComplexNumber::from_real(decimal))
             }
             Err(error) => Err(BcError::Error(error.into_message())),
         }
     }
 
+    fn parse_matrix_scalar(&self, entry: &str) -> Option<Decimal> {
+        let ibase = self.runtime.ibase();
+        let (negative, digits) = match entry.strip_prefix('-') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, entry),
+        };
+        if ibase == 10 && split_radix_prefix(digits).is_none() {
+            return Decimal::from_str(entry).ok();
+        }
+        let value = BcNumber::parse_in_base(digits, ibase).ok()?.value;
+        Some(if negative { -value } else { value })
+    }
+
     fn skip_whitespace(input: &str, mut index: usize) -> usize {
         while index < input.len() {
             let ch = input[index..].chars().next().unwrap();
@@ -1,28 +1,92 @@
-use std::collections::BTreeMap;
-
-use dashu::base::{Abs, Sign};
+use dashu::base::{Abs, BitTest, Sign};
 use dashu::Decimal;
 use fasteval::compiler::{Compiler, Instruction, InstructionI, IC};
 use fasteval::slab::CompileSlab;
 use fasteval::Slab;
-use libm::jn;
 use num_traits::{ToPrimitive, Zero};
 use rand::{Rng, RngCore};
 
+use super::arrays::Argument;
+use super::distributions;
 use super::error::BcError;
-use super::runtime::StatementOutcome;
+use super::finance::FINANCE;
+use super::ieee754::{self, FloatFormat};
+use super::mathlib::{self, UnaryFn};
+use super::number::BcNumber;
+use super::numtheory;
+use super::runtime::{AngleUnit, IntervalMode, RationalMode, StatementOutcome};
+use super::special;
+use super::statistics::STATISTICS;
+
+/// Largest number of significant digits an integer power is computed to exactly.
+pub(super) const EXACT_POWER_DIGITS: usize = 100_000;
+
+/// What the exact evaluators answer for an integer power whose result would
+/// have more than `EXACT_POWER_DIGITS` digits.
+pub(super) fn power_too_large() -> BcError {
+    BcError::Error("Power result is too large".to_string())
+}
 
 impl super::BcExecuter {
     pub(super) fn eval_expression(&mut self, expr: &str) -> Result<Decimal, BcError> {
+        Ok(self.eval_number(expr)?.value)
+    }
+
+    pub(super) fn eval_number(&mut self, expr: &str) -> Result<BcNumber, BcError> {
         let trimmed = expr.trim();
+        let ibase = self.runtime.ibase();
 
-        if let Ok(decimal_value) = trimmed.parse::<Decimal>() {
-            return Ok(self.promote_precision(decimal_value));
+        if ibase == 10 && self.runtime.word().is_none() {
+            if let Some(literal) = BcNumber::parse_literal(trimmed) {
+                let value = self.promote_precision(literal.value);
+                return Ok(BcNumber::new(value, literal.scale));
+            }
         }
 
-        self.literals.reset();
+        if trimmed.contains('"') {
+            return Err(BcError::Error(
+                "Strings can only be printed, not used as values".to_string(),
+            ));
+        }
         let processed = self.preprocess_bc_syntax(expr);
-        let substituted = self.literals.substitute(&processed)?;
+        if self.needs_integer_evaluation() {
+            return self.eval_integer_expression(&processed);
+        }
+        if self.mentions_network(&processed) {
+            return self.eval_network_expression(&processed);
+        }
+        if self.mentions_time(&processed) {
+            return self.eval_time_expression(&processed);
+        }
+        if self.mentions_units(&processed) {
+            return self.eval_unit_expression(&processed);
+        }
+        if self.runtime.rational() != RationalMode::Off {
+            return self.eval_rational_expression(&processed);
+        }
+        if self.runtime.interval() != IntervalMode::Off {
+            return self.eval_interval_expression(&processed);
+        }
+        if self.mentions_uncertain(&processed) {
+            return self.eval_uncertain_expression(&processed);
+        }
+        if self.runtime.posix_scale() {
+            return self.eval_posix_expression(&processed);
+        }
+        // fasteval has neither subscripts nor bitwise operators, and on plain
+        // numbers the unit evaluator gives the same results.
+        if self.mentions_arrays(&processed) || self.mentions_bitwise(&processed) {
+            return self.eval_unit_expression(&processed);
+        }
+
+        if ibase == 10 {
+            if let Ok(decimal_value) = trimmed.parse::<Decimal>() {
+                return Ok(BcNumber::natural(self.promote_precision(decimal_value)));
+            }
+        }
+
+        self.literals.reset();
+        let substituted = self.literals.substitute(&processed, ibase)?;
         let mut slab = Slab::new();
         let expr_idx = self
             .parser
@@ -32,7 +96,7 @@ impl super::BcExecuter {
         let instruction = expression.compile(&slab.ps, &mut slab.cs);
         let value = self.eval_instruction(&instruction, &slab.cs)?;
         self.literals.reset();
-        Ok(self.promote_precision(value))
+        Ok(BcNumber::natural(self.promote_precision(value)))
     }
 
     fn eval_instruction(
@@ -156,7 +220,7 @@ impl super::BcExecuter {
             Instruction::IFuncLog { base, of } => {
                 let base_val = self.eval_ic(base, compile_slab)?;
                 let value = self.eval_ic(of, compile_slab)?;
-                Self::builtin_log_decimal(&base_val, &value)
+                self.builtin_log_decimal(&base_val, &value)
             }
             Instruction::IFuncRound { modulus, of } => {
                 let modulus_val = self.eval_ic(modulus, compile_slab)?;
@@ -186,44 +250,50 @@ impl super::BcExecuter {
                 Ok(current)
             }
             Instruction::IFuncSin(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::sin, "sin")
+                let value = self.eval_instruction_index(*idx, compile_slab)?;
+                self.precise_trig("sin", &[value], mathlib::sin)
             }
             Instruction::IFuncCos(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::cos, "cos")
+                let value = self.eval_instruction_index(*idx, compile_slab)?;
+                self.precise_trig("cos", &[value], mathlib::cos)
             }
             Instruction::IFuncTan(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::tan, "tan")
+                let value = self.eval_instruction_index(*idx, compile_slab)?;
+                self.precise_trig("tan", &[value], mathlib::tan)
             }
             Instruction::IFuncASin(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::asin, "asin")
+                let value = self.eval_instruction_index(*idx, compile_slab)?;
+                self.precise_inverse_trig("asin", &[value], mathlib::asin)
             }
             Instruction::IFuncACos(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::acos, "acos")
+                let value = self.eval_instruction_index(*idx, compile_slab)?;
+                self.precise_inverse_trig("acos", &[value], mathlib::acos)
             }
             Instruction::IFuncATan(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::atan, "atan")
+                let value = self.eval_instruction_index(*idx, compile_slab)?;
+                self.precise_inverse_trig("atan", &[value], mathlib::atan)
             }
             Instruction::IFuncSinH(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::sinh, "sinh")
+                self.eval_math_function(*idx, compile_slab, mathlib::sinh, "sinh")
             }
             Instruction::IFuncCosH(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::cosh, "cosh")
+                self.eval_math_function(*idx, compile_slab, mathlib::cosh, "cosh")
             }
             Instruction::IFuncTanH(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::tanh, "tanh")
+                self.eval_math_function(*idx, compile_slab, mathlib::tanh, "tanh")
             }
             Instruction::IFuncASinH(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::asinh, "asinh")
+                self.eval_math_function(*idx, compile_slab, mathlib::asinh, "asinh")
             }
             Instruction::IFuncACosH(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::acosh, "acosh")
+                self.eval_math_function(*idx, compile_slab, mathlib::acosh, "acosh")
             }
             Instruction::IFuncATanH(idx) => {
-                self.eval_math_function(*idx, compile_slab, libm::atanh, "atanh")
-            }
-            Instruction::IPrintFunc(_) => {
-                Err(BcError::Error("print() is not supported".to_string()))
+                self.eval_math_function(*idx, compile_slab, mathlib::atanh, "atanh")
             }
+            Instruction::IPrintFunc(_) => Err(BcError::Error(
+                "print is a statement and cannot be used inside an expression".to_string(),
+            )),
         }?;
         Ok(self.promote_precision(value))
     }
@@ -248,43 +318,43 @@ impl super::BcExecuter {
         &mut self,
         idx: InstructionI,
         compile_slab: &CompileSlab,
-        func: impl Fn(f64) -> f64,
+        func: UnaryFn,
         name: &str,
     ) -> Result<Decimal, BcError> {
         let value = self.eval_instruction_index(idx, compile_slab)?;
-        let input = Self::decimal_to_f64(&value, &format!("{name}() argument out of range"))?;
-        let result = func(input);
-        self.decimal_from_f64(result, &format!("{name}() produced invalid result"))
+        self.precise_unary(name, &[value], func)
     }
 
-    fn power_decimal(&self, base: &Decimal, exponent: &Decimal) -> Result<Decimal, BcError> {
+    pub(super) fn power_decimal(
+        &self,
+        base: &Decimal,
+        exponent: &Decimal,
+    ) -> Result<Decimal, BcError> {
         if exponent.fract().is_zero() {
             let power = ToPrimitive::to_i64(&exponent.trunc())
                 .ok_or_else(|| BcError::Error("Exponent out of supported range".to_string()))?;
-            Ok(base.clone().powi(power.into()))
+            // Keep integer powers exact; past the bound they are refused,
+            // so a stray huge exponent in the live preview cannot stall the
+            // prompt. Roughly 10 bits of the significand make 3 digits.
+            let magnitude = power.unsigned_abs() as usize;
+            let bits = base.repr().significand().bit_len().saturating_sub(1);
+            if bits.saturating_mul(magnitude).saturating_mul(3) / 10 > EXACT_POWER_DIGITS {
+                return Err(power_too_large());
+            }
+            let digits = base.repr().digits().saturating_mul(magnitude);
+            let widened = mathlib::at_precision(base, self.working_precision(digits));
+            Ok(widened.powi(power.into()))
         } else {
-            let base_f = Self::decimal_to_f64(base, "Exponentiation base out of range")?;
-            let exponent_f = Self::decimal_to_f64(exponent, "Exponentiation power out of range")?;
-            let result = libm::pow(base_f, exponent_f);
-            self.decimal_from_f64(result, "Exponentiation produced invalid result")
+            self.eval_precise(&[base, exponent], |precision| {
+                mathlib::powf(base, exponent, precision)
+            })
         }
     }
 
-    fn builtin_log_decimal(base: &Decimal, value: &Decimal) -> Result<Decimal, BcError> {
-        let base_f = Self::decimal_to_f64(base, "log() base out of range")?;
-        let value_f = Self::decimal_to_f64(value, "log() expects positive argument")?;
-        if value_f <= 0.0 {
-            return Err(BcError::Error(
-                "log() expects positive argument".to_string(),
-            ));
-        }
-        if base_f <= 0.0 || (base_f - 1.0).abs() < f64::EPSILON {
-            return Err(BcError::Error(
-                "log() base must be positive and not equal to 1".to_string(),
-            ));
-        }
-        let result = libm::log(value_f) / libm::log(base_f);
-        Self::decimal_from_f64_static(result, "log() produced invalid result")
+    fn builtin_log_decimal(&self, base: &Decimal, value: &Decimal) -> Result<Decimal, BcError> {
+        self.eval_precise(&[base, value], |precision| {
+            mathlib::log(base, value, precision)
+        })
     }
 
     fn bool_to_decimal(value: bool) -> Decimal {
@@ -310,15 +380,14 @@ impl super::BcExecuter {
         }
     }
 
-    fn decimal_to_f64(value: &Decimal, err: &str) -> Result<f64, BcError> {
-        ToPrimitive::to_f64(value).ok_or_else(|| BcError::Error(err.to_string()))
-    }
-
     fn resolve_name(&mut self, name: &str, args: Vec<Decimal>) -> Result<Decimal, BcError> {
         if args.is_empty() {
             if let Some(value) = self.literals.get(name) {
                 return Ok(value);
             }
+            if let Some(value) = self.lookup_constant(name) {
+                return value;
+            }
             if let Some(value) = self.lookup_variable(name) {
                 return Ok(value);
             }
@@ -328,22 +397,37 @@ impl super::BcExecuter {
             return result;
         }
 
+        let args = args.into_iter().map(BcNumber::natural).collect();
         if let Some(func_value) = self.call_function(name, args)? {
-            return Ok(func_value);
+            return Ok(func_value.value);
         }
 
         Err(BcError::Error(format!("Undefined identifier: {name}")))
     }
 
     fn lookup_variable(&self, name: &str) -> Option<Decimal> {
-        self.runtime.get_variable(name)
+        self.runtime.get_variable(name).map(|number| number.value)
     }
 
-    fn call_function(
+    pub(super) fn call_function(
         &mut self,
         name: &str,
-        args: Vec<Decimal>,
-    ) -> Result<Option<Decimal>, BcError> {
+        args: Vec<BcNumber>,
+    ) -> Result<Option<BcNumber>, BcError> {
+        self.call_function_with(
+            name,
+            args.into_iter()
+                .map(|arg| Argument::Value(Box::new(arg)))
+                .collect(),
+        )
+    }
+
+    /// Runs the user function `name`, or gives `None` when there is none.
+    pub(super) fn call_function_with(
+        &mut self,
+        name: &str,
+        args: Vec<Argument>,
+    ) -> Result<Option<BcNumber>, BcError> {
         let def = match self.runtime.get_function(name) {
             Some(def) => def.clone(),
             None => return Ok(None),
@@ -358,71 +442,139 @@ impl super::BcExecuter {
             )));
         }
 
-        let mut local_scope = BTreeMap::new();
-        for (param, arg) in def.params.iter().zip(args.into_iter()) {
-            local_scope.insert(param.clone(), arg);
-        }
-
-        self.runtime.push_scope(local_scope);
+        self.bind_arguments(name, &def.params, args)?;
         let outcome = self.eval_block(def.body.iter().map(|s| s.as_str()))?;
         self.runtime.pop_scope();
 
         let result = match outcome {
             StatementOutcome::Return(value) | StatementOutcome::Value(value) => value,
-            StatementOutcome::None => Decimal::ZERO,
+            StatementOutcome::None => BcNumber::zero(),
         };
 
         Ok(Some(result))
     }
 
-    fn call_builtin_function(
+    pub(super) fn call_builtin_function(
         &mut self,
         name: &str,
         args: &[Decimal],
     ) -> Option<Result<Decimal, BcError>> {
+        if args.is_empty() {
+            if let Some(value) = self.lookup_constant(name) {
+                return Some(value);
+            }
+        }
         let result = match name {
             "length" => Some(Self::builtin_length(args)),
             "scale" => Some(Self::builtin_scale(args)),
-            "j" => Some(Self::builtin_bessel(args)),
+            "j" => Some(self.precise_binary("j", args, special::bessel_j)),
+            "y" => Some(self.precise_binary("y", args, special::bessel_y)),
             "rand" => Some(self.builtin_rand(args)),
             "srand" => Some(self.builtin_srand(args)),
-            "sqrt" => Some(Self::builtin_math_unary("sqrt", args, libm::sqrt)),
-            "cbrt" => Some(Self::builtin_math_unary("cbrt", args, libm::cbrt)),
+            "read" => Some(self.builtin_read(args)),
+            "sqrt" => Some(self.precise_unary("sqrt", args, mathlib::sqrt)),
+            "cbrt" => Some(self.precise_unary("cbrt", args, mathlib::cbrt)),
             "abs" => Some(Self::builtin_abs(args)),
             "sign" => Some(Self::builtin_sign(args)),
             "floor" => Some(Self::builtin_decimal_unary("floor", args, |v| v.floor())),
             "ceil" => Some(Self::builtin_decimal_unary("ceil", args, |v| v.ceil())),
             "trunc" => Some(Self::builtin_decimal_unary("trunc", args, |v| v.trunc())),
             "round" => Some(Self::builtin_decimal_unary("round", args, |v| v.round())),
-            "sin" => Some(Self::builtin_math_unary("sin", args, libm::sin)),
-            "cos" => Some(Self::builtin_math_unary("cos", args, libm::cos)),
-            "tan" => Some(Self::builtin_math_unary("tan", args, libm::tan)),
-            "asin" | "arcsin" => Some(Self::builtin_math_unary("asin", args, libm::asin)),
-            "acos" | "arccos" => Some(Self::builtin_math_unary("acos", args, libm::acos)),
-            "atan" | "arctan" => Some(Self::builtin_math_unary("atan", args, libm::atan)),
-            "atan2" => Some(Self::builtin_math_binary("atan2", args, libm::atan2)),
-            "sinh" => Some(Self::builtin_math_unary("sinh", args, libm::sinh)),
-            "cosh" => Some(Self::builtin_math_unary("cosh", args, libm::cosh)),
-            "tanh" => Some(Self::builtin_math_unary("tanh", args, libm::tanh)),
-            "asinh" => Some(Self::builtin_math_unary("asinh", args, libm::asinh)),
-            "acosh" => Some(Self::builtin_math_unary("acosh", args, libm::acosh)),
-            "atanh" => Some(Self::builtin_math_unary("atanh", args, libm::atanh)),
-            "exp" => Some(Self::builtin_math_unary("exp", args, libm::exp)),
-            "expm1" => Some(Self::builtin_math_unary("expm1", args, libm::expm1)),
-            "ln" => Some(Self::builtin_math_unary("ln", args, libm::log)),
-            "log" => Some(Self::builtin_log(args)),
-            "log10" => Some(Self::builtin_math_unary("log10", args, libm::log10)),
-            "log2" => Some(Self::builtin_math_unary("log2", args, libm::log2)),
-            "pow" => Some(Self::builtin_pow(args)),
-            "hypot" => Some(Self::builtin_math_binary("hypot", args, libm::hypot)),
+            "sin" => Some(self.precise_trig("sin", args, mathlib::sin)),
+            "cos" => Some(self.precise_trig("cos", args, mathlib::cos)),
+            "tan" => Some(self.precise_trig("tan", args, mathlib::tan)),
+            "asin" | "arcsin" => Some(self.precise_inverse_trig("asin", args, mathlib::asin)),
+            "acos" | "arccos" => Some(self.precise_inverse_trig("acos", args, mathlib::acos)),
+            "atan" | "arctan" => Some(self.precise_inverse_trig("atan", args, mathlib::atan)),
+            "atan2" => Some(self.precise_atan2(args)),
+            "deg" => Some(self.builtin_angle("deg", AngleUnit::Degrees, args)),
+            "rad" => Some(self.builtin_angle("rad", AngleUnit::Radians, args)),
+            "grad" => Some(self.builtin_angle("grad", AngleUnit::Gradians, args)),
+            "sinh" => Some(self.precise_unary("sinh", args, mathlib::sinh)),
+            "cosh" => Some(self.precise_unary("cosh", args, mathlib::cosh)),
+            "tanh" => Some(self.precise_unary("tanh", args, mathlib::tanh)),
+            "asinh" => Some(self.precise_unary("asinh", args, mathlib::asinh)),
+            "acosh" => Some(self.precise_unary("acosh", args, mathlib::acosh)),
+            "atanh" => Some(self.precise_unary("atanh", args, mathlib::atanh)),
+            "exp" => Some(self.precise_unary("exp", args, mathlib::exp)),
+            "expm1" => Some(self.precise_unary("expm1", args, mathlib::expm1)),
+            "ln" => Some(self.precise_unary("ln", args, mathlib::ln)),
+            "log" => Some(self.builtin_log(args)),
+            "log10" => Some(self.precise_unary("log10", args, mathlib::log10)),
+            "log2" => Some(self.precise_unary("log2", args, mathlib::log2)),
+            "pow" => Some(self.builtin_pow(args)),
+            "hypot" => Some(self.precise_binary("hypot", args, mathlib::hypot)),
             "min" => Some(Self::builtin_min(args)),
             "max" => Some(Self::builtin_max(args)),
+            _ if STATISTICS.contains(&name) => Some(self.builtin_statistic(name, args)),
+            _ if FINANCE.contains(&name) => Some(self.builtin_finance(name, args)),
+            _ if distributions::is_distribution(name) => {
+                Some(self.builtin_distribution(name, args))
+            }
+            "gamma" => Some(self.precise_unary("gamma", args, special::gamma)),
+            "lgamma" => Some(self.precise_unary("lgamma", args, special::lgamma)),
+            "erf" => Some(self.precise_unary("erf", args, special::erf)),
+            "erfc" => Some(self.precise_unary("erfc", args, special::erfc)),
+            "beta" => Some(self.precise_binary("beta", args, special::beta)),
+            "zeta" => Some(self.precise_unary("zeta", args, special::zeta)),
+            "gammainc" => Some(self.precise_binary("gammainc", args, special::gammainc)),
+            "gammaincc" => Some(self.precise_binary("gammaincc", args, special::gammaincc)),
+            "betainc" => Some(self.builtin_betainc(args)),
+            "gcd" => Some(numtheory::gcd(args)),
+            "lcm" => Some(numtheory::lcm(args)),
+            "factorial" => Some(numtheory::factorial(args)),
+            "binomial" | "nCr" => Some(numtheory::binomial(args)),
+            "nPr" => Some(numtheory::permutations(args)),
+            "modpow" => Some(numtheory::modpow(args)),
+            "modinv" => Some(numtheory::modinv(args)),
+            "isprime" => Some(numtheory::isprime(args)),
+            "nextprime" => Some(numtheory::nextprime(args)),
+            "totient" => Some(numtheory::totient(args)),
+            "factor" => Some(Err(BcError::Error(
+                "factor() can only be used as a statement of its own".to_string(),
+            ))),
+            "amort" => Some(Err(BcError::Error(
+                "amort() can only be used as a statement of its own".to_string(),
+            ))),
+            "f64bits" => Some(ieee754::bits(FloatFormat::Double, args)),
+            "f32bits" => Some(ieee754::bits(FloatFormat::Single, args)),
+            "f64frombits" => Some(ieee754::from_bits(FloatFormat::Double, args)),
+            "f32frombits" => Some(ieee754::from_bits(FloatFormat::Single, args)),
+            "f64exact" => Some(ieee754::exact(FloatFormat::Double, args)),
+            "f32exact" => Some(ieee754::exact(FloatFormat::Single, args)),
+            "f64decode" | "f32decode" => Some(Err(BcError::Error(format!(
+                "{name}() can only be used as a statement of its own"
+            )))),
             _ => None,
         };
 
         result.map(|res| res.map(|value| self.promote_precision(value)))
     }
 
+    /// `deg(x)`, `rad(x)` and `grad(x)` read `x` in that unit and give it
+    /// back in the current `angle` unit.
+    fn builtin_angle(
+        &self,
+        name: &str,
+        from: AngleUnit,
+        args: &[Decimal],
+    ) -> Result<Decimal, BcError> {
+        if args.len() != 1 {
+            return Err(BcError::Error(format!(
+                "{}() expects 1 argument, got {}",
+                name,
+                args.len()
+            )));
+        }
+        let to = self.runtime.angle();
+        if from == to {
+            return Ok(args[0].clone());
+        }
+        self.eval_precise(&[&args[0]], |precision| {
+            Ok(self.convert_angle(&args[0], from, to, precision))
+        })
+    }
+
     fn builtin_length(args: &[Decimal]) -> Result<Decimal, BcError> {
         if args.len() != 1 {
             return Err(BcError::Error(format!(
@@ -454,29 +606,16 @@ impl super::BcExecuter {
         }
     }
 
-    fn builtin_bessel(args: &[Decimal]) -> Result<Decimal, BcError> {
-        if args.len() != 2 {
+    fn builtin_betainc(&self, args: &[Decimal]) -> Result<Decimal, BcError> {
+        if args.len() != 3 {
             return Err(BcError::Error(format!(
-                "j() expects 2 arguments, got {}",
+                "betainc() expects 3 arguments, got {}",
                 args.len()
             )));
         }
-        let order = Self::decimal_to_f64(&args[0], "Bessel order out of range")?;
-        let rounded = order.round();
-        if (order - rounded).abs() > f64::EPSILON {
-            return Err(BcError::Error(
-                "Bessel function order must be an integer".to_string(),
-            ));
-        }
-        let order_int = rounded as i32;
-        let argument = Self::decimal_to_f64(&args[1], "Bessel argument out of range")?;
-        let value = jn(order_int, argument);
-        if !value.is_finite() {
-            return Err(BcError::Error(
-                "Bessel function produced a non-finite result".to_string(),
-            ));
-        }
-        Self::decimal_from_f64_static(value, "Failed to convert bessel result")
+        self.eval_precise(&[&args[0], &args[1], &args[2]], |precision| {
+            special::betainc(&args[0], &args[1], &args[2], precision)
+        })
     }
 
     fn builtin_rand(&mut self, args: &[Decimal]) -> Result<Decimal, BcError> {
@@ -522,41 +661,6 @@ impl super::BcExecuter {
         Ok(Decimal::from(seed))
     }
 
-    fn builtin_math_unary(
-        name: &str,
-        args: &[Decimal],
-        func: impl Fn(f64) -> f64,
-    ) -> Result<Decimal, BcError> {
-        if args.len() != 1 {
-            return Err(BcError::Error(format!(
-                "{}() expects 1 argument, got {}",
-                name,
-                args.len()
-            )));
-        }
-        let input = Self::decimal_to_f64(&args[0], &format!("{name}() argument out of range"))?;
-        let result = func(input);
-        Self::decimal_from_f64_static(result, &format!("{name}() produced invalid result"))
-    }
-
-    fn builtin_math_binary(
-        name: &str,
-        args: &[Decimal],
-        func: impl Fn(f64, f64) -> f64,
-    ) -> Result<Decimal, BcError> {
-        if args.len() != 2 {
-            return Err(BcError::Error(format!(
-                "{}() expects 2 arguments, got {}",
-                name,
-                args.len()
-            )));
-        }
-        let lhs = Self::decimal_to_f64(&args[0], &format!("{name}() argument out of range"))?;
-        let rhs = Self::decimal_to_f64(&args[1], &format!("{name}() argument out of range"))?;
-        let result = func(lhs, rhs);
-        Self::decimal_from_f64_static(result, &format!("{name}() produced invalid result"))
-    }
-
     fn builtin_decimal_unary(
         name: &str,
         args: &[Decimal],
@@ -582,54 +686,21 @@ impl super::BcExecuter {
         Ok(args[0].clone().abs())
     }
 
-    fn builtin_log(args: &[Decimal]) -> Result<Decimal, BcError> {
+    fn builtin_log(&self, args: &[Decimal]) -> Result<Decimal, BcError> {
         match args.len() {
-            1 => {
-                let value = Self::decimal_to_f64(&args[0], "log() expects positive input")?;
-                if value <= 0.0 {
-                    return Err(BcError::Error("log() expects positive input".to_string()));
-                }
-                let result = libm::log10(value);
-                Self::decimal_from_f64_static(result, "log() produced invalid result")
-            }
-            2 => {
-                let base = Self::decimal_to_f64(&args[0], "log() base out of range")?;
-                let value = Self::decimal_to_f64(&args[1], "log() expects positive argument")?;
-                if value <= 0.0 {
-                    return Err(BcError::Error(
-                        "log() expects positive argument".to_string(),
-                    ));
-                }
-                if base <= 0.0 || (base - 1.0).abs() < f64::EPSILON {
-                    return Err(BcError::Error(
-                        "log() base must be positive and not equal to 1".to_string(),
-                    ));
-                }
-                let result = libm::log(value) / libm::log(base);
-                Self::decimal_from_f64_static(result, "log() produced invalid result")
-            }
+            1 => self.precise_unary("log", args, mathlib::log10),
+            2 => self.builtin_log_decimal(&args[0], &args[1]),
             n => Err(BcError::Error(format!(
                 "log() expects 1 or 2 arguments, got {n}",
             ))),
         }
     }
 
-    fn builtin_pow(args: &[Decimal]) -> Result<Decimal, BcError> {
+    fn builtin_pow(&self, args: &[Decimal]) -> Result<Decimal, BcError> {
         if args.len() != 2 {
             return Err(BcError::Error("pow() expects 2 arguments".to_string()));
         }
-        let base = &args[0];
-        let exponent = &args[1];
-        if exponent.fract().is_zero() {
-            let power = ToPrimitive::to_i64(&exponent.trunc())
-                .ok_or_else(|| BcError::Error("pow() exponent out of range".to_string()))?;
-            Ok(base.clone().powi(power.into()))
-        } else {
-            let base_f = Self::decimal_to_f64(base, "pow() base out of range")?;
-            let exp_f = Self::decimal_to_f64(exponent, "pow() exponent out of range")?;
-            let result = libm::pow(base_f, exp_f);
-            Self::decimal_from_f64_static(result, "pow() produced invalid result")
-        }
+        self.power_decimal(&args[0], &args[1])
     }
 
     fn builtin_sign(args: &[Decimal]) -> Result<Decimal, BcError> {
@@ -0,0 +1,337 @@
+use std::convert::TryFrom;
+
+use dashu::base::{Abs, Sign};
+use dashu::integer::IBig;
+use dashu::rational::RBig;
+use dashu::Decimal;
+use num_traits::Zero;
+
+use super::complex::ComplexNumber;
+use super::error::BcError;
+use super::mathlib::{self, WORK_DIGITS};
+use super::numtheory::integer_arg;
+
+/// The builtins `call_builtin_function` hands to `builtin_finance`.
+pub(super) const FINANCE: [&str; 8] = ["pv", "fv", "pmt", "nper", "rate", "npv", "irr", "xnpv"];
+
+/// Longest schedule `amort` prints.
+const MAX_PERIODS: usize = 10_000;
+/// Secant steps `rate` and `irr` take before giving up.
+const MAX_STEPS: usize = 100;
+
+impl super::BcExecuter {
+    /// Time value of money with the spreadsheet conventions: money paid out
+    /// is negative, money received positive, and a trailing `type` of 1
+    /// moves payments to the start of each period. `pv`, `fv`, `pmt` and
+    /// `npv` are exact for whole numbers of periods.
+    pub(super) fn builtin_finance(&self, name: &str, args: &[Decimal]) -> Result<Decimal, BcError> {
+        match name {
+            "npv" => self.builtin_npv(args),
+            "irr" => self.builtin_irr(args),
+            "xnpv" => self.builtin_xnpv(args),
+            _ => self.builtin_time_value(name, args),
+        }
+    }
+
+    /// `amort(rate, nper, pv)`: one `[period, payment, interest, principal,
+    /// balance]` row per period. Payments and interest are rounded to
+    /// `scale` under the `round` mode, as a bank would to cents, and the
+    /// last payment absorbs the difference so that the balance ends at 0.
+    pub(super) fn try_eval_amortization(
+        &mut self,
+        statement: &str,
+    ) -> Result<Option<String>, BcError> {
+        let inner = match Self::strip_wrapped_function(statement, "amort")? {
+            Some(inner) => inner,
+            None => return Ok(None),
+        };
+        let parts = Self::split_top_level(inner, ',');
+        if parts.len() != 3 {
+            return Err(BcError::Error(format!(
+                "amort() expects 3 arguments, got {}",
+                parts.len()
+            )));
+        }
+        let mut values = Vec::with_capacity(3);
+        for part in parts {
+            values.push(self.eval_expression(part)?);
+        }
+        let rate = Self::decimal_to_rational(&values[0]);
+        let periods = usize::try_from(&integer_arg("amort", &values[1])?)
+            .ok()
+            .filter(|periods| (1..=MAX_PERIODS).contains(periods))
+            .ok_or_else(|| {
+                BcError::Error(format!("amort() needs between 1 and {MAX_PERIODS} periods"))
+            })?;
+        let principal = Self::decimal_to_rational(&values[2]);
+        let growth = growth(&rate, periods)?;
+        let payment =
+            self.round_money(&(principal.clone() * &growth / annuity(&rate, &growth, periods)));
+
+        let mut balance = principal;
+        let mut rows = Vec::with_capacity(periods);
+        for period in 1..=periods {
+            let interest = self.round_money(&(balance.clone() * &rate));
+            let paid = if period == periods {
+                balance.clone() + &interest
+            } else {
+                payment.clone()
+            };
+            let repaid = paid.clone() - &interest;
+            balance -= &repaid;
+            let row = [RBig::from(period), paid, interest, repaid, balance.clone()];
+            rows.push(
+                row.iter()
+                    .map(|value| ComplexNumber::new(self.rational_to_decimal(value), Decimal::ZERO))
+                    .collect(),
+            );
+        }
+        Ok(Some(self.format_matrix(&rows)))
+    }
+
+    /// `pv`, `fv`, `pmt`, `nper` and `rate`, each solving
+    /// `pv * (1 + r)^n + pmt * (1 + r * type) * ((1 + r)^n - 1) / r + fv = 0`
+    /// for one of its terms.
+    fn builtin_time_value(&self, name: &str, args: &[Decimal]) -> Result<Decimal, BcError> {
+        if !(3..=5).contains(&args.len()) {
+            return Err(BcError::Error(format!(
+                "{name}() expects 3 to 5 arguments, got {}",
+                args.len()
+            )));
+        }
+        let timing = match args.get(4).map(Self::decimal_to_rational) {
+            None => RBig::ZERO,
+            Some(timing) if timing.is_zero() || timing == RBig::ONE => timing,
+            Some(_) => return Err(BcError::Error(format!("{name}() type must be 0 or 1"))),
+        };
+        let fourth = args
+            .get(3)
+            .map(Self::decimal_to_rational)
+            .unwrap_or(RBig::ZERO);
+        match name {
+            "nper" => return self.solve_nper(&args[..3], &fourth, &timing),
+            "rate" => return self.solve_rate(&args[..3], &fourth, &timing),
+            _ => {}
+        }
+
+        let rate = Self::decimal_to_rational(&args[0]);
+        let periods = usize::try_from(&integer_arg(name, &args[1])?)
+            .map_err(|_| BcError::Error(format!("{name}() needs nper >= 0")))?;
+        let third = Self::decimal_to_rational(&args[2]);
+        let growth = growth(&rate, periods)?;
+        let annuity = annuity(&rate, &growth, periods) * (RBig::ONE + rate * timing);
+        let result = match name {
+            "pv" => -(fourth + third * annuity) / growth,
+            "fv" => -(fourth * growth + third * annuity),
+            _ => {
+                if annuity.is_zero() {
+                    return Err(BcError::Error("pmt() needs nper > 0".to_string()));
+                }
+                -(third * growth + fourth) / annuity
+            }
+        };
+        Ok(self.rational_to_decimal(&result))
+    }
+
+    fn solve_nper(
+        &self,
+        args: &[Decimal],
+        future: &RBig,
+        timing: &RBig,
+    ) -> Result<Decimal, BcError> {
+        let (rate, payment, present) = (
+            Self::decimal_to_rational(&args[0]),
+            Self::decimal_to_rational(&args[1]),
+            Self::decimal_to_rational(&args[2]),
+        );
+        if rate.is_zero() {
+            if payment.is_zero() {
+                return Err(BcError::Error("nper() needs pmt != 0".to_string()));
+            }
+            return Ok(self.rational_to_decimal(&(-(present + future) / payment)));
+        }
+        let flow = payment * (RBig::ONE + rate.clone() * timing);
+        let ratio = (flow.clone() - future.clone() * &rate) / (flow + present * &rate);
+        if ratio <= RBig::ZERO || rate <= -RBig::ONE {
+            return Err(BcError::Error(
+                "nper() has no solution for these values".to_string(),
+            ));
+        }
+        let (ratio, base) = (self.rational_to_decimal(&ratio), &args[0]);
+        self.eval_precise(&[base], |precision| {
+            let work = precision + WORK_DIGITS;
+            let one = mathlib::at_precision(&Decimal::ONE, work);
+            let log_base = mathlib::ln(&(one + base), work)?;
+            Ok(mathlib::ln(&mathlib::at_precision(&ratio, work), work)? / log_base)
+        })
+    }
+
+    fn solve_rate(
+        &self,
+        args: &[Decimal],
+        future: &RBig,
+        timing: &RBig,
+    ) -> Result<Decimal, BcError> {
+        let periods = integer_arg("rate", &args[0])?;
+        if periods.sign() == Sign::Negative || periods.is_zero() {
+            return Err(BcError::Error("rate() needs nper > 0".to_string()));
+        }
+        let (payment, present) = (&args[1], &args[2]);
+        let future = self.rational_to_decimal(future);
+        let timing = self.rational_to_decimal(timing);
+        self.eval_precise(&[payment, present, &future], |precision| {
+            let work = precision + WORK_DIGITS;
+            let one = mathlib::at_precision(&Decimal::ONE, work);
+            secant("rate", precision, work, |rate| {
+                let growth = (one.clone() + rate).powi(periods.clone());
+                let annuity = if rate.is_zero() {
+                    mathlib::at_precision(&Decimal::from(periods.clone()), work)
+                } else {
+                    (growth.clone() - &one) / rate
+                };
+                Ok(present.clone() * growth
+                    + payment.clone() * (one.clone() + rate.clone() * &timing) * annuity
+                    + &future)
+            })
+        })
+    }
+
+    /// `npv(rate, v1, v2, ...)`, discounting the first value by one period
+    /// as spreadsheets do.
+    fn builtin_npv(&self, args: &[Decimal]) -> Result<Decimal, BcError> {
+        if args.len() < 2 {
+            return Err(BcError::Error(
+                "npv() expects a rate and at least 1 value".to_string(),
+            ));
+        }
+        let discount = RBig::ONE / growth(&Self::decimal_to_rational(&args[0]), 1)?;
+        let mut factor = RBig::ONE;
+        let mut total = RBig::ZERO;
+        for value in &args[1..] {
+            factor *= &discount;
+            total += Self::decimal_to_rational(value) * &factor;
+        }
+        Ok(self.rational_to_decimal(&total))
+    }
+
+    /// `irr(v0, v1, ...)`: the rate at which the flows, the first one
+    /// undiscounted, are worth nothing today.
+    fn builtin_irr(&self, args: &[Decimal]) -> Result<Decimal, BcError> {
+        let has = |sign: Sign| args.iter().any(|v| v.sign() == sign && !v.is_zero());
+        if !has(Sign::Positive) || !has(Sign::Negative) {
+            return Err(BcError::Error(
+                "irr() needs at least one positive and one negative value".to_string(),
+            ));
+        }
+        let inputs: Vec<&Decimal> = args.iter().collect();
+        self.eval_precise(&inputs, |precision| {
+            let work = precision + WORK_DIGITS;
+            let one = mathlib::at_precision(&Decimal::ONE, work);
+            secant("irr", precision, work, |rate| {
+                let discount = one.clone() / (one.clone() + rate);
+                let mut factor = one.clone();
+                let mut total = Decimal::ZERO;
+                for value in args {
+                    total += value.clone() * &factor;
+                    factor *= &discount;
+                }
+                Ok(total)
+            })
+        })
+    }
+
+    /// `xnpv(rate, v1, d1, v2, d2, ...)`: each value discounted by the
+    /// years of 365 days between its date and the first one. Dates are
+    /// day numbers, or instants when the expression holds date literals.
+    fn builtin_xnpv(&self, args: &[Decimal]) -> Result<Decimal, BcError> {
+        if args.len() < 3 || args.len().is_multiple_of(2) {
+            return Err(BcError::Error(
+                "xnpv() expects a rate and value, date pairs".to_string(),
+            ));
+        }
+        if Self::decimal_to_rational(&args[0]) <= -RBig::ONE {
+            return Err(BcError::Error("xnpv() needs rate > -1".to_string()));
+        }
+        let inputs: Vec<&Decimal> = args.iter().collect();
+        self.eval_precise(&inputs, |precision| {
+            let work = precision + WORK_DIGITS;
+            let one = mathlib::at_precision(&Decimal::ONE, work);
+            let log_growth = mathlib::ln(&(one + &args[0]), work)?;
+            let start = &args[2];
+            let mut total = Decimal::ZERO;
+            for pair in args[1..].chunks(2) {
+                let years =
+                    mathlib::at_precision(&(pair[1].clone() - start), work) / Decimal::from(365);
+                total += pair[0].clone() * mathlib::exp(&-(years * &log_growth), work)?;
+            }
+            Ok(total)
+        })
+    }
+
+    /// `value` rounded to `scale` digits under the `round` mode.
+    fn round_money(&self, value: &RBig) -> RBig {
+        let rounded = Self::round_decimal_to_scale(
+            &self.rational_to_decimal(value),
+            self.runtime.scale(),
+            self.runtime.round(),
+        );
+        Self::decimal_to_rational(&rounded)
+    }
+}
+
+/// `(1 + rate)^periods`, exactly.
+fn growth(rate: &RBig, periods: usize) -> Result<RBig, BcError> {
+    let base = RBig::ONE + rate;
+    if base <= RBig::ZERO {
+        return Err(BcError::Error(
+            "Interest rates must be above -1".to_string(),
+        ));
+    }
+    Ok(base.pow(periods))
+}
+
+/// What paying 1 each period for `periods` periods is worth at the end:
+/// `((1 + rate)^periods - 1) / rate`, or `periods` when `rate` is 0.
+fn annuity(rate: &RBig, growth: &RBig, periods: usize) -> RBig {
+    if rate.is_zero() {
+        RBig::from(periods)
+    } else {
+        (growth.clone() - RBig::ONE) / rate
+    }
+}
+
+/// A root of `f` above -1 by the secant method, starting from 10% and 11%.
+fn secant(
+    name: &str,
+    precision: usize,
+    work: usize,
+    f: impl Fn(&Decimal) -> Result<Decimal, BcError>,
+) -> Result<Decimal, BcError> {
+    let tolerance = mathlib::epsilon(precision);
+    let failed = || BcError::Error(format!("{name}() did not converge"));
+    let mut previous = mathlib::at_precision(&Decimal::from_parts(IBig::from(1), -1), work);
+    let mut current = mathlib::at_precision(&Decimal::from_parts(IBig::from(11), -2), work);
+    let mut previous_value = f(&previous)?;
+    for _ in 0..MAX_STEPS {
+        let value = f(&current)?;
+        if value.is_zero() {
+            return Ok(current);
+        }
+        let slope = value.clone() - &previous_value;
+        if slope.is_zero() {
+            return Err(failed());
+        }
+        let mut next = current.clone() - value.clone() * (current.clone() - &previous) / slope;
+        let floor = -mathlib::at_precision(&Decimal::ONE, work);
+        if next <= floor {
+            next = (current.clone() + floor) / Decimal::from(2);
+        }
+        if (next.clone() - &current).abs() <= tolerance {
+            return Ok(next);
+        }
+        previous = current;
+        previous_value = value;
+        current = next;
+    }
+    Err(failed())
+}
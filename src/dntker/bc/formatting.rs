@@ -1,10 +1,18 @@
 use std::iter;
 
 use dashu::base::{Abs, Approximation, Sign, UnsignedAbs};
+use dashu::integer::{IBig, UBig};
+use dashu::rational::RBig;
 use dashu::Decimal;
 use num_traits::{ToPrimitive, Zero};
 
 use super::error::BcError;
+use super::literals::SI_PREFIXES;
+use super::mathlib;
+use super::number::BcNumber;
+use super::programmer;
+use super::runtime::{IntervalMode, Locale, Notation, RationalMode, RoundingMode, WordType};
+use super::uncertain::{self, Uncertain};
 
 impl super::BcExecuter {
     pub(super) fn show_limits(&self) -> String {
@@ -24,14 +32,119 @@ impl super::BcExecuter {
         )
     }
 
-    pub(super) fn format_result(&self, value: Decimal) -> String {
-        if self.runtime.obase() == 10 {
-            return self.format_result_decimal(&value);
+    pub(super) fn format_result(&self, mut number: BcNumber) -> String {
+        if let Some(time) = &number.time {
+            return self.format_temporal(time);
         }
-        if let Some(formatted) = self.format_result_obase(&value) {
-            formatted
+        if let Some(address) = &number.address {
+            return address.to_string();
+        }
+        if let Some(unit) = number.unit.take() {
+            return format!("{} {}", self.format_result(number), unit.symbol);
+        }
+        if let Some(word) = self.runtime.word() {
+            return self.format_word(&number, word);
+        }
+        if self.runtime.obase() != 10 {
+            return self.format_result_obase(&number);
+        }
+        if let Some(measured) = &number.uncertainty {
+            if let Some(formatted) = self.format_uncertain(measured) {
+                return formatted;
+            }
+        }
+        match self.runtime.rational() {
+            RationalMode::Fraction => return Self::format_fraction(&number.to_rational()),
+            RationalMode::Decimal => {
+                let scale = self.runtime.scale();
+                let exact = number.to_rational();
+                let units = IBig::from(10).pow(scale as usize) * exact.numerator()
+                    / IBig::from(exact.denominator().clone());
+                let value = Decimal::from_parts(units, -(scale as isize));
+                return self.localize(&Self::format_decimal_at_scale(&value, scale));
+            }
+            RationalMode::Off => {}
+        }
+        // Only enclosed values print as intervals; the interval evaluator
+        // hands back comparisons as plain 0 or 1.
+        let interval = match number.bounds {
+            Some(_) => self.runtime.interval(),
+            None => IntervalMode::Off,
+        };
+        match interval {
+            IntervalMode::Bounds => {
+                let scale = self.runtime.scale();
+                let bounds = number.to_interval().outward_at_scale(scale);
+                let lo = self.localize(&Self::format_decimal_at_scale(&bounds.lo, scale));
+                let hi = self.localize(&Self::format_decimal_at_scale(&bounds.hi, scale));
+                return format!("[{lo}{}{hi}]", self.list_separator());
+            }
+            IntervalMode::Midpoint => {
+                let scale = self.runtime.scale();
+                let bounds = number.to_interval();
+                let mid = Self::truncate_decimal_to_scale(&bounds.midpoint(), scale);
+                let radius = bounds.radius_about(&mid, scale);
+                let mid = self.localize(&Self::format_decimal_at_scale(&mid, scale));
+                let radius = self.localize(&Self::format_decimal_at_scale(&radius, scale));
+                return format!("{mid} ± {radius}");
+            }
+            IntervalMode::Off => {}
+        }
+        let notation = self.runtime.notation();
+        let formatted = if notation != Notation::Plain {
+            self.format_notation(&number.value, notation)
+        } else if self.runtime.posix_scale() {
+            Self::format_posix_decimal(&number.value, number.scale)
         } else {
-            self.format_result_decimal(&value)
+            self.format_result_decimal(&number.value)
+        };
+        self.localize(&formatted)
+    }
+
+    /// Groups the integer digits of the number `formatted` starts with and
+    /// swaps in the locale's decimal separator; whatever follows the number,
+    /// such as an exponent, is kept as is.
+    pub(super) fn localize(&self, formatted: &str) -> String {
+        let locale = self.runtime.locale();
+        let Some(group) = locale.group_separator() else {
+            return formatted.to_string();
+        };
+        let (sign, body) = match formatted.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", formatted),
+        };
+        let integer_len = body
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(body.len());
+        let (integer, rest) = body.split_at(integer_len);
+        let mut grouped = String::with_capacity(integer.len() * 2);
+        let len = integer.len();
+        for (index, digit) in integer.chars().enumerate() {
+            let remaining = len - index;
+            let boundary = if locale == Locale::Indian && remaining > 3 {
+                (remaining - 3) % 2 == 0
+            } else {
+                remaining % 3 == 0
+            };
+            if index > 0 && boundary {
+                grouped.push(group);
+            }
+            grouped.push(digit);
+        }
+        let rest = match rest.strip_prefix('.') {
+            Some(fraction) if locale.decimal_comma() => format!(",{fraction}"),
+            _ => rest.to_string(),
+        };
+        format!("{sign}{grouped}{rest}")
+    }
+
+    /// Separates the entries of interval and matrix output: `; ` where the
+    /// locale already spends the comma on the decimal separator.
+    pub(super) fn list_separator(&self) -> &'static str {
+        if self.runtime.locale().decimal_comma() {
+            "; "
+        } else {
+            ", "
         }
     }
 
@@ -49,7 +162,9 @@ impl super::BcExecuter {
 
     pub(super) fn promote_precision(&self, value: Decimal) -> Decimal {
         const PRECISION_PADDING: usize = 4;
-        let digits = value.repr().digits().max(1);
+        // Count trailing integer zeros too, so `10^30 + 1` keeps its last digit.
+        let repr = value.repr();
+        let digits = (repr.digits() + repr.exponent().max(0) as usize).max(1);
         let target = digits
             .saturating_mul(2)
             .saturating_add(self.runtime.scale() as usize)
@@ -70,6 +185,18 @@ impl super::BcExecuter {
         truncated / factor
     }
 
+    /// Cuts `value` down to `scale` fractional digits under `mode`.
+    pub(crate) fn round_decimal_to_scale(
+        value: &Decimal,
+        scale: u32,
+        mode: RoundingMode,
+    ) -> Decimal {
+        if mode == RoundingMode::Truncate {
+            return Self::truncate_decimal_to_scale(value, scale);
+        }
+        round_to_places(value, scale as isize, mode)
+    }
+
     pub(super) fn decimal_to_plain_string(value: &Decimal) -> String {
         let repr = value.repr();
         if repr.significand().is_zero() {
@@ -106,9 +233,16 @@ impl super::BcExecuter {
     }
 
     pub(crate) fn format_result_decimal(&self, value: &Decimal) -> String {
-        let scale = self.runtime.scale();
-        let truncated = Self::truncate_decimal_to_scale(value, scale);
-        let mut formatted = Self::decimal_to_plain_string(&truncated);
+        Self::format_rounded_at_scale(value, self.runtime.scale(), self.runtime.round())
+    }
+
+    pub(super) fn format_decimal_at_scale(value: &Decimal, scale: u32) -> String {
+        Self::format_rounded_at_scale(value, scale, RoundingMode::Truncate)
+    }
+
+    fn format_rounded_at_scale(value: &Decimal, scale: u32, mode: RoundingMode) -> String {
+        let rounded = Self::round_decimal_to_scale(value, scale, mode);
+        let mut formatted = Self::decimal_to_plain_string(&rounded);
 
         if let Some(point_index) = formatted.find('.') {
             if scale == 0 {
@@ -134,63 +268,251 @@ impl super::BcExecuter {
         }
     }
 
-    fn format_result_obase(&self, value: &Decimal) -> Option<String> {
-        const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-        let base = self.runtime.obase() as i32;
-        let negative = value.sign() == Sign::Negative;
-        let abs_value = value.clone().abs();
-        let integer_part = abs_value.trunc();
-        let mut integer = ToPrimitive::to_i128(&integer_part)?;
-
-        let mut int_buf = Vec::new();
-        if integer == 0 {
-            int_buf.push('0');
+    fn format_posix_decimal(value: &Decimal, scale: u32) -> String {
+        let mut formatted = Self::format_decimal_at_scale(value, scale);
+        if scale > 0 && !value.is_zero() && !formatted.contains('.') {
+            formatted.push('.');
+            formatted.extend(iter::repeat_n('0', scale as usize));
+        }
+        formatted
+    }
+
+    /// `value ± sigma` with the uncertainty kept to two significant figures
+    /// and the value rounded to the same decimal place.
+    fn format_uncertain(&self, measured: &Uncertain) -> Option<String> {
+        let precision = self.working_precision(mathlib::integer_digits(&measured.value));
+        let sigma = measured.sigma(precision).ok()?;
+        if sigma.is_zero() {
+            return None;
+        }
+        let leading = |value: &Decimal| {
+            let repr = value.repr();
+            repr.digits() as isize + repr.exponent() - 1
+        };
+        let mut places = 1 - leading(&sigma);
+        let mut rounded = uncertain::round_at(&sigma, places);
+        if leading(&rounded) > leading(&sigma) {
+            places -= 1;
+            rounded = uncertain::round_at(&sigma, places);
+        }
+        let value = uncertain::round_at(&measured.value, places);
+        let scale = places.max(0) as u32;
+        Some(format!(
+            "{} ± {}",
+            self.localize(&Self::format_posix_decimal(&value, scale)),
+            self.localize(&Self::format_posix_decimal(&rounded, scale))
+        ))
+    }
+
+    fn format_fraction(exact: &RBig) -> String {
+        if exact.denominator().is_one() {
+            exact.numerator().to_string()
+        } else {
+            format!("{}/{}", exact.numerator(), exact.denominator())
+        }
+    }
+
+    /// Writes `value` as a mantissa and power of ten, the mantissa cut to
+    /// `scale + 1` significant digits under the rounding mode and stripped of
+    /// trailing zeros. Engineering notation keeps the exponent a multiple of
+    /// three, which SI notation spells as a prefix where one exists.
+    fn format_notation(&self, value: &Decimal, notation: Notation) -> String {
+        if value.is_zero() {
+            return "0".to_string();
+        }
+        let repr = value.repr();
+        let step = if notation == Notation::Scientific {
+            1
         } else {
-            while integer > 0 {
-                let digit = (integer % base as i128) as usize;
-                int_buf.push(DIGITS[digit] as char);
-                integer /= base as i128;
+            3
+        };
+        let leading = repr.digits() as isize - 1 + repr.exponent();
+        let mut exponent = leading.div_euclid(step) * step;
+        let limit = Decimal::from(10u32.pow(step as u32));
+        let mantissa = loop {
+            let shifted =
+                Decimal::from_parts(repr.significand().clone(), repr.exponent() - exponent);
+            let places = self.runtime.scale() as isize - (leading - exponent);
+            let rounded = round_to_places(&shifted, places, self.runtime.round());
+            if rounded.clone().abs() < limit {
+                break rounded;
             }
-            int_buf.reverse();
+            exponent += step;
+        };
+        let mut digits = Self::decimal_to_plain_string(&mantissa);
+        if digits.contains('.') {
+            digits.truncate(digits.trim_end_matches('0').trim_end_matches('.').len());
         }
+        if notation == Notation::SiPrefix {
+            if exponent == 0 {
+                return digits;
+            }
+            if let Some((prefix, _)) = SI_PREFIXES
+                .iter()
+                .find(|(_, power)| *power as isize == exponent)
+            {
+                return format!("{digits}{prefix}");
+            }
+        }
+        format!("{digits}e{exponent}")
+    }
 
-        let mut result = String::new();
-        if negative && (!int_buf.is_empty() || !abs_value.fract().is_zero()) {
-            result.push('-');
+    /// Programmer mode prints the wrapped integer in decimal, and in any other
+    /// `obase` its two's-complement bit pattern, zero-padded to the full word
+    /// when the base is a power of two.
+    fn format_word(&self, number: &BcNumber, word: WordType) -> String {
+        let value = programmer::wrap(number.units(0), word);
+        let base = self.runtime.obase();
+        if base == 10 {
+            return value.to_string();
         }
-        for ch in int_buf {
-            result.push(ch);
+        let pattern = value & ((IBig::ONE << word.bits as usize) - IBig::ONE);
+        let digits = self.format_result_obase(&BcNumber::new(Decimal::from(pattern), 0));
+        if base > 36 || !base.is_power_of_two() {
+            return digits;
         }
+        let bits_per_digit = base.trailing_zeros();
+        let width = word.bits.div_ceil(bits_per_digit) as usize;
+        format!("{digits:0>width$}")
+    }
 
-        let mut fraction = abs_value - integer_part;
-        let scale = self.runtime.scale();
-        if !fraction.is_zero() && scale > 0 {
-            result.push('.');
-            let base_decimal = Decimal::from(base as i64);
-            let mut digits_written = 0;
-            while digits_written < scale {
-                fraction *= base_decimal.clone();
-                let digit_dec = fraction.trunc();
-                let digit = ToPrimitive::to_u32(&digit_dec)? as usize;
-                result.push(DIGITS[digit] as char);
-                fraction -= digit_dec;
-                digits_written += 1;
-                if fraction.is_zero() {
-                    break;
-                }
+    /// `value` as decimal, `0x` hex, `0o` octal and `0b` binary. Programmer
+    /// mode shows the word's two's-complement pattern padded to its width.
+    pub(super) fn format_bases(&self, value: &IBig) -> Vec<String> {
+        let (decimal, sign, pattern, bits) = match self.runtime.word() {
+            Some(word) => {
+                let wrapped = programmer::wrap(value.clone(), word);
+                let mask = (IBig::ONE << word.bits as usize) - IBig::ONE;
+                let pattern = (wrapped.clone() & mask).unsigned_abs();
+                (wrapped.to_string(), "", pattern, word.bits as usize)
             }
-            while result.ends_with('0') {
-                result.pop();
+            None => {
+                let sign = if value.sign() == Sign::Negative {
+                    "-"
+                } else {
+                    ""
+                };
+                (value.to_string(), sign, value.unsigned_abs(), 0)
             }
-            if result.ends_with('.') {
-                result.pop();
+        };
+        let binary = format!("{pattern:0width$b}", width = bits);
+        let mut nibbles: Vec<&str> = Vec::new();
+        let head = binary.len() % 4;
+        if head > 0 {
+            nibbles.push(&binary[..head]);
+        }
+        nibbles.extend(
+            binary.as_bytes()[head..]
+                .chunks(4)
+                .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default()),
+        );
+        vec![
+            decimal,
+            format!("{sign}0x{pattern:0width$X}", width = bits.div_ceil(4)),
+            format!("{sign}0o{pattern:0width$o}", width = bits.div_ceil(3)),
+            format!("{sign}0b{}", nibbles.join("_")),
+        ]
+    }
+
+    /// Renders `number` in `obase` following bc: bases up to 36 use single
+    /// `0-9A-Z` digits, larger ones print each digit as a zero-padded decimal
+    /// group separated by spaces. The fraction gets as many digits as it takes
+    /// for `obase^k` to exceed the precision of `scale` decimal places.
+    fn format_result_obase(&self, number: &BcNumber) -> String {
+        const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        let base = self.runtime.obase();
+        let scale = if self.runtime.posix_scale() {
+            number.scale
+        } else {
+            self.runtime.scale()
+        };
+        let group_width = (base - 1).to_string().len();
+        let render = |digit: u32| {
+            if base <= 36 {
+                (DIGITS[digit as usize] as char).to_string()
+            } else {
+                format!("{digit:0group_width$}")
             }
+        };
+        let separator = if base <= 36 { "" } else { " " };
+
+        if number.is_zero() {
+            return "0".to_string();
+        }
+        let magnitude = number.truncate(scale);
+        let units = magnitude.units(scale).unsigned_abs();
+        let one = UBig::from(10u8).pow(scale as usize);
+        let mut integer = &units / &one;
+        let mut fraction = &units % &one;
+
+        let mut int_digits = Vec::new();
+        let radix = UBig::from(base);
+        while !integer.is_zero() {
+            let digit = &integer % &radix;
+            int_digits.push(render(ToPrimitive::to_u32(&digit).unwrap_or_default()));
+            integer /= &radix;
+        }
+        int_digits.reverse();
+
+        let mut result = String::new();
+        if number.value.sign() == Sign::Negative && !units.is_zero() {
+            result.push('-');
+        }
+        let posix = self.runtime.posix_scale();
+        let show_fraction = scale > 0 && (posix || !fraction.is_zero());
+        if int_digits.is_empty() && !show_fraction {
+            int_digits.push(render(0));
         }
+        result.push_str(&int_digits.join(separator));
 
-        if result.is_empty() || result == "-" {
-            result.push('0');
+        if show_fraction {
+            let mut frac_digits = Vec::new();
+            let mut place = UBig::ONE;
+            while place.to_string().len() <= scale as usize {
+                fraction *= &radix;
+                let digit = &fraction / &one;
+                fraction %= &one;
+                frac_digits.push(render(ToPrimitive::to_u32(&digit).unwrap_or_default()));
+                place *= &radix;
+            }
+            result.push('.');
+            result.push_str(&frac_digits.join(separator));
         }
 
-        Some(result)
+        result
     }
 }
+
+/// Rounds `value` to `places` fractional digits under `mode`; a negative
+/// count rounds to tens, hundreds and so on.
+pub(super) fn round_to_places(value: &Decimal, places: isize, mode: RoundingMode) -> Decimal {
+    let repr = value.repr();
+    let shift = repr.exponent() + places;
+    if shift >= 0 {
+        return value.clone();
+    }
+    let divisor = IBig::from(10).pow(shift.unsigned_abs());
+    let quotient = repr.significand() / &divisor;
+    let remainder = repr.significand() - &quotient * &divisor;
+    if remainder.is_zero() {
+        return Decimal::from_parts(quotient, -places);
+    }
+    let negative = repr.sign() == Sign::Negative;
+    let twice = remainder.unsigned_abs() * UBig::from(2u8);
+    let divisor = divisor.unsigned_abs();
+    let away = match mode {
+        RoundingMode::Truncate => false,
+        RoundingMode::Floor => negative,
+        RoundingMode::Ceiling => !negative,
+        RoundingMode::HalfUp => twice >= divisor,
+        RoundingMode::HalfEven => {
+            twice > divisor || (twice == divisor && quotient.clone() % IBig::from(2) != IBig::ZERO)
+        }
+    };
+    let units = match (away, negative) {
+        (false, _) => quotient,
+        (true, false) => quotient + IBig::ONE,
+        (true, true) => quotient - IBig::ONE,
+    };
+    Decimal::from_parts(units, -places)
+}
@@ -0,0 +1,181 @@
+use std::convert::TryFrom;
+
+use dashu::integer::IBig;
+use dashu::Decimal;
+
+use super::error::BcError;
+use super::numtheory::{expect_args, integer_arg};
+
+/// An IEEE-754 binary interchange format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum FloatFormat {
+    Single,
+    Double,
+}
+
+impl FloatFormat {
+    fn name(self) -> &'static str {
+        match self {
+            FloatFormat::Single => "f32",
+            FloatFormat::Double => "f64",
+        }
+    }
+
+    fn exponent_bits(self) -> u32 {
+        match self {
+            FloatFormat::Single => 8,
+            FloatFormat::Double => 11,
+        }
+    }
+
+    fn fraction_bits(self) -> u32 {
+        match self {
+            FloatFormat::Single => 23,
+            FloatFormat::Double => 52,
+        }
+    }
+
+    fn width(self) -> u32 {
+        1 + self.exponent_bits() + self.fraction_bits()
+    }
+
+    fn bias(self) -> i64 {
+        (1 << (self.exponent_bits() - 1)) - 1
+    }
+
+    fn max_exponent(self) -> u64 {
+        (1 << self.exponent_bits()) - 1
+    }
+
+    /// The bit pattern of the float nearest to `value`, rounding half to
+    /// even the way the standard library's parser does.
+    fn encode(self, value: &Decimal) -> Result<u64, BcError> {
+        let literal = super::BcExecuter::decimal_to_plain_string(value);
+        let invalid = || BcError::Error(format!("Cannot convert {literal} to {}", self.name()));
+        match self {
+            FloatFormat::Single => literal
+                .parse::<f32>()
+                .map(|float| u64::from(float.to_bits()))
+                .map_err(|_| invalid()),
+            FloatFormat::Double => literal
+                .parse::<f64>()
+                .map(f64::to_bits)
+                .map_err(|_| invalid()),
+        }
+    }
+
+    /// The exact value a bit pattern stores: `±mantissa·2^exponent` always has
+    /// a terminating decimal expansion.
+    fn decode(self, bits: u64) -> Result<Decimal, BcError> {
+        let (negative, biased, fraction) = self.split(bits);
+        if biased == self.max_exponent() {
+            let what = if fraction == 0 { "infinity" } else { "NaN" };
+            return Err(BcError::Error(format!(
+                "{} bit pattern {bits:#X} is {what}",
+                self.name()
+            )));
+        }
+        let (mantissa, exponent) = if biased == 0 {
+            (fraction, 1 - self.bias())
+        } else {
+            (
+                fraction | (1 << self.fraction_bits()),
+                biased as i64 - self.bias(),
+            )
+        };
+        let exponent = exponent - i64::from(self.fraction_bits());
+        let mut significand = IBig::from(mantissa);
+        if negative {
+            significand = -significand;
+        }
+        if exponent >= 0 {
+            return Ok(Decimal::from(significand << exponent as usize));
+        }
+        let places = exponent.unsigned_abs() as usize;
+        let scaled = significand * IBig::from(5).pow(places);
+        Ok(Decimal::from_parts(scaled, -(places as isize)))
+    }
+
+    fn split(self, bits: u64) -> (bool, u64, u64) {
+        let negative = bits >> (self.width() - 1) & 1 == 1;
+        let biased = bits >> self.fraction_bits() & self.max_exponent();
+        let fraction = bits & ((1 << self.fraction_bits()) - 1);
+        (negative, biased, fraction)
+    }
+
+    /// `sign 0, exponent -4 (0x3FB), mantissa 0x999999999999A, bits
+    /// 0x3FB999999999999A`: the unbiased exponent with its stored field.
+    fn describe(self, bits: u64) -> String {
+        let (negative, biased, fraction) = self.split(bits);
+        let exponent_digits = self.exponent_bits().div_ceil(4) as usize;
+        let field = format!("{biased:#0width$X}", width = exponent_digits + 2);
+        let exponent = if biased == self.max_exponent() {
+            let what = if fraction == 0 { "inf" } else { "nan" };
+            format!("{what} ({field})")
+        } else if biased == 0 && fraction != 0 {
+            format!("{} subnormal ({field})", 1 - self.bias())
+        } else if biased == 0 {
+            format!("0 ({field})")
+        } else {
+            format!("{} ({field})", biased as i64 - self.bias())
+        };
+        format!(
+            "sign {}, exponent {exponent}, mantissa {fraction:#X}, bits {bits:#0width$X}",
+            u8::from(negative),
+            width = self.width() as usize / 4 + 2,
+        )
+    }
+}
+
+impl super::BcExecuter {
+    /// `f64decode(x)` / `f32decode(x)` print the fields of the float nearest
+    /// to `x`, and `f64exact(x)` / `f32exact(x)` print every digit it stores,
+    /// so they are understood as statements of their own. The `exact` forms also
+    /// work inside expressions, at the current `scale`.
+    pub(super) fn try_eval_float_inspection(
+        &mut self,
+        statement: &str,
+    ) -> Result<Option<String>, BcError> {
+        for format in [FloatFormat::Double, FloatFormat::Single] {
+            let decode = format!("{}decode", format.name());
+            if let Some(inner) = Self::strip_wrapped_function(statement, &decode)? {
+                let value = self.eval_expression(inner)?;
+                return Ok(Some(format.describe(format.encode(&value)?)));
+            }
+            let exact = format!("{}exact", format.name());
+            if let Some(inner) = Self::strip_wrapped_function(statement, &exact)? {
+                let value = self.eval_expression(inner)?;
+                let stored = format.decode(format.encode(&value)?)?;
+                let places = stored.repr().exponent().min(0).unsigned_abs() as u32;
+                return Ok(Some(Self::format_decimal_at_scale(&stored, places)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// `f64bits(x)`: the bit pattern of the float nearest to `x`, as an integer.
+pub(super) fn bits(format: FloatFormat, args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args(&format!("{}bits", format.name()), args, 1)?;
+    Ok(Decimal::from(format.encode(&args[0])?))
+}
+
+/// `f64frombits(n)`: the exact value the bit pattern `n` stores.
+pub(super) fn from_bits(format: FloatFormat, args: &[Decimal]) -> Result<Decimal, BcError> {
+    let name = format!("{}frombits", format.name());
+    expect_args(&name, args, 1)?;
+    let pattern = integer_arg(&name, &args[0])?;
+    let out_of_range =
+        || BcError::Error(format!("{name}() expects a {}-bit pattern", format.width()));
+    if pattern >= IBig::ONE << format.width() as usize {
+        return Err(out_of_range());
+    }
+    let pattern = u64::try_from(pattern).map_err(|_| out_of_range())?;
+    format.decode(pattern)
+}
+
+/// `f64exact(x)`: the exact value of the float nearest to `x`.
+pub(super) fn exact(format: FloatFormat, args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args(&format!("{}exact", format.name()), args, 1)?;
+    format.decode(format.encode(&args[0])?)
+}
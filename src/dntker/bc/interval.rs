@@ -0,0 +1,597 @@
+use std::convert::TryFrom;
+
+use dashu::base::{Abs, Sign};
+use dashu::float::round::mode::{Down, Up};
+use dashu::float::FBig;
+use dashu::integer::IBig;
+use dashu::Decimal;
+use num_traits::{ToPrimitive, Zero};
+
+use super::error::BcError;
+use super::expression::{power_too_large, EXACT_POWER_DIGITS};
+use super::mathlib::{self, GUARD_DIGITS};
+use super::number::BcNumber;
+use super::numtheory::NUMBER_THEORY;
+use super::posix::{
+    addresses_unsupported, time_unsupported, units_unsupported, PosixExpr, PosixOp, PosixParser,
+};
+
+/// Builtins besides the number-theory ones whose results are exact, so their
+/// point results are not widened by the library's error.
+const EXACT_BUILTINS: [&str; 12] = [
+    "length",
+    "scale",
+    "sign",
+    "rand",
+    "srand",
+    "read",
+    "f64bits",
+    "f32bits",
+    "f64frombits",
+    "f32frombits",
+    "f64exact",
+    "f32exact",
+];
+
+type Lower = FBig<Down, 10>;
+type Upper = FBig<Up, 10>;
+
+/// A closed range `[lo, hi]` guaranteed to contain the true value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: Decimal,
+    pub hi: Decimal,
+}
+
+impl Interval {
+    pub fn new(lo: Decimal, hi: Decimal) -> Self {
+        Self { lo, hi }
+    }
+
+    pub fn point(value: Decimal) -> Self {
+        Self::new(value.clone(), value)
+    }
+
+    pub fn is_point(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.lo.is_zero() && self.hi.is_zero()
+    }
+
+    pub fn contains_zero(&self) -> bool {
+        self.lo <= Decimal::ZERO && self.hi >= Decimal::ZERO
+    }
+
+    pub fn midpoint(&self) -> Decimal {
+        (self.lo.clone() + &self.hi) / Decimal::from(2)
+    }
+
+    pub fn negate(&self) -> Self {
+        Self::new(-self.hi.clone(), -self.lo.clone())
+    }
+
+    pub fn hull(&self, other: &Self) -> Self {
+        Self::new(
+            cmp_min(&self.lo, &other.lo).clone(),
+            cmp_max(&self.hi, &other.hi).clone(),
+        )
+    }
+
+    pub fn add(&self, other: &Self, precision: usize) -> Self {
+        Self::new(
+            from_lower(lower(&self.lo, precision) + lower(&other.lo, precision)),
+            from_upper(upper(&self.hi, precision) + upper(&other.hi, precision)),
+        )
+    }
+
+    pub fn sub(&self, other: &Self, precision: usize) -> Self {
+        self.add(&other.negate(), precision)
+    }
+
+    pub fn mul(&self, other: &Self, precision: usize) -> Self {
+        let pairs = self.endpoint_pairs(other);
+        let lo = pairs
+            .iter()
+            .map(|(a, b)| lower(a, precision) * lower(b, precision))
+            .min()
+            .unwrap_or_default();
+        let hi = pairs
+            .iter()
+            .map(|(a, b)| upper(a, precision) * upper(b, precision))
+            .max()
+            .unwrap_or_default();
+        Self::new(from_lower(lo), from_upper(hi))
+    }
+
+    pub fn div(&self, other: &Self, precision: usize) -> Result<Self, BcError> {
+        if other.contains_zero() {
+            return Err(BcError::Error("Division by zero".to_string()));
+        }
+        let pairs = self.endpoint_pairs(other);
+        let lo = pairs
+            .iter()
+            .map(|(a, b)| lower(a, precision) / lower(b, precision))
+            .min()
+            .unwrap_or_default();
+        let hi = pairs
+            .iter()
+            .map(|(a, b)| upper(a, precision) / upper(b, precision))
+            .max()
+            .unwrap_or_default();
+        Ok(Self::new(from_lower(lo), from_upper(hi)))
+    }
+
+    pub fn powi(&self, exponent: u32, precision: usize) -> Self {
+        if exponent == 0 {
+            return Self::point(Decimal::ONE);
+        }
+        let magnitude = |value: &Decimal| {
+            let abs = value.clone().abs();
+            let power = IBig::from(exponent);
+            (
+                from_lower(lower(&abs, precision).powi(power.clone())),
+                from_upper(upper(&abs, precision).powi(power)),
+            )
+        };
+        let (lo_down, lo_up) = magnitude(&self.lo);
+        let (hi_down, hi_up) = magnitude(&self.hi);
+        if exponent % 2 == 1 {
+            let lo = if self.lo.sign() == Sign::Negative {
+                -lo_up
+            } else {
+                lo_down
+            };
+            let hi = if self.hi.sign() == Sign::Negative {
+                -hi_down
+            } else {
+                hi_up
+            };
+            return Self::new(lo, hi);
+        }
+        if self.contains_zero() {
+            return Self::new(Decimal::ZERO, cmp_max(&lo_up, &hi_up).clone());
+        }
+        if self.hi.sign() == Sign::Negative {
+            Self::new(hi_down, lo_up)
+        } else {
+            Self::new(lo_down, hi_up)
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        if self.contains_zero() {
+            let hi = cmp_max(&self.lo.clone().abs(), &self.hi.clone().abs()).clone();
+            return Self::new(Decimal::ZERO, hi);
+        }
+        if self.hi.sign() == Sign::Negative {
+            return self.negate();
+        }
+        self.clone()
+    }
+
+    /// Pushes both ends out by `margin`.
+    pub fn widen(&self, margin: &Decimal, precision: usize) -> Self {
+        Self::new(
+            from_lower(lower(&self.lo, precision) - lower(margin, precision)),
+            from_upper(upper(&self.hi, precision) + upper(margin, precision)),
+        )
+    }
+
+    /// Rounds `lo` down and `hi` up to `scale` fractional digits.
+    pub fn outward_at_scale(&self, scale: u32) -> Self {
+        Self::new(
+            directed_at_scale(&self.lo, scale, false),
+            directed_at_scale(&self.hi, scale, true),
+        )
+    }
+
+    /// Smallest radius about `center` that still covers the interval,
+    /// rounded up to `scale` fractional digits.
+    pub fn radius_about(&self, center: &Decimal, scale: u32) -> Decimal {
+        let precision = self.lo.repr().digits().max(self.hi.repr().digits()) + scale as usize;
+        let above = upper(&self.hi, precision) - upper(center, precision);
+        let below = upper(center, precision) - upper(&self.lo, precision);
+        let radius = from_upper(above.max(below));
+        directed_at_scale(&radius, scale, true)
+    }
+
+    /// Whether some `offset + k * period` lies inside the interval.
+    fn contains_phase(&self, offset: &Decimal, period: &Decimal) -> bool {
+        let k = ((self.lo.clone() - offset) / period).ceil();
+        offset.clone() + k * period <= self.hi
+    }
+
+    /// `self op other` when it holds for every pair of points the two
+    /// intervals contain, or fails for every pair; `None` when it depends
+    /// on where in the intervals the true values lie.
+    fn compare(&self, op: PosixOp, other: &Self) -> Option<bool> {
+        match op {
+            PosixOp::Lt if self.hi < other.lo => Some(true),
+            PosixOp::Lt if self.lo >= other.hi => Some(false),
+            PosixOp::Le if self.hi <= other.lo => Some(true),
+            PosixOp::Le if self.lo > other.hi => Some(false),
+            PosixOp::Gt => other.compare(PosixOp::Lt, self),
+            PosixOp::Ge => other.compare(PosixOp::Le, self),
+            PosixOp::Eq if self.is_point() && other.is_point() => Some(self.lo == other.lo),
+            PosixOp::Eq if self.hi < other.lo || other.hi < self.lo => Some(false),
+            PosixOp::Ne => other.compare(PosixOp::Eq, self).map(|equal| !equal),
+            _ => None,
+        }
+    }
+
+    fn endpoint_pairs<'a>(&'a self, other: &'a Self) -> [(&'a Decimal, &'a Decimal); 4] {
+        [
+            (&self.lo, &other.lo),
+            (&self.lo, &other.hi),
+            (&self.hi, &other.lo),
+            (&self.hi, &other.hi),
+        ]
+    }
+}
+
+impl super::BcExecuter {
+    /// Evaluates `expr` carrying a guaranteed `[lo, hi]` enclosure through
+    /// every operator and builtin.
+    pub(super) fn eval_interval_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let tree = PosixParser::parse_expression(expr, self.runtime.ibase())?;
+        let bounds = self.eval_interval_node(&tree)?;
+        // A comparison's answer is certain, so it prints as a plain 0 or 1.
+        if matches!(
+            tree,
+            PosixExpr::Not(_)
+                | PosixExpr::Binary(
+                    PosixOp::Or
+                        | PosixOp::And
+                        | PosixOp::Lt
+                        | PosixOp::Le
+                        | PosixOp::Gt
+                        | PosixOp::Ge
+                        | PosixOp::Eq
+                        | PosixOp::Ne,
+                    ..
+                )
+        ) {
+            return Ok(BcNumber::from_bool(!bounds.is_zero()));
+        }
+        Ok(BcNumber::interval(bounds))
+    }
+
+    fn eval_interval_node(&mut self, node: &PosixExpr) -> Result<Interval, BcError> {
+        match node {
+            PosixExpr::Number(number) => Ok(number.to_interval()),
+            PosixExpr::Quantity(..) => Err(units_unsupported()),
+            PosixExpr::Time(_) => Err(time_unsupported()),
+            PosixExpr::Address(_) => Err(addresses_unsupported()),
+            node @ (PosixExpr::Element(..) | PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                let number = self.eval_array_node(node, |exec, node| {
+                    exec.eval_interval_node(node).map(BcNumber::interval)
+                })?;
+                Ok(number.to_interval())
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => Ok(number.to_interval()),
+                None => self.eval_interval_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_interval_node(arg)?);
+                }
+                self.eval_interval_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => Ok(self.eval_interval_node(operand)?.negate()),
+            PosixExpr::Not(operand) => {
+                let value = self.eval_interval_node(operand)?;
+                Ok(Self::interval_bool(value.is_zero()))
+            }
+            node @ (PosixExpr::Complement(_) | PosixExpr::Bitwise(..)) => {
+                let number = self.eval_bitwise_node(node, |exec, node| {
+                    exec.eval_interval_node(node).map(BcNumber::interval)
+                })?;
+                Ok(number.to_interval())
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_interval_node(lhs)?.is_zero() {
+                    return Ok(Self::interval_bool(false));
+                }
+                let right = self.eval_interval_node(rhs)?;
+                Ok(Self::interval_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_interval_node(lhs)?.is_zero() {
+                    return Ok(Self::interval_bool(true));
+                }
+                let right = self.eval_interval_node(rhs)?;
+                Ok(Self::interval_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let left = self.eval_interval_node(lhs)?;
+                let right = self.eval_interval_node(rhs)?;
+                let precision = self.interval_precision(&[&left, &right]);
+                // Comparisons only answer when the overlap leaves no doubt.
+                let compare = |op| match left.compare(op, &right) {
+                    Some(result) => Ok(Self::interval_bool(result)),
+                    None => Err(BcError::Error(
+                        "Ambiguous comparison: the intervals overlap".to_string(),
+                    )),
+                };
+                match op {
+                    PosixOp::Add => Ok(left.add(&right, precision)),
+                    PosixOp::Sub => Ok(left.sub(&right, precision)),
+                    PosixOp::Mul => Ok(left.mul(&right, precision)),
+                    PosixOp::Div => left.div(&right, precision),
+                    PosixOp::Mod => {
+                        if !left.is_point() || !right.is_point() {
+                            return Err(BcError::Error(
+                                "% is not defined for intervals".to_string(),
+                            ));
+                        }
+                        if right.is_zero() {
+                            return Err(BcError::Error("Modulo by zero".to_string()));
+                        }
+                        Ok(Interval::point(left.lo % right.lo))
+                    }
+                    PosixOp::Pow => self.interval_power(&left, &right),
+                    PosixOp::Lt
+                    | PosixOp::Le
+                    | PosixOp::Gt
+                    | PosixOp::Ge
+                    | PosixOp::Eq
+                    | PosixOp::Ne => compare(*op),
+                    _ => unreachable!("handled above"),
+                }
+            }
+        }
+    }
+
+    /// Integer powers use the exact even/odd rule; anything else is
+    /// `exp(y * ln(x))`, which needs a positive base.
+    fn interval_power(
+        &mut self,
+        base: &Interval,
+        exponent: &Interval,
+    ) -> Result<Interval, BcError> {
+        let precision = self.interval_precision(&[base, exponent]);
+        if exponent.is_point() && exponent.lo.fract().is_zero() {
+            let power = ToPrimitive::to_i64(&exponent.lo.trunc())
+                .ok_or_else(|| BcError::Error("Exponent out of supported range".to_string()))?;
+            let magnitude = u32::try_from(power.unsigned_abs())
+                .map_err(|_| BcError::Error("Exponent out of supported range".to_string()))?;
+            let digits = base.lo.repr().digits().max(base.hi.repr().digits());
+            if digits.saturating_mul(magnitude as usize) > EXACT_POWER_DIGITS {
+                return Err(power_too_large());
+            }
+            let result = base.powi(magnitude, precision);
+            if power >= 0 {
+                return Ok(result);
+            }
+            return Interval::point(Decimal::ONE).div(&result, precision);
+        }
+        if base.lo <= Decimal::ZERO {
+            return Err(BcError::Error(
+                "Fractional powers of an interval need a positive base".to_string(),
+            ));
+        }
+        let log = self.interval_monotone("ln", base, true)?;
+        let product = log.mul(exponent, precision);
+        self.interval_monotone("exp", &product, true)
+    }
+
+    fn eval_interval_call(&mut self, name: &str, args: Vec<Interval>) -> Result<Interval, BcError> {
+        match (name, args.as_slice()) {
+            ("interval", [a, b]) => return Ok(a.hull(b)),
+            ("abs", [x]) => return Ok(x.abs()),
+            ("pow", [x, y]) => return self.interval_power(x, y),
+            ("min", [first, rest @ ..]) if !rest.is_empty() => {
+                return Ok(rest.iter().fold(first.clone(), |acc, x| {
+                    Interval::new(
+                        cmp_min(&acc.lo, &x.lo).clone(),
+                        cmp_min(&acc.hi, &x.hi).clone(),
+                    )
+                }));
+            }
+            ("max", [first, rest @ ..]) if !rest.is_empty() => {
+                return Ok(rest.iter().fold(first.clone(), |acc, x| {
+                    Interval::new(
+                        cmp_max(&acc.lo, &x.lo).clone(),
+                        cmp_max(&acc.hi, &x.hi).clone(),
+                    )
+                }));
+            }
+            ("floor" | "ceil" | "trunc" | "round" | "int", [x]) => {
+                let builtin = if name == "int" { "trunc" } else { name };
+                let lo = self.interval_builtin(builtin, &x.lo)?;
+                let hi = self.interval_builtin(builtin, &x.hi)?;
+                return Ok(Interval::new(lo, hi));
+            }
+            (
+                "sqrt" | "cbrt" | "exp" | "expm1" | "ln" | "log" | "log10" | "log2" | "atan"
+                | "arctan" | "asin" | "arcsin" | "sinh" | "tanh" | "asinh" | "acosh" | "atanh",
+                [x],
+            ) => return self.interval_monotone(name, x, true),
+            ("acos" | "arccos", [x]) => return self.interval_monotone(name, x, false),
+            ("cosh", [x]) => return self.interval_monotone(name, &x.abs(), true),
+            ("sin" | "cos" | "tan", [x]) => return self.interval_trig(name, x),
+            _ => {}
+        }
+
+        if args.iter().all(Interval::is_point) {
+            let values: Vec<Decimal> = args.iter().map(|arg| arg.lo.clone()).collect();
+            if let Some(result) = self.call_builtin_function(name, &values) {
+                let value = Interval::point(result?);
+                if NUMBER_THEORY.contains(&name) || EXACT_BUILTINS.contains(&name) {
+                    return Ok(value);
+                }
+                let precision = self.interval_precision(&[&value]);
+                return Ok(value.widen(&self.interval_margin(), precision));
+            }
+        }
+
+        let numbers = args.into_iter().map(BcNumber::interval).collect();
+        if let Some(result) = self.call_function(name, numbers)? {
+            return Ok(result.to_interval());
+        }
+
+        Err(BcError::Error(format!(
+            "{name}() is not defined for intervals"
+        )))
+    }
+
+    /// Applies a monotonic builtin to both ends, then widens by the library's
+    /// error so the enclosure stays guaranteed.
+    fn interval_monotone(
+        &mut self,
+        name: &str,
+        x: &Interval,
+        increasing: bool,
+    ) -> Result<Interval, BcError> {
+        let at_lo = self.interval_builtin(name, &x.lo)?;
+        let at_hi = if x.is_point() {
+            at_lo.clone()
+        } else {
+            self.interval_builtin(name, &x.hi)?
+        };
+        let ends = if increasing {
+            Interval::new(at_lo, at_hi)
+        } else {
+            Interval::new(at_hi, at_lo)
+        };
+        let precision = self.interval_precision(&[&ends]);
+        Ok(ends.widen(&self.interval_margin(), precision))
+    }
+
+    /// `pi` stands for half a turn in the current `angle` unit, so the
+    /// phases below hold in degrees and gradians as well.
+    fn interval_trig(&mut self, name: &str, x: &Interval) -> Result<Interval, BcError> {
+        let pi = Self::half_turn(self.runtime.angle(), self.interval_precision(&[x]));
+        let two_pi = pi.clone() * Decimal::from(2);
+        let half_pi = pi.clone() / Decimal::from(2);
+        if name == "tan" {
+            if x.contains_phase(&half_pi, &pi) {
+                return Err(BcError::Error("tan() interval contains a pole".to_string()));
+            }
+            return self.interval_monotone(name, x, true);
+        }
+        if x.hi.clone() - &x.lo >= two_pi {
+            return Ok(Interval::new(-Decimal::ONE, Decimal::ONE));
+        }
+        // Peaks of sin sit at pi/2 + 2k*pi and troughs at -pi/2 + 2k*pi;
+        // cos has them a quarter turn earlier.
+        let (peak, trough) = if name == "sin" {
+            (half_pi.clone(), -half_pi)
+        } else {
+            (Decimal::ZERO, pi)
+        };
+        let at_lo = self.interval_builtin(name, &x.lo)?;
+        let at_hi = self.interval_builtin(name, &x.hi)?;
+        let ends = Interval::new(
+            cmp_min(&at_lo, &at_hi).clone(),
+            cmp_max(&at_lo, &at_hi).clone(),
+        );
+        let precision = self.interval_precision(&[&ends]);
+        let widened = ends.widen(&self.interval_margin(), precision);
+        let (one, minus_one) = (Decimal::ONE, -Decimal::ONE);
+        let hi = if x.contains_phase(&peak, &two_pi) {
+            one.clone()
+        } else {
+            cmp_min(&widened.hi, &one).clone()
+        };
+        let lo = if x.contains_phase(&trough, &two_pi) {
+            minus_one
+        } else {
+            cmp_max(&widened.lo, &minus_one).clone()
+        };
+        Ok(Interval::new(lo, hi))
+    }
+
+    fn interval_builtin(&mut self, name: &str, value: &Decimal) -> Result<Decimal, BcError> {
+        self.call_builtin_function(name, std::slice::from_ref(value))
+            .unwrap_or_else(|| Err(BcError::Error(format!("Undefined identifier: {name}"))))
+    }
+
+    /// One unit in the last guard digit: math-library results are rounded
+    /// there, so the true value is never further away than this.
+    fn interval_margin(&self) -> Decimal {
+        let digits = self.runtime.scale() as usize + GUARD_DIGITS;
+        Decimal::from_parts(IBig::ONE, -(digits as isize))
+    }
+
+    fn interval_precision(&self, intervals: &[&Interval]) -> usize {
+        let digits = intervals
+            .iter()
+            .map(|x| mathlib::integer_digits(&x.lo).max(mathlib::integer_digits(&x.hi)))
+            .sum();
+        self.working_precision(digits)
+    }
+
+    fn interval_bool(value: bool) -> Interval {
+        Interval::point(if value { Decimal::ONE } else { Decimal::ZERO })
+    }
+}
+
+/// `value` rounded to `scale` fractional digits, toward +inf when `up`
+/// and toward -inf otherwise.
+fn directed_at_scale(value: &Decimal, scale: u32, up: bool) -> Decimal {
+    let repr = value.repr();
+    let shift = repr.exponent() + scale as isize;
+    let units = if shift >= 0 {
+        repr.significand() * IBig::from(10).pow(shift as usize)
+    } else {
+        let divisor = IBig::from(10).pow(shift.unsigned_abs());
+        let quotient = repr.significand() / &divisor;
+        let exact = &quotient * &divisor == *repr.significand();
+        let positive = repr.significand().sign() == Sign::Positive;
+        match (exact, up, positive) {
+            (false, true, true) => quotient + IBig::ONE,
+            (false, false, false) => quotient - IBig::ONE,
+            _ => quotient,
+        }
+    };
+    Decimal::from_parts(units, -(scale as isize))
+}
+
+/// Lifts `value` exactly into a round-toward-minus-infinity context.
+fn lower(value: &Decimal, precision: usize) -> Lower {
+    let precision = precision.max(value.repr().digits());
+    value
+        .clone()
+        .with_rounding::<Down>()
+        .with_precision(precision)
+        .value()
+}
+
+/// Lifts `value` exactly into a round-toward-plus-infinity context.
+fn upper(value: &Decimal, precision: usize) -> Upper {
+    let precision = precision.max(value.repr().digits());
+    value
+        .clone()
+        .with_rounding::<Up>()
+        .with_precision(precision)
+        .value()
+}
+
+fn from_lower(value: Lower) -> Decimal {
+    value.with_rounding()
+}
+
+fn from_upper(value: Upper) -> Decimal {
+    value.with_rounding()
+}
+
+fn cmp_min<'a>(a: &'a Decimal, b: &'a Decimal) -> &'a Decimal {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+fn cmp_max<'a>(a: &'a Decimal, b: &'a Decimal) -> &'a Decimal {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
@@ -1,8 +1,36 @@
 use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 use dashu::Decimal;
 
 use super::error::BcError;
+use super::number::BcNumber;
+use super::units::Unit;
+
+/// SI prefixes and their powers of ten, smallest first. `u` is accepted on
+/// input as the ASCII spelling of `µ`.
+pub(crate) const SI_PREFIXES: [(char, i32); 16] = [
+    ('y', -24),
+    ('z', -21),
+    ('a', -18),
+    ('f', -15),
+    ('p', -12),
+    ('n', -9),
+    ('µ', -6),
+    ('m', -3),
+    ('k', 3),
+    ('M', 6),
+    ('G', 9),
+    ('T', 12),
+    ('P', 15),
+    ('E', 18),
+    ('Z', 21),
+    ('Y', 24),
+];
+
+/// Largest exponent a `1e...` literal may carry; anything bigger would
+/// expand to an unprintable digit string.
+const MAX_LITERAL_EXPONENT: i64 = 100_000;
 
 #[derive(Debug, Default)]
 pub(crate) struct LiteralTable {
@@ -20,12 +48,35 @@ impl LiteralTable {
         self.values.get(name).cloned()
     }
 
-    pub fn substitute(&mut self, expr: &str) -> Result<String, BcError> {
+    pub fn substitute(&mut self, expr: &str, ibase: u32) -> Result<String, BcError> {
         let mut result = String::with_capacity(expr.len());
         let chars: Vec<char> = expr.chars().collect();
         let mut index = 0;
         while index < chars.len() {
-            if let Some((literal, consumed)) = Self::extract_numeric_literal(&chars, index) {
+            if let Some(consumed) = Self::scan_radix_literal(&chars, index, ibase) {
+                let literal: String = chars[index..index + consumed].iter().collect();
+                let number = BcNumber::parse_in_base(&literal, ibase)?;
+                let name = self.next_literal_name();
+                self.values.insert(name.clone(), number.value);
+                result.push_str(&name);
+                index += consumed;
+            } else if ibase != 10 {
+                if Self::is_digit_start(&chars, index) {
+                    return Err(BcError::Error(format!(
+                        "Failed to parse literal in ibase {ibase}"
+                    )));
+                }
+                result.push(chars[index]);
+                index += 1;
+            } else if let Some(consumed) = Self::scan_scientific_literal(&chars, index) {
+                let literal: String = chars[index..index + consumed].iter().collect();
+                let number = BcNumber::parse_scientific(&literal)
+                    .ok_or_else(|| BcError::Error(format!("Failed to parse literal: {literal}")))?;
+                let name = self.next_literal_name();
+                self.values.insert(name.clone(), number.value);
+                result.push_str(&name);
+                index += consumed;
+            } else if let Some((literal, consumed)) = Self::extract_numeric_literal(&chars, index) {
                 let name = self.next_literal_name();
                 let decimal = literal
                     .parse::<Decimal>()
@@ -41,6 +92,356 @@ impl LiteralTable {
         Ok(result)
     }
 
+    /// Length of a `0x`/`0b`/`0o` literal at `start`, or, when `ibase` is
+    /// not ten, of a bc numeral written with digits `0-9` and `A-Z`.
+    pub(crate) fn scan_radix_literal(chars: &[char], start: usize, ibase: u32) -> Option<usize> {
+        if start > 0 && Self::is_word_char(chars[start - 1]) {
+            return None;
+        }
+        let is_upper_digit = |ch: char| ch.is_ascii_digit() || ch.is_ascii_uppercase();
+        let mut index = start;
+
+        let prefix_radix = match (chars.get(start), chars.get(start + 1)) {
+            (Some('0'), Some('x')) => Some(16),
+            (Some('0'), Some('b')) => Some(2),
+            (Some('0'), Some('o')) => Some(8),
+            _ => None,
+        };
+        if let Some(radix) = prefix_radix {
+            index += 2;
+            while index < chars.len() && chars[index].is_digit(radix) {
+                index += 1;
+            }
+        } else if ibase != 10 {
+            let first = *chars.get(start)?;
+            let leads_fraction =
+                first == '.' && chars.get(start + 1).is_some_and(|c| is_upper_digit(*c));
+            if !is_upper_digit(first) && !leads_fraction {
+                return None;
+            }
+            while index < chars.len() && is_upper_digit(chars[index]) {
+                index += 1;
+            }
+            if index < chars.len() && chars[index] == '.' {
+                index += 1;
+                while index < chars.len() && is_upper_digit(chars[index]) {
+                    index += 1;
+                }
+            }
+            // An uppercase word only reads as a numeral when every digit fits
+            // `ibase` (or it is a single digit); otherwise it stays a name.
+            if first.is_ascii_uppercase() && index - start > 1 {
+                let fits = chars[start..index]
+                    .iter()
+                    .all(|c| *c == '.' || c.to_digit(36).is_some_and(|d| d < ibase));
+                if !fits {
+                    return None;
+                }
+            }
+        } else {
+            return None;
+        }
+
+        let digits_end = if prefix_radix.is_some() {
+            start + 2
+        } else {
+            start
+        };
+        if index == digits_end || chars.get(index).is_some_and(|c| Self::is_word_char(*c)) {
+            return None;
+        }
+        Some(index - start)
+    }
+
+    /// Length of a `9.81±0.02` (or `9.81 +/- 0.02`) measurement literal at
+    /// `start`: two plain decimals around the `±`.
+    pub(crate) fn scan_measurement_literal(chars: &[char], start: usize) -> Option<usize> {
+        if start > 0 && Self::is_word_char(chars[start - 1]) {
+            return None;
+        }
+        let decimal_end = |mut index: usize| {
+            let begin = index;
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+            let digits = chars[begin..index].iter().filter(|c| c.is_ascii_digit());
+            (digits.count() > 0).then_some(index)
+        };
+        let skip_spaces = |mut index: usize| {
+            while index < chars.len() && chars[index] == ' ' {
+                index += 1;
+            }
+            index
+        };
+        let mut index = skip_spaces(decimal_end(start)?);
+        index += match chars.get(index..) {
+            Some(['±', ..]) => 1,
+            Some(['+', '/', '-', ..]) => 3,
+            _ => return None,
+        };
+        let end = decimal_end(skip_spaces(index))?;
+        if chars.get(end).is_some_and(|c| Self::is_word_char(*c)) {
+            return None;
+        }
+        Some(end - start)
+    }
+
+    /// Length of a `1.5e-9` or `4.7k` literal at `start`: a plain decimal
+    /// followed by an exponent or a single SI prefix.
+    pub(crate) fn scan_scientific_literal(chars: &[char], start: usize) -> Option<usize> {
+        if start > 0 && Self::is_word_char(chars[start - 1]) {
+            return None;
+        }
+        let mut index = start;
+        while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+            index += 1;
+        }
+        let mantissa = &chars[start..index];
+        if !mantissa.iter().any(char::is_ascii_digit)
+            || mantissa.iter().filter(|c| **c == '.').count() > 1
+        {
+            return None;
+        }
+        let exponent_digits = |from: usize| {
+            let mut end = from;
+            if matches!(chars.get(end), Some('+' | '-')) {
+                end += 1;
+            }
+            let digits = end;
+            while end < chars.len() && chars[end].is_ascii_digit() {
+                end += 1;
+            }
+            (end > digits).then_some(end)
+        };
+        let end = match chars.get(index) {
+            Some('e' | 'E') if exponent_digits(index + 1).is_some() => exponent_digits(index + 1)?,
+            Some(ch) if Self::si_power(*ch).is_some() => index + 1,
+            _ => return None,
+        };
+        if chars.get(end).is_some_and(|c| Self::is_word_char(*c)) {
+            return None;
+        }
+        Some(end - start)
+    }
+
+    /// Splits a scientific literal into its decimal mantissa and power of ten.
+    pub(crate) fn split_scientific(literal: &str) -> Option<(&str, i64)> {
+        let last = literal.chars().next_back()?;
+        if let Some(power) = Self::si_power(last) {
+            return Some((
+                &literal[..literal.len() - last.len_utf8()],
+                i64::from(power),
+            ));
+        }
+        let (mantissa, exponent) = literal.split_once(['e', 'E'])?;
+        let exponent: i64 = exponent.parse().ok()?;
+        (exponent.abs() <= MAX_LITERAL_EXPONENT).then_some((mantissa, exponent))
+    }
+
+    pub(crate) fn si_power(prefix: char) -> Option<i32> {
+        let prefix = if prefix == 'u' { 'µ' } else { prefix };
+        SI_PREFIXES
+            .iter()
+            .find(|(symbol, _)| *symbol == prefix)
+            .map(|(_, power)| *power)
+    }
+
+    /// Length of a `2026-10-18` date or `2026-10-18T09:30:15.5+09:00`
+    /// date-time literal at `start`. A time may end in `Z` or a `±HH:MM`
+    /// offset; without one it is read as UTC.
+    pub(crate) fn scan_date_literal(chars: &[char], start: usize) -> Option<usize> {
+        if start > 0 && Self::is_word_char(chars[start - 1]) {
+            return None;
+        }
+        let digits = |from: usize, count: usize| {
+            let end = from + count;
+            (end <= chars.len() && chars[from..end].iter().all(char::is_ascii_digit)).then_some(end)
+        };
+        let expect = |index: usize, ch: char| (chars.get(index) == Some(&ch)).then_some(index + 1);
+        let mut end = digits(expect(digits(expect(digits(start, 4)?, '-')?, 2)?, '-')?, 2)?;
+        if chars.get(end) == Some(&'T') {
+            end = digits(expect(digits(end + 1, 2)?, ':')?, 2)?;
+            if let Some(seconds) = expect(end, ':').and_then(|index| digits(index, 2)) {
+                end = seconds;
+                if chars.get(end) == Some(&'.') {
+                    let fraction = end + 1;
+                    end = fraction;
+                    while end < chars.len() && chars[end].is_ascii_digit() {
+                        end += 1;
+                    }
+                    if end == fraction {
+                        return None;
+                    }
+                }
+            }
+            match chars.get(end) {
+                Some('Z') => end += 1,
+                Some('+' | '-') => end = digits(expect(digits(end + 1, 2)?, ':')?, 2)?,
+                _ => {}
+            }
+        }
+        if chars.get(end).is_some_and(|c| Self::is_word_char(*c)) {
+            return None;
+        }
+        Some(end - start)
+    }
+
+    /// Length of a `1h30m` or `3d` duration literal at `start`: numbers
+    /// each followed by `w`, `d`, `h`, `min`, `m` or `s`. A lone `5m` stays
+    /// the SI literal for 0.005, so minutes on their own are `5min`.
+    pub(crate) fn scan_duration_literal(chars: &[char], start: usize) -> Option<usize> {
+        if start > 0 && Self::is_word_char(chars[start - 1]) {
+            return None;
+        }
+        let mut index = start;
+        let mut components = Vec::new();
+        while chars
+            .get(index)
+            .is_some_and(|c| c.is_ascii_digit() || *c == '.')
+        {
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+            let unit = Self::duration_unit(&chars[index..])?;
+            components.push(unit);
+            index += unit.len();
+        }
+        if components.is_empty()
+            || components == ["m"]
+            || chars.get(index).is_some_and(|c| Self::is_word_char(*c))
+        {
+            return None;
+        }
+        Some(index - start)
+    }
+
+    /// The duration unit `rest` starts with.
+    fn duration_unit(rest: &[char]) -> Option<&'static str> {
+        const UNITS: [&str; 6] = ["min", "w", "d", "h", "m", "s"];
+        UNITS
+            .iter()
+            .copied()
+            .find(|unit| rest.iter().copied().take(unit.len()).eq(unit.chars()))
+    }
+
+    /// Length of an IPv4 (`10.0.0.1`) or IPv6 (`2001:db8::1`) address
+    /// literal at `start`, with an optional `/22` prefix length right after
+    /// it. IPv6 addresses need at least two colons, so `10:30` is left alone.
+    pub(crate) fn scan_ip_literal(chars: &[char], start: usize) -> Option<usize> {
+        if start > 0 && (Self::is_word_char(chars[start - 1]) || chars[start - 1] == ':') {
+            return None;
+        }
+        let mut end = start;
+        while end < chars.len() && (chars[end].is_ascii_hexdigit() || ".:".contains(chars[end])) {
+            end += 1;
+        }
+        let address: String = chars[start..end].iter().collect();
+        let valid = if address.matches(':').count() >= 2 {
+            address.parse::<Ipv6Addr>().is_ok()
+        } else {
+            address.parse::<Ipv4Addr>().is_ok()
+        };
+        if !valid {
+            return None;
+        }
+        if chars.get(end) == Some(&'/') {
+            let digits = chars[end + 1..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            if digits > 0 {
+                end += 1 + digits;
+            }
+        }
+        if chars.get(end).is_some_and(|c| Self::is_word_char(*c)) {
+            return None;
+        }
+        Some(end - start)
+    }
+
+    /// Length of a unit suffix such as ` km` or ` m/s^2` right after a
+    /// number: optional spaces, then known units joined by `/`, `*` or `·`,
+    /// each with an optional integer power.
+    pub(crate) fn scan_unit_suffix(chars: &[char], start: usize) -> Option<usize> {
+        let skip_spaces = |mut index: usize| {
+            while index < chars.len() && chars[index] == ' ' {
+                index += 1;
+            }
+            index
+        };
+        let mut end = Self::scan_unit_term(chars, skip_spaces(start))?;
+        loop {
+            let joint = skip_spaces(end);
+            if !matches!(chars.get(joint), Some('/' | '*' | '·')) {
+                break;
+            }
+            match Self::scan_unit_term(chars, skip_spaces(joint + 1)) {
+                Some(term_end) => end = term_end,
+                None => break,
+            }
+        }
+        Some(end - start)
+    }
+
+    /// End of a `km` or `s^-2` unit term at `start`; a name followed by `(`
+    /// is a function call instead.
+    fn scan_unit_term(chars: &[char], start: usize) -> Option<usize> {
+        let mut index = start;
+        while index < chars.len() && (chars[index].is_alphabetic() || chars[index] == '_') {
+            index += 1;
+        }
+        let name: String = chars[start..index].iter().collect();
+        if name.is_empty()
+            || Unit::named(&name).is_none()
+            || chars
+                .get(index)
+                .is_some_and(|c| *c == '(' || c.is_ascii_digit())
+        {
+            return None;
+        }
+        if chars.get(index) == Some(&'^') {
+            let mut end = index + 1;
+            if chars.get(end) == Some(&'-') {
+                end += 1;
+            }
+            let digits = end;
+            while end < chars.len() && chars[end].is_ascii_digit() {
+                end += 1;
+            }
+            if end > digits {
+                index = end;
+            }
+        }
+        Some(index)
+    }
+
+    /// Splits a measurement literal into its value and uncertainty.
+    pub(crate) fn split_measurement(literal: &str) -> Option<(&str, &str)> {
+        let (value, sigma) = literal
+            .split_once('±')
+            .or_else(|| literal.split_once("+/-"))?;
+        Some((value.trim(), sigma.trim()))
+    }
+
+    /// `scan_radix_literal` for lexers that walk a `&str` rather than chars.
+    pub(crate) fn radix_literal_len(input: &str, ibase: u32) -> Option<usize> {
+        let chars: Vec<char> = input
+            .chars()
+            .take_while(|c| Self::is_word_char(*c))
+            .chain(std::iter::once(' '))
+            .collect();
+        Self::scan_radix_literal(&chars, 0, ibase)
+    }
+
+    fn is_digit_start(chars: &[char], index: usize) -> bool {
+        let starts_word = index == 0 || !Self::is_word_char(chars[index - 1]);
+        starts_word && chars[index].is_ascii_digit()
+    }
+
+    fn is_word_char(ch: char) -> bool {
+        ch.is_ascii_alphanumeric() || ch == '_' || ch == '.'
+    }
+
     fn next_literal_name(&mut self) -> String {
         let name = format!("__dntk_lit{}", self.counter);
         self.counter = self.counter.wrapping_add(1);
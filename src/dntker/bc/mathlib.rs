@@ -0,0 +1,533 @@
+use dashu::base::{Abs, Approximation, Sign, SquareRoot};
+use dashu::integer::IBig;
+use dashu::rational::RBig;
+use dashu::Decimal;
+use num_traits::{ToPrimitive, Zero};
+
+use super::error::BcError;
+use super::runtime::AngleUnit;
+
+/// Fractional digits kept past `scale` when rounding a math-library result.
+pub(super) const GUARD_DIGITS: usize = 5;
+
+/// Extra significant digits used while a series or reduction is running.
+pub(super) const WORK_DIGITS: usize = 10;
+
+pub(super) type UnaryFn = fn(&Decimal, usize) -> Result<Decimal, BcError>;
+pub(super) type BinaryFn = fn(&Decimal, &Decimal, usize) -> Result<Decimal, BcError>;
+
+impl super::BcExecuter {
+    /// Number of significant digits needed to get `scale` correct fractional digits
+    /// out of a value whose integer part has `int_digits` digits.
+    pub(super) fn working_precision(&self, int_digits: usize) -> usize {
+        (self.runtime.scale() as usize)
+            .saturating_add(GUARD_DIGITS)
+            .saturating_add(int_digits)
+            .max(1)
+    }
+
+    pub(super) fn precise_unary(
+        &self,
+        name: &str,
+        args: &[Decimal],
+        func: UnaryFn,
+    ) -> Result<Decimal, BcError> {
+        if args.len() != 1 {
+            return Err(BcError::Error(format!(
+                "{}() expects 1 argument, got {}",
+                name,
+                args.len()
+            )));
+        }
+        self.eval_precise(&[&args[0]], |precision| func(&args[0], precision))
+    }
+
+    pub(super) fn precise_binary(
+        &self,
+        name: &str,
+        args: &[Decimal],
+        func: BinaryFn,
+    ) -> Result<Decimal, BcError> {
+        if args.len() != 2 {
+            return Err(BcError::Error(format!(
+                "{}() expects 2 arguments, got {}",
+                name,
+                args.len()
+            )));
+        }
+        self.eval_precise(&[&args[0], &args[1]], |precision| {
+            func(&args[0], &args[1], precision)
+        })
+    }
+
+    /// `sin`, `cos` or `tan` of an angle in the current `angle` unit.
+    pub(super) fn precise_trig(
+        &self,
+        name: &str,
+        args: &[Decimal],
+        func: UnaryFn,
+    ) -> Result<Decimal, BcError> {
+        if args.len() != 1 {
+            return Err(BcError::Error(format!(
+                "{}() expects 1 argument, got {}",
+                name,
+                args.len()
+            )));
+        }
+        if name == "tan" && self.is_odd_quarter_turn(&args[0]) {
+            return Err(domain_error("tan"));
+        }
+        self.eval_precise(&[&args[0]], |precision| {
+            func(&self.radians_of(&args[0], precision), precision)
+        })
+    }
+
+    /// Whether `angle` is an odd multiple of a quarter turn, where `tan` has
+    /// its poles. Only degrees and gradians can hit one exactly: converted
+    /// to radians the cosine comes out tiny rather than 0.
+    pub(super) fn is_odd_quarter_turn(&self, angle: &Decimal) -> bool {
+        let Some(half_turn) = self.runtime.angle().half_turn() else {
+            return false;
+        };
+        let quarters = Self::decimal_to_rational(angle) / RBig::from(half_turn / 2);
+        quarters.denominator().is_one() && !(quarters.numerator() % IBig::from(2)).is_zero()
+    }
+
+    /// `asin`, `acos` or `atan`, with the angle given back in the current
+    /// `angle` unit.
+    pub(super) fn precise_inverse_trig(
+        &self,
+        name: &str,
+        args: &[Decimal],
+        func: UnaryFn,
+    ) -> Result<Decimal, BcError> {
+        if args.len() != 1 {
+            return Err(BcError::Error(format!(
+                "{}() expects 1 argument, got {}",
+                name,
+                args.len()
+            )));
+        }
+        self.eval_precise(&[&args[0]], |precision| {
+            let radians = func(&args[0], precision + WORK_DIGITS)?;
+            Ok(self.angle_of(&radians, precision))
+        })
+    }
+
+    /// `atan2(y, x)` in the current `angle` unit.
+    pub(super) fn precise_atan2(&self, args: &[Decimal]) -> Result<Decimal, BcError> {
+        if args.len() != 2 {
+            return Err(BcError::Error(format!(
+                "atan2() expects 2 arguments, got {}",
+                args.len()
+            )));
+        }
+        self.eval_precise(&[&args[0], &args[1]], |precision| {
+            let radians = atan2(&args[0], &args[1], precision + WORK_DIGITS)?;
+            Ok(self.angle_of(&radians, precision))
+        })
+    }
+
+    /// Half a turn in `unit`: pi, 180 or 200.
+    pub(super) fn half_turn(unit: AngleUnit, precision: usize) -> Decimal {
+        match unit.half_turn() {
+            Some(units) => at_precision(&Decimal::from(units), precision),
+            None => pi(precision),
+        }
+    }
+
+    /// An angle in the current `angle` unit, in radians. The conversion runs
+    /// with extra digits so that `sin(180)` in degrees still comes out 0.
+    pub(super) fn radians_of(&self, angle: &Decimal, precision: usize) -> Decimal {
+        self.convert_angle(angle, self.runtime.angle(), AngleUnit::Radians, precision)
+    }
+
+    /// An angle in radians, in the current `angle` unit.
+    pub(super) fn angle_of(&self, radians: &Decimal, precision: usize) -> Decimal {
+        self.convert_angle(radians, AngleUnit::Radians, self.runtime.angle(), precision)
+    }
+
+    /// `angle` in `from` units, expressed in `to` units.
+    pub(super) fn convert_angle(
+        &self,
+        angle: &Decimal,
+        from: AngleUnit,
+        to: AngleUnit,
+        precision: usize,
+    ) -> Decimal {
+        if from == to {
+            return angle.clone();
+        }
+        let work = precision + WORK_DIGITS;
+        at_precision(angle, work) * Self::half_turn(to, work) / Self::half_turn(from, work)
+    }
+
+    /// Runs `func` at a precision that covers both the arguments and the result,
+    /// retrying once when the result turns out to have more integer digits than
+    /// the first guess allowed for, then rounds to `scale` plus guard digits.
+    pub(super) fn eval_precise(
+        &self,
+        inputs: &[&Decimal],
+        func: impl Fn(usize) -> Result<Decimal, BcError>,
+    ) -> Result<Decimal, BcError> {
+        let input_digits = inputs.iter().map(|v| integer_digits(v)).max().unwrap_or(0);
+        let mut precision = self.working_precision(input_digits);
+        let mut result = func(precision)?;
+        let needed = self.working_precision(integer_digits(&result));
+        if needed > precision {
+            precision = needed;
+            result = func(precision)?;
+        }
+        let rounded = round_fraction(&result, self.runtime.scale() as usize + GUARD_DIGITS);
+        Ok(self.promote_precision(rounded))
+    }
+}
+
+/// Count of digits before the decimal point (0 for values below 1).
+pub(super) fn integer_digits(value: &Decimal) -> usize {
+    let repr = value.repr();
+    if repr.significand().is_zero() {
+        return 0;
+    }
+    let digits = repr.digits() as isize + repr.exponent();
+    digits.max(0) as usize
+}
+
+/// Rounds half away from zero to `digits` fractional digits.
+pub(super) fn round_fraction(value: &Decimal, digits: usize) -> Decimal {
+    let factor = Decimal::from(10).powi(digits.into());
+    (value.clone() * &factor).round() / factor
+}
+
+pub(super) fn at_precision(value: &Decimal, precision: usize) -> Decimal {
+    let precision = precision.max(value.repr().digits());
+    match value.clone().with_precision(precision) {
+        Approximation::Exact(v) | Approximation::Inexact(v, _) => v,
+    }
+}
+
+pub(super) fn epsilon(precision: usize) -> Decimal {
+    Decimal::from_parts(IBig::from(1), -(precision as isize))
+}
+
+pub(super) fn domain_error(name: &str) -> BcError {
+    BcError::Error(format!("{name}() argument out of domain"))
+}
+
+pub(super) fn pi(precision: usize) -> Decimal {
+    // Machin: pi = 16*atan(1/5) - 4*atan(1/239)
+    let work = precision + WORK_DIGITS;
+    let one = at_precision(&Decimal::from(1), work);
+    let a = atan_series(&(one.clone() / Decimal::from(5)), work);
+    let b = atan_series(&(one / Decimal::from(239)), work);
+    Decimal::from(16) * a - Decimal::from(4) * b
+}
+
+/// Taylor series for atan, only used for |x| well below 1.
+fn atan_series(x: &Decimal, precision: usize) -> Decimal {
+    let eps = epsilon(precision);
+    let x2 = x.clone() * x;
+    let mut power = x.clone();
+    let mut sum = x.clone();
+    let mut n = 1i64;
+    loop {
+        power = -(power * &x2);
+        n += 2;
+        let term = power.clone() / Decimal::from(n);
+        if term.clone().abs() < eps {
+            break;
+        }
+        sum += term;
+    }
+    sum
+}
+
+/// sin(x) and cos(x) for |x| <= pi/4 via Taylor series.
+fn sin_cos_series(x: &Decimal, precision: usize) -> (Decimal, Decimal) {
+    let eps = epsilon(precision);
+    let x2 = x.clone() * x;
+    let one = at_precision(&Decimal::from(1), precision);
+
+    let mut sin_term = x.clone();
+    let mut sin_sum = x.clone();
+    let mut cos_term = one.clone();
+    let mut cos_sum = one;
+    let mut n = 0i64;
+    loop {
+        n += 2;
+        cos_term = -(cos_term * &x2) / Decimal::from(n * (n - 1));
+        sin_term = -(sin_term * &x2) / Decimal::from(n * (n + 1));
+        if cos_term.clone().abs() < eps && sin_term.clone().abs() < eps {
+            break;
+        }
+        cos_sum += cos_term.clone();
+        sin_sum += sin_term.clone();
+    }
+    (sin_sum, cos_sum)
+}
+
+/// Reduces x to r in [-pi/4, pi/4] with x = r + k*pi/2, returning (r, k mod 4).
+fn reduce_quarter_turns(x: &Decimal, precision: usize) -> (Decimal, u8) {
+    let work = precision + integer_digits(x) + WORK_DIGITS;
+    let x = at_precision(x, work);
+    let half_pi = pi(work) / Decimal::from(2);
+    let quotient = (x.clone() / &half_pi).round();
+    let r = x - quotient.clone() * half_pi;
+    let k = (quotient % Decimal::from(4)).trunc();
+    let k = ToPrimitive::to_i64(&k).unwrap_or(0).rem_euclid(4) as u8;
+    (r, k)
+}
+
+fn sin_cos(x: &Decimal, precision: usize) -> (Decimal, Decimal) {
+    if x.is_zero() {
+        return (Decimal::ZERO, Decimal::from(1));
+    }
+    let (r, k) = reduce_quarter_turns(x, precision);
+    let (s, c) = sin_cos_series(&r, precision + WORK_DIGITS);
+    match k {
+        0 => (s, c),
+        1 => (c, -s),
+        2 => (-s, -c),
+        _ => (-c, s),
+    }
+}
+
+pub(super) fn sin(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    Ok(sin_cos(x, precision).0)
+}
+
+pub(super) fn cos(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    Ok(sin_cos(x, precision).1)
+}
+
+pub(super) fn tan(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let (s, c) = sin_cos(x, precision);
+    if c.is_zero() {
+        return Err(domain_error("tan"));
+    }
+    Ok(s / c)
+}
+
+pub(super) fn atan(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.is_zero() {
+        return Ok(Decimal::ZERO);
+    }
+    let work = precision + WORK_DIGITS;
+    let one = at_precision(&Decimal::from(1), work);
+    let negative = x.sign() == Sign::Negative;
+    let mut value = at_precision(&x.clone().abs(), work);
+
+    let inverted = value > one;
+    if inverted {
+        value = one.clone() / value;
+    }
+
+    // atan(x) = 2*atan(x / (1 + sqrt(1 + x^2))) shrinks the argument quickly.
+    let threshold = Decimal::from(1) / Decimal::from(10);
+    let mut doublings = 0u32;
+    while value > threshold {
+        let root = (one.clone() + value.clone() * &value).sqrt();
+        value /= one.clone() + root;
+        doublings += 1;
+    }
+
+    let mut result = atan_series(&value, work) * Decimal::from(2).powi(doublings.into());
+    if inverted {
+        result = pi(work) / Decimal::from(2) - result;
+    }
+    Ok(if negative { -result } else { result })
+}
+
+pub(super) fn asin(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let one = Decimal::from(1);
+    let magnitude = x.clone().abs();
+    if magnitude > one {
+        return Err(domain_error("asin"));
+    }
+    if magnitude == one {
+        let half_pi = pi(precision) / Decimal::from(2);
+        return Ok(if x.sign() == Sign::Negative {
+            -half_pi
+        } else {
+            half_pi
+        });
+    }
+    let work = precision + WORK_DIGITS;
+    let x = at_precision(x, work);
+    let denom = (at_precision(&one, work) - x.clone() * &x).sqrt();
+    atan(&(x / denom), precision)
+}
+
+pub(super) fn acos(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.clone().abs() > Decimal::from(1) {
+        return Err(domain_error("acos"));
+    }
+    let half_pi = pi(precision) / Decimal::from(2);
+    Ok(half_pi - asin(x, precision)?)
+}
+
+pub(super) fn atan2(y: &Decimal, x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.is_zero() {
+        if y.is_zero() {
+            return Ok(Decimal::ZERO);
+        }
+        let half_pi = pi(precision) / Decimal::from(2);
+        return Ok(if y.sign() == Sign::Negative {
+            -half_pi
+        } else {
+            half_pi
+        });
+    }
+    let work = precision + WORK_DIGITS;
+    let ratio = at_precision(y, work) / at_precision(x, work);
+    let base = atan(&ratio, precision)?;
+    if x.sign() == Sign::Positive {
+        return Ok(base);
+    }
+    let full_pi = pi(precision);
+    if y.sign() == Sign::Negative {
+        Ok(base - full_pi)
+    } else {
+        Ok(base + full_pi)
+    }
+}
+
+pub(super) fn sqrt(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.sign() == Sign::Negative && !x.is_zero() {
+        return Err(domain_error("sqrt"));
+    }
+    if x.is_zero() {
+        return Ok(Decimal::ZERO);
+    }
+    Ok(at_precision(x, precision + WORK_DIGITS).sqrt())
+}
+
+pub(super) fn cbrt(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.is_zero() {
+        return Ok(Decimal::ZERO);
+    }
+    let work = precision + WORK_DIGITS;
+    let magnitude = at_precision(&x.clone().abs(), work);
+    let root = (magnitude.ln() / Decimal::from(3)).exp();
+    Ok(if x.sign() == Sign::Negative {
+        -root
+    } else {
+        root
+    })
+}
+
+pub(super) fn exp(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    Ok(at_precision(x, precision + WORK_DIGITS).exp())
+}
+
+pub(super) fn expm1(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    Ok(at_precision(x, precision + WORK_DIGITS).exp_m1())
+}
+
+pub(super) fn ln(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.sign() == Sign::Negative || x.is_zero() {
+        return Err(domain_error("ln"));
+    }
+    Ok(at_precision(x, precision + WORK_DIGITS).ln())
+}
+
+pub(super) fn log(base: &Decimal, x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.sign() == Sign::Negative || x.is_zero() {
+        return Err(BcError::Error(
+            "log() expects positive argument".to_string(),
+        ));
+    }
+    if base.sign() == Sign::Negative || base.is_zero() || *base == Decimal::from(1) {
+        return Err(BcError::Error(
+            "log() base must be positive and not equal to 1".to_string(),
+        ));
+    }
+    Ok(ln(x, precision)? / ln(base, precision)?)
+}
+
+pub(super) fn log10(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    log(&Decimal::from(10), x, precision)
+}
+
+pub(super) fn log2(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    log(&Decimal::from(2), x, precision)
+}
+
+pub(super) fn sinh(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let e = exp(x, precision)?;
+    Ok((e.clone() - Decimal::from(1) / e) / Decimal::from(2))
+}
+
+pub(super) fn cosh(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let e = exp(x, precision)?;
+    Ok((e.clone() + Decimal::from(1) / e) / Decimal::from(2))
+}
+
+pub(super) fn tanh(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let e2 = exp(&(x.clone() * Decimal::from(2)), precision)?;
+    let one = Decimal::from(1);
+    Ok((e2.clone() - &one) / (e2 + one))
+}
+
+pub(super) fn asinh(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.is_zero() {
+        return Ok(Decimal::ZERO);
+    }
+    let work = precision + WORK_DIGITS;
+    let magnitude = at_precision(&x.clone().abs(), work);
+    let inner = magnitude.clone() + (magnitude.clone() * &magnitude + Decimal::from(1)).sqrt();
+    let result = inner.ln();
+    Ok(if x.sign() == Sign::Negative {
+        -result
+    } else {
+        result
+    })
+}
+
+pub(super) fn acosh(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if *x < Decimal::from(1) {
+        return Err(domain_error("acosh"));
+    }
+    let x = at_precision(x, precision + WORK_DIGITS);
+    let inner = x.clone() + (x.clone() * &x - Decimal::from(1)).sqrt();
+    Ok(inner.ln())
+}
+
+pub(super) fn atanh(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let one = Decimal::from(1);
+    if x.clone().abs() >= one {
+        return Err(domain_error("atanh"));
+    }
+    let x = at_precision(x, precision + WORK_DIGITS);
+    let ratio = (one.clone() + &x) / (one - x);
+    Ok(ratio.ln() / Decimal::from(2))
+}
+
+pub(super) fn hypot(x: &Decimal, y: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let work = precision + WORK_DIGITS;
+    let x = at_precision(x, work);
+    let y = at_precision(y, work);
+    sqrt(&(x.clone() * &x + y.clone() * &y), precision)
+}
+
+/// x^y for a non-integer exponent, computed as exp(y * ln(x)).
+pub(super) fn powf(
+    base: &Decimal,
+    exponent: &Decimal,
+    precision: usize,
+) -> Result<Decimal, BcError> {
+    if base.is_zero() {
+        if exponent.sign() == Sign::Negative {
+            return Err(BcError::Error("Division by zero".to_string()));
+        }
+        return Ok(Decimal::ZERO);
+    }
+    if base.sign() == Sign::Negative {
+        return Err(BcError::Error(
+            "Fractional power of a negative base is not a real number".to_string(),
+        ));
+    }
+    let work = precision + WORK_DIGITS;
+    let log = at_precision(base, work).ln();
+    Ok((log * at_precision(exponent, work)).exp())
+}
@@ -4,6 +4,8 @@ use dashu::Decimal;
 
 use super::complex::{ComplexNumber, ComplexParseError};
 use super::error::BcError;
+use super::literals::LiteralTable;
+use super::number::BcNumber;
 
 impl super::BcExecuter {
     pub(super) fn try_eval_matrix_expression(
@@ -182,40 +184,24 @@ impl<'a> MatrixParser<'a> {
                 for row in matrix {
                     let mut formatted_row = Vec::with_capacity(row.len());
                     for entry in row {
-                        let (real_f, imag_f) = entry
-                            .sin_components()
-                            .map_err(MatrixParseError::from_complex_error)?;
-                        let real = self
-                            .exec
-                            .decimal_from_f64(real_f, "matrix sin overflowed")
-                            .map_err(MatrixParseError::from_bc_error)?;
-                        let imag = self
-                            .exec
-                            .decimal_from_f64(imag_f, "matrix sin overflowed")
-                            .map_err(MatrixParseError::from_bc_error)?;
-                        formatted_row.push(ComplexNumber::new(real, imag));
+                        formatted_row.push(self.sin_entry(&entry)?);
                     }
                     result.push(formatted_row);
                 }
                 Ok(MatrixValue::Matrix(result))
             }
-            MatrixValue::Scalar(value) => {
-                let (real_f, imag_f) = value
-                    .sin_components()
-                    .map_err(MatrixParseError::from_complex_error)?;
-                let real = self
-                    .exec
-                    .decimal_from_f64(real_f, "matrix sin overflowed")
-                    .map_err(MatrixParseError::from_bc_error)?;
-                let imag = self
-                    .exec
-                    .decimal_from_f64(imag_f, "matrix sin overflowed")
-                    .map_err(MatrixParseError::from_bc_error)?;
-                Ok(MatrixValue::Scalar(ComplexNumber::new(real, imag)))
-            }
+            MatrixValue::Scalar(value) => Ok(MatrixValue::Scalar(self.sin_entry(&value)?)),
         }
     }
 
+    fn sin_entry(&self, entry: &ComplexNumber) -> Result<ComplexNumber, MatrixParseError> {
+        let (real, imag) = self
+            .exec
+            .complex_sin_components(entry)
+            .map_err(MatrixParseError::from_bc_error)?;
+        Ok(ComplexNumber::new(real, imag))
+    }
+
     fn peek(&self) -> Option<MatrixToken> {
         self.tokens.get(self.position).cloned()
     }
@@ -265,6 +251,7 @@ impl MatrixLexer {
         let mut tokens = Vec::new();
         let mut remaining = expr.trim_start();
         let mut seen_matrix = false;
+        let ibase = exec.runtime.ibase();
         while !remaining.is_empty() {
             let ch = remaining.chars().next().unwrap();
             match ch {
@@ -303,8 +290,8 @@ impl MatrixLexer {
                     tokens.push(MatrixToken::RParen);
                     remaining = &remaining[ch.len_utf8()..];
                 }
-                '0'..='9' | '.' => {
-                    let (number, rest) = MatrixLexer::parse_number(remaining)?;
+                c if c.is_ascii_digit() || c == '.' || (ibase != 10 && c.is_ascii_uppercase()) => {
+                    let (number, rest) = MatrixLexer::parse_number(remaining, ibase)?;
                     let promoted = exec.promote_precision(number.clone());
                     let mut scalar = ComplexNumber::from_real(promoted);
                     let mut remainder = rest;
@@ -344,7 +331,15 @@ impl MatrixLexer {
         Ok((tokens, seen_matrix))
     }
 
-    fn parse_number(input: &str) -> Result<(Decimal, &str), MatrixParseError> {
+    fn parse_number(input: &str, ibase: u32) -> Result<(Decimal, &str), MatrixParseError> {
+        if let Some(len) = LiteralTable::radix_literal_len(input, ibase) {
+            let number = BcNumber::parse_in_base(&input[..len], ibase)
+                .map_err(|_| MatrixParseError::NotMatrix)?;
+            return Ok((number.value, &input[len..]));
+        }
+        if ibase != 10 {
+            return Err(MatrixParseError::NotMatrix);
+        }
         let mut len = 0usize;
         let mut has_digit = false;
         let mut has_decimal = false;
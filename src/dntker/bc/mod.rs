@@ -1,14 +1,33 @@
 use super::util;
 
+mod arrays;
 mod complex;
+mod constants;
+mod datetime;
+mod distributions;
 mod error;
 mod execution;
 mod expression;
+mod finance;
 mod formatting;
+mod ieee754;
+mod interval;
 mod literals;
+mod mathlib;
 mod matrix;
+mod network;
+mod number;
+mod numtheory;
 mod parsing;
+mod posix;
+mod printing;
+mod programmer;
+mod rational;
 mod runtime;
+mod special;
+mod statistics;
+mod uncertain;
+mod units;
 
 #[allow(unused_imports)]
 pub use error::BcError;
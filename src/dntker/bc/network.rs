@@ -0,0 +1,449 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use dashu::integer::IBig;
+use dashu::Decimal;
+use num_traits::Zero;
+
+use super::error::BcError;
+use super::number::BcNumber;
+use super::numtheory::integer_arg;
+use super::posix::{PosixExpr, PosixOp, PosixParser};
+
+/// An IPv4 or IPv6 address; with a prefix length it also names the network
+/// around it, as in `192.168.0.0/22`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IpAddress {
+    pub bits: u128,
+    pub v6: bool,
+    pub prefix: Option<u32>,
+}
+
+impl IpAddress {
+    /// Parses a literal `scan_ip_literal` delimited.
+    pub(crate) fn parse(literal: &str) -> Result<Self, BcError> {
+        let invalid = || BcError::Error(format!("Invalid address: {literal}"));
+        let (address, prefix) = match literal.split_once('/') {
+            Some((address, prefix)) => {
+                (address, Some(prefix.parse::<u32>().map_err(|_| invalid())?))
+            }
+            None => (literal, None),
+        };
+        let parsed = if address.contains(':') {
+            let bits = u128::from(address.parse::<Ipv6Addr>().map_err(|_| invalid())?);
+            Self {
+                bits,
+                v6: true,
+                prefix,
+            }
+        } else {
+            let bits = u32::from(address.parse::<Ipv4Addr>().map_err(|_| invalid())?);
+            Self {
+                bits: u128::from(bits),
+                v6: false,
+                prefix,
+            }
+        };
+        if prefix.is_some_and(|prefix| prefix > parsed.width()) {
+            return Err(BcError::Error(format!("Invalid prefix length: {literal}")));
+        }
+        Ok(parsed)
+    }
+
+    /// 32 for IPv4, 128 for IPv6.
+    fn width(&self) -> u32 {
+        if self.v6 {
+            128
+        } else {
+            32
+        }
+    }
+
+    /// The address with every bit set.
+    fn all_ones(&self) -> u128 {
+        u128::MAX >> (128 - self.width())
+    }
+
+    /// The netmask of the prefix, all ones for a bare address.
+    fn mask(&self) -> u128 {
+        let prefix = self.prefix.unwrap_or_else(|| self.width());
+        self.all_ones() & !self.all_ones().checked_shr(prefix).unwrap_or(0)
+    }
+
+    /// The same kind of address with other bits and prefix.
+    fn with(&self, bits: u128, prefix: Option<u32>) -> Self {
+        Self {
+            bits,
+            v6: self.v6,
+            prefix,
+        }
+    }
+
+    fn first(&self) -> u128 {
+        self.bits & self.mask()
+    }
+
+    fn last(&self) -> u128 {
+        self.first() | (self.all_ones() & !self.mask())
+    }
+
+    /// The address `delta` further on, keeping the prefix length.
+    fn offset(&self, delta: &IBig) -> Result<Self, BcError> {
+        u128::try_from(IBig::from(self.bits) + delta)
+            .ok()
+            .filter(|bits| *bits <= self.all_ones())
+            .map(|bits| self.with(bits, self.prefix))
+            .ok_or_else(|| BcError::Error("Address out of range".to_string()))
+    }
+
+    fn kind(&self) -> &'static str {
+        if self.v6 {
+            "an IPv6 address"
+        } else {
+            "an IPv4 address"
+        }
+    }
+}
+
+impl fmt::Display for IpAddress {
+    /// Dotted quads for IPv4, the shortest colon form for IPv6.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.v6 {
+            write!(f, "{}", Ipv6Addr::from(self.bits))?;
+        } else {
+            let bits = u32::try_from(self.bits).unwrap_or(u32::MAX);
+            write!(f, "{}", Ipv4Addr::from(bits))?;
+        }
+        match self.prefix {
+            Some(prefix) => write!(f, "/{prefix}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// What the address evaluator works on: addresses and the plain numbers
+/// that offset them.
+#[derive(Clone, Debug)]
+enum NetValue {
+    Plain(Decimal),
+    Address(IpAddress),
+}
+
+impl NetValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            NetValue::Plain(_) => "a number",
+            NetValue::Address(address) => address.kind(),
+        }
+    }
+
+    /// The value as a bc number, the way variables hold it.
+    fn number(self) -> BcNumber {
+        match self {
+            NetValue::Plain(value) => BcNumber::natural(value),
+            NetValue::Address(address) => BcNumber::address(address),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        match self {
+            NetValue::Plain(value) => value.is_zero(),
+            NetValue::Address(address) => address.bits == 0,
+        }
+    }
+}
+
+impl super::BcExecuter {
+    /// Whether `expr` has an IP address literal or reads a variable holding
+    /// one, and so has to go through `eval_network_expression`.
+    pub(super) fn mentions_network(&self, expr: &str) -> bool {
+        // `::` is an address without a single digit.
+        if !expr.contains(|c: char| c.is_ascii_digit() || c.is_alphabetic() || c == ':') {
+            return false;
+        }
+        if PosixParser::mentions_addresses(expr, self.runtime.ibase()) {
+            return true;
+        }
+        expr.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|word| !word.is_empty())
+            .any(|word| self.runtime.holds(word, |number| number.address.is_some()))
+    }
+
+    /// Evaluates `expr` on IPv4 and IPv6 addresses and networks.
+    pub(super) fn eval_network_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let tree = PosixParser::parse_expression(expr, self.runtime.ibase())?;
+        Ok(self.eval_network_node(&tree)?.number())
+    }
+
+    fn eval_network_node(&mut self, node: &PosixExpr) -> Result<NetValue, BcError> {
+        match node {
+            PosixExpr::Number(number) => self.number_network(number),
+            PosixExpr::Address(address) => Ok(NetValue::Address(address.clone())),
+            PosixExpr::Quantity(..) => Err(BcError::Error(
+                "Units cannot be combined with IP addresses".to_string(),
+            )),
+            PosixExpr::Time(_) => Err(BcError::Error(
+                "Dates and durations cannot be combined with IP addresses".to_string(),
+            )),
+            node @ (PosixExpr::Element(..) | PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                let number = self.eval_array_node(node, |exec, node| {
+                    exec.eval_network_node(node).map(NetValue::number)
+                })?;
+                self.number_network(&number)
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => self.number_network(&number),
+                None => self.eval_network_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_network_node(arg)?);
+                }
+                self.eval_network_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => match self.eval_network_node(operand)? {
+                NetValue::Plain(value) => Ok(NetValue::Plain(-value)),
+                NetValue::Address(_) => Err(BcError::Error("Cannot negate an address".to_string())),
+            },
+            PosixExpr::Not(operand) => {
+                let value = self.eval_network_node(operand)?;
+                Ok(Self::network_bool(value.is_zero()))
+            }
+            node @ (PosixExpr::Complement(_) | PosixExpr::Bitwise(..)) => {
+                let number = self.eval_bitwise_node(node, |exec, node| {
+                    exec.eval_network_node(node).map(NetValue::number)
+                })?;
+                self.number_network(&number)
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_network_node(lhs)?.is_zero() {
+                    return Ok(Self::network_bool(false));
+                }
+                let right = self.eval_network_node(rhs)?;
+                Ok(Self::network_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_network_node(lhs)?.is_zero() {
+                    return Ok(Self::network_bool(true));
+                }
+                let right = self.eval_network_node(rhs)?;
+                Ok(Self::network_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let left = self.eval_network_node(lhs)?;
+                let right = self.eval_network_node(rhs)?;
+                self.network_binary(*op, left, right)
+            }
+        }
+    }
+
+    fn network_binary(
+        &self,
+        op: PosixOp,
+        left: NetValue,
+        right: NetValue,
+    ) -> Result<NetValue, BcError> {
+        use NetValue::{Address, Plain};
+
+        let mismatch = |verb: &str| {
+            BcError::Error(format!(
+                "Cannot {verb} {} and {}",
+                left.kind(),
+                right.kind()
+            ))
+        };
+        match (op, &left, &right) {
+            (PosixOp::Add, Address(address), Plain(delta))
+            | (PosixOp::Add, Plain(delta), Address(address)) => {
+                Ok(Address(address.offset(&offset_arg(delta)?)?))
+            }
+            (PosixOp::Sub, Address(address), Plain(delta)) => {
+                Ok(Address(address.offset(&-offset_arg(delta)?)?))
+            }
+            (PosixOp::Sub, Address(a), Address(b)) if a.v6 == b.v6 => {
+                let difference = IBig::from(a.bits) - IBig::from(b.bits);
+                Ok(Plain(Decimal::from(difference)))
+            }
+            (_, Plain(a), Plain(b)) => {
+                if matches!(op, PosixOp::Div | PosixOp::Mod) && b.is_zero() {
+                    return Err(BcError::Error("Division by zero".to_string()));
+                }
+                let value = match op {
+                    PosixOp::Add => a.clone() + b,
+                    PosixOp::Sub => a.clone() - b,
+                    PosixOp::Mul => a.clone() * b,
+                    PosixOp::Div => a.clone() / b,
+                    PosixOp::Mod => a.clone() % b,
+                    PosixOp::Pow => self.power_decimal(a, b)?,
+                    _ => return Ok(Self::network_compare(op, a.cmp(b))),
+                };
+                Ok(Plain(value))
+            }
+            (PosixOp::Add, ..) => Err(mismatch("add")),
+            (PosixOp::Sub, ..) => Err(mismatch("subtract")),
+            (PosixOp::Mul, ..) => Err(mismatch("multiply")),
+            (PosixOp::Div | PosixOp::Mod, ..) => Err(mismatch("divide")),
+            (PosixOp::Pow, ..) => Err(mismatch("raise")),
+            (_, Address(a), Address(b)) if a.v6 == b.v6 => match op {
+                PosixOp::Eq => Ok(Self::network_bool(a == b)),
+                PosixOp::Ne => Ok(Self::network_bool(a != b)),
+                _ => Ok(Self::network_compare(op, a.bits.cmp(&b.bits))),
+            },
+            _ => Err(mismatch("compare")),
+        }
+    }
+
+    fn network_compare(op: PosixOp, ordering: std::cmp::Ordering) -> NetValue {
+        Self::network_bool(match op {
+            PosixOp::Lt => ordering.is_lt(),
+            PosixOp::Le => ordering.is_le(),
+            PosixOp::Gt => ordering.is_gt(),
+            PosixOp::Ge => ordering.is_ge(),
+            PosixOp::Eq => ordering.is_eq(),
+            PosixOp::Ne => ordering.is_ne(),
+            _ => unreachable!("arithmetic handled by the caller"),
+        })
+    }
+
+    /// The address builtins; every other builtin needs plain numbers, while
+    /// user functions get addresses as they are.
+    fn eval_network_call(&mut self, name: &str, args: Vec<NetValue>) -> Result<NetValue, BcError> {
+        use NetValue::{Address, Plain};
+
+        let wrong_args = || BcError::Error(format!("Wrong arguments to {name}()"));
+        let needs_prefix =
+            || BcError::Error(format!("{name}() needs a network such as 192.168.0.0/24"));
+        match (name, args.as_slice()) {
+            ("network" | "broadcast" | "netmask" | "prefix" | "hosts", [Address(address)]) => {
+                let prefix = address.prefix.ok_or_else(needs_prefix)?;
+                return match name {
+                    "network" => Ok(Address(address.with(address.first(), Some(prefix)))),
+                    "broadcast" if address.v6 => Err(BcError::Error(
+                        "IPv6 networks have no broadcast address".to_string(),
+                    )),
+                    "broadcast" => Ok(Address(address.with(address.last(), None))),
+                    "netmask" => Ok(Address(address.with(address.mask(), None))),
+                    "prefix" => Ok(Plain(Decimal::from(prefix))),
+                    _ => {
+                        let host_bits = address.width() - prefix;
+                        let count = IBig::ONE << host_bits as usize;
+                        let usable = if address.v6 || host_bits <= 1 {
+                            count
+                        } else {
+                            count - IBig::from(2)
+                        };
+                        Ok(Plain(Decimal::from(usable)))
+                    }
+                };
+            }
+            ("contains", [Address(network), Address(member)]) => {
+                network.prefix.ok_or_else(needs_prefix)?;
+                let inside = network.v6 == member.v6
+                    && network.first() <= member.first()
+                    && member.last() <= network.last();
+                return Ok(Self::network_bool(inside));
+            }
+            ("subnets", [Address(network), Plain(length)]) => {
+                let (prefix, length) = Self::subnet_length(network, length, needs_prefix)?;
+                let count = IBig::ONE << (length - prefix) as usize;
+                return Ok(Plain(Decimal::from(count)));
+            }
+            ("subnet", [Address(network), Plain(length), Plain(index)]) => {
+                let (prefix, length) = Self::subnet_length(network, length, needs_prefix)?;
+                let index = integer_arg(name, index)?;
+                let count = IBig::ONE << (length - prefix) as usize;
+                if index < IBig::ZERO || index >= count {
+                    return Err(BcError::Error(format!(
+                        "subnet() index must be between 0 and {}",
+                        count - IBig::ONE
+                    )));
+                }
+                let size = IBig::ONE << (network.width() - length) as usize;
+                let start = network.with(network.first(), Some(length));
+                return Ok(Address(start.offset(&(index * size))?));
+            }
+            (
+                "network" | "broadcast" | "netmask" | "prefix" | "hosts" | "contains" | "subnets"
+                | "subnet",
+                _,
+            ) => return Err(wrong_args()),
+            _ => {}
+        }
+
+        if args.iter().all(|arg| matches!(arg, Plain(_))) {
+            let values: Vec<Decimal> = args
+                .iter()
+                .filter_map(|arg| match arg {
+                    Plain(value) => Some(value.clone()),
+                    Address(_) => None,
+                })
+                .collect();
+            if let Some(result) = self.call_builtin_function(name, &values) {
+                return Ok(Plain(result?));
+            }
+        } else if self.runtime.get_function(name).is_none() {
+            return Err(BcError::Error(format!(
+                "{name}() needs plain numbers, not addresses"
+            )));
+        }
+
+        let numbers = args.into_iter().map(NetValue::number).collect();
+        if let Some(result) = self.call_function(name, numbers)? {
+            return self.number_network(&result);
+        }
+
+        Err(BcError::Error(format!("Undefined identifier: {name}")))
+    }
+
+    /// The prefix of `network` and the longer prefix `length` that splits it.
+    fn subnet_length(
+        network: &IpAddress,
+        length: &Decimal,
+        needs_prefix: impl Fn() -> BcError,
+    ) -> Result<(u32, u32), BcError> {
+        let prefix = network.prefix.ok_or_else(needs_prefix)?;
+        let length = integer_arg("subnet", length)?;
+        u32::try_from(length)
+            .ok()
+            .filter(|length| (prefix..=network.width()).contains(length))
+            .map(|length| (prefix, length))
+            .ok_or_else(|| {
+                BcError::Error(format!(
+                    "Subnet prefix length must be between {prefix} and {}",
+                    network.width()
+                ))
+            })
+    }
+
+    fn number_network(&self, number: &BcNumber) -> Result<NetValue, BcError> {
+        if number.unit.is_some() {
+            return Err(BcError::Error(
+                "Units cannot be combined with IP addresses".to_string(),
+            ));
+        }
+        if number.time.is_some() {
+            return Err(BcError::Error(
+                "Dates and durations cannot be combined with IP addresses".to_string(),
+            ));
+        }
+        Ok(match &number.address {
+            Some(address) => NetValue::Address(address.clone()),
+            None => NetValue::Plain(self.promote_precision(number.value.clone())),
+        })
+    }
+
+    fn network_bool(value: bool) -> NetValue {
+        NetValue::Plain(if value { Decimal::ONE } else { Decimal::ZERO })
+    }
+}
+
+fn offset_arg(delta: &Decimal) -> Result<IBig, BcError> {
+    if !delta.fract().is_zero() {
+        return Err(BcError::Error(
+            "Address offsets must be integers".to_string(),
+        ));
+    }
+    integer_arg("offset", delta)
+}
@@ -0,0 +1,362 @@
+use std::cmp;
+use std::convert::TryFrom;
+
+use dashu::integer::IBig;
+use dashu::rational::RBig;
+use dashu::Decimal;
+use num_traits::{ToPrimitive, Zero};
+
+use super::datetime::Temporal;
+use super::error::BcError;
+use super::interval::Interval;
+use super::literals::LiteralTable;
+use super::mathlib;
+use super::network::IpAddress;
+use super::uncertain::Uncertain;
+use super::units::Unit;
+
+/// A value together with its bc scale: the number of fractional digits bc
+/// keeps for it, trailing zeros included (`Decimal` normalises those away).
+/// Values computed in rational mode also keep the exact fraction they stand
+/// for, those computed in interval mode the bounds around them, measured
+/// (`±`) values their uncertainty, quantities the unit they are in, dates
+/// and durations what they stand for (`value` counts their seconds), and
+/// IP addresses the address (`value` is the address as an integer).
+#[derive(Clone, Debug)]
+pub struct BcNumber {
+    pub value: Decimal,
+    pub scale: u32,
+    pub exact: Option<RBig>,
+    pub bounds: Option<Interval>,
+    pub uncertainty: Option<Uncertain>,
+    pub unit: Option<Unit>,
+    pub time: Option<Temporal>,
+    pub address: Option<IpAddress>,
+}
+
+impl BcNumber {
+    pub fn new(value: Decimal, scale: u32) -> Self {
+        Self {
+            value,
+            scale,
+            exact: None,
+            bounds: None,
+            uncertainty: None,
+            unit: None,
+            time: None,
+            address: None,
+        }
+    }
+
+    /// Pairs an exact fraction with its decimal approximation `value`.
+    pub fn rational(value: Decimal, exact: RBig) -> Self {
+        Self {
+            exact: Some(exact),
+            ..Self::natural(value)
+        }
+    }
+
+    /// Wraps a value whose scale is just the fractional digits it carries.
+    pub fn natural(value: Decimal) -> Self {
+        let exponent = value.repr().exponent();
+        let scale = if exponent < 0 {
+            u32::try_from(-exponent).unwrap_or(u32::MAX)
+        } else {
+            0
+        };
+        Self::new(value, scale)
+    }
+
+    /// Wraps interval bounds, with their midpoint as the value.
+    pub fn interval(bounds: Interval) -> Self {
+        Self {
+            bounds: Some(bounds.clone()),
+            ..Self::natural(bounds.midpoint())
+        }
+    }
+
+    /// Wraps a measured value; exact results come back as plain numbers.
+    pub fn uncertain(measured: Uncertain) -> Self {
+        if measured.is_exact() {
+            return Self::natural(measured.value);
+        }
+        Self {
+            uncertainty: Some(measured.clone()),
+            ..Self::natural(measured.value)
+        }
+    }
+
+    /// Wraps a date or duration, with its seconds as the value.
+    pub fn temporal(time: Temporal) -> Self {
+        Self {
+            time: Some(time.clone()),
+            ..Self::natural(time.seconds().clone())
+        }
+    }
+
+    /// Wraps an IP address, with its bits as the value.
+    pub fn address(address: IpAddress) -> Self {
+        Self {
+            address: Some(address.clone()),
+            ..Self::new(Decimal::from(address.bits), 0)
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(Decimal::ZERO, 0)
+    }
+
+    pub fn from_bool(value: bool) -> Self {
+        Self::new(if value { Decimal::ONE } else { Decimal::ZERO }, 0)
+    }
+
+    /// Keeps exactly `scale` fractional digits, truncating or zero-padding.
+    pub fn at_scale(value: Decimal, scale: u32) -> Self {
+        let truncated = Self::natural(value).truncate(scale);
+        Self::new(truncated.value, scale)
+    }
+
+    /// Parses a plain bc numeral, keeping the scale exactly as written.
+    pub fn parse_literal(literal: &str) -> Option<Self> {
+        let (negative, body) = match literal.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, literal.strip_prefix('+').unwrap_or(literal)),
+        };
+        let (integer, fraction) = body.split_once('.').unwrap_or((body, ""));
+        let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if integer.len() + fraction.len() == 0 || !all_digits(integer) || !all_digits(fraction) {
+            return None;
+        }
+        let mut units: IBig = format!("{integer}{fraction}").parse().ok()?;
+        if negative {
+            units = -units;
+        }
+        Some(Self::from_units(units, fraction.len() as u32))
+    }
+
+    /// Parses `1.5e-9` or an SI-suffixed `4.7k` exactly, keeping as many
+    /// fractional digits as the value needs.
+    pub fn parse_scientific(literal: &str) -> Option<Self> {
+        let (mantissa, exponent) = LiteralTable::split_scientific(literal)?;
+        let mantissa = Self::parse_literal(mantissa)?;
+        let shift = exponent - i64::from(mantissa.scale);
+        let scale = u32::try_from((-shift).max(0)).ok()?;
+        let units = mantissa.units(mantissa.scale);
+        Some(Self::new(Decimal::from_parts(units, shift as isize), scale))
+    }
+
+    /// Parses a `value±sigma` (or `value+/-sigma`) measurement literal.
+    pub fn parse_measurement(literal: &str, ibase: u32) -> Result<Self, BcError> {
+        let (value, sigma) = LiteralTable::split_measurement(literal)
+            .ok_or_else(|| BcError::Error(format!("Failed to parse literal: {literal}")))?;
+        let value = Self::parse_in_base(value, ibase)?;
+        let sigma = Self::parse_in_base(sigma, ibase)?;
+        Ok(Self {
+            uncertainty: Some(Uncertain::measurement(value.value.clone(), sigma.value)),
+            ..value
+        })
+    }
+
+    /// Parses a `0x`/`0b`/`0o` literal, or a bc numeral read in `ibase`
+    /// (uppercase digits; a lone digit keeps its face value, so `ibase=A`
+    /// always restores decimal input).
+    pub fn parse_in_base(literal: &str, ibase: u32) -> Result<Self, BcError> {
+        let invalid = || BcError::Error(format!("Failed to parse literal: {literal}"));
+        if let Some((radix, digits)) = split_radix_prefix(literal) {
+            let units = digits
+                .chars()
+                .try_fold(IBig::ZERO, |acc, ch| {
+                    ch.to_digit(radix).map(|digit| acc * radix + digit)
+                })
+                .ok_or_else(invalid)?;
+            return Ok(Self::from_units(units, 0));
+        }
+        if ibase == 10 {
+            return Self::parse_literal(literal)
+                .or_else(|| Self::parse_scientific(literal))
+                .ok_or_else(invalid);
+        }
+
+        let (integer, fraction) = literal.split_once('.').unwrap_or((literal, ""));
+        let face_value = fraction.is_empty() && integer.len() == 1;
+        let mut digits = Vec::with_capacity(integer.len() + fraction.len());
+        for ch in integer.chars().chain(fraction.chars()) {
+            let digit = match ch {
+                '0'..='9' | 'A'..='Z' => ch.to_digit(36).unwrap_or_default(),
+                _ => return Err(invalid()),
+            };
+            if digit >= ibase && !face_value {
+                return Err(BcError::Error(format!(
+                    "Invalid digit '{ch}' for ibase {ibase}"
+                )));
+            }
+            digits.push(digit);
+        }
+        if digits.is_empty() {
+            return Err(invalid());
+        }
+        let accumulate = |part: &[u32]| {
+            part.iter()
+                .fold(IBig::ZERO, |acc, digit| acc * ibase + *digit)
+        };
+        let (int_digits, frac_digits) = digits.split_at(integer.len());
+        let scale = frac_digits.len() as u32;
+        let fraction_units =
+            accumulate(frac_digits) * pow10(scale) / IBig::from(ibase).pow(frac_digits.len());
+        let units = accumulate(int_digits) * pow10(scale) + fraction_units;
+        Ok(Self::from_units(units, scale))
+    }
+
+    /// The exact fraction, read off the decimal value when none was kept.
+    pub fn to_rational(&self) -> RBig {
+        match &self.exact {
+            Some(exact) => exact.clone(),
+            None => RBig::try_from(self.value.clone()).unwrap_or(RBig::ZERO),
+        }
+    }
+
+    /// The interval bounds, a single point when none were kept.
+    pub fn to_interval(&self) -> Interval {
+        match &self.bounds {
+            Some(bounds) => bounds.clone(),
+            None => Interval::point(self.value.clone()),
+        }
+    }
+
+    /// The measured value, with no uncertainty when none was kept.
+    pub fn to_uncertain(&self) -> Uncertain {
+        match &self.uncertainty {
+            Some(measured) => measured.clone(),
+            None => Uncertain::exact(self.value.clone()),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    pub fn negate(&self) -> Self {
+        Self {
+            value: -self.value.clone(),
+            scale: self.scale,
+            exact: self.exact.as_ref().map(|exact| -exact),
+            bounds: self.bounds.as_ref().map(Interval::negate),
+            uncertainty: self.uncertainty.as_ref().map(Uncertain::negate),
+            unit: self.unit.clone(),
+            time: self.time.as_ref().and_then(Temporal::negate),
+            address: None,
+        }
+    }
+
+    /// Drops digits beyond `scale`; never widens the current scale.
+    pub fn truncate(&self, scale: u32) -> Self {
+        if scale >= self.scale {
+            return self.clone();
+        }
+        Self::from_units(self.units(scale), scale)
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let scale = cmp::max(self.scale, other.scale);
+        Self::from_units(self.units(scale) + other.units(scale), scale)
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        let scale = cmp::max(self.scale, other.scale);
+        Self::from_units(self.units(scale) - other.units(scale), scale)
+    }
+
+    /// `min(a+b, max(scale, a, b))` fractional digits, truncated.
+    pub fn mul(&self, other: &Self, scale: u32) -> Self {
+        let full = self.scale.saturating_add(other.scale);
+        let product = Self::from_units(self.units(self.scale) * other.units(other.scale), full);
+        product.truncate(cmp::min(full, scale.max(self.scale).max(other.scale)))
+    }
+
+    /// Quotient truncated to exactly `scale` fractional digits.
+    pub fn div(&self, other: &Self, scale: u32) -> Result<Self, BcError> {
+        if other.is_zero() {
+            return Err(BcError::Error("Division by zero".to_string()));
+        }
+        Ok(Self::from_units(self.quotient_units(other, scale), scale))
+    }
+
+    /// `a - (a / b) * b`, where the quotient is truncated to `scale` digits.
+    pub fn rem(&self, other: &Self, scale: u32) -> Result<Self, BcError> {
+        if other.is_zero() {
+            return Err(BcError::Error("Modulo by zero".to_string()));
+        }
+        let quotient = self.quotient_units(other, scale);
+        let result_scale = cmp::max(scale.saturating_add(other.scale), self.scale);
+        let product_shift = result_scale - scale - other.scale;
+        let remainder =
+            self.units(result_scale) - quotient * other.units(other.scale) * pow10(product_shift);
+        Ok(Self::from_units(remainder, result_scale))
+    }
+
+    /// Integer powers only, as in bc: `min(a*n, max(scale, a))` digits for
+    /// positive exponents and `scale` digits for negative ones.
+    pub fn pow(&self, exponent: &Self, scale: u32) -> Result<Self, BcError> {
+        if !exponent.value.fract().is_zero() {
+            return Err(BcError::Error("Non-zero scale in exponent".to_string()));
+        }
+        let power = ToPrimitive::to_i64(&exponent.value.trunc())
+            .ok_or_else(|| BcError::Error("Exponent out of supported range".to_string()))?;
+        let magnitude = u32::try_from(power.unsigned_abs())
+            .map_err(|_| BcError::Error("Exponent out of supported range".to_string()))?;
+        let full_scale = self.scale.saturating_mul(magnitude);
+        let full_units = self.units(self.scale).pow(magnitude as usize);
+        if power >= 0 {
+            let full = Self::from_units(full_units, full_scale);
+            return Ok(full.truncate(cmp::min(full_scale, scale.max(self.scale))));
+        }
+        if full_units.is_zero() {
+            return Err(BcError::Error("Division by zero".to_string()));
+        }
+        let numerator = pow10(scale.saturating_add(full_scale));
+        Ok(Self::from_units(numerator / full_units, scale))
+    }
+
+    /// The value scaled by `10^scale` as an integer, truncated toward zero.
+    pub fn units(&self, scale: u32) -> IBig {
+        let repr = self.value.repr();
+        let shift = repr.exponent() + scale as isize;
+        if shift >= 0 {
+            repr.significand() * pow10(shift as u32)
+        } else {
+            repr.significand() / pow10(shift.unsigned_abs() as u32)
+        }
+    }
+
+    fn quotient_units(&self, other: &Self, scale: u32) -> IBig {
+        let numerator = self.units(self.scale) * pow10(other.scale.saturating_add(scale));
+        let denominator = other.units(other.scale) * pow10(self.scale);
+        numerator / denominator
+    }
+
+    fn from_units(units: IBig, scale: u32) -> Self {
+        Self::new(Decimal::from_parts(units, -(scale as isize)), scale)
+    }
+}
+
+/// Splits a C-style `0x`/`0b`/`0o` prefix off `literal`, returning the radix.
+pub(crate) fn split_radix_prefix(literal: &str) -> Option<(u32, &str)> {
+    let radix = match literal.get(..2)? {
+        "0x" => 16,
+        "0b" => 2,
+        "0o" => 8,
+        _ => return None,
+    };
+    Some((radix, &literal[2..]))
+}
+
+/// `value` at `precision` significant digits, for the constants of the
+/// series in special.rs and distributions.rs: `Decimal::from(1)` alone
+/// would round whatever it meets to a single digit.
+pub(super) fn number(value: i64, precision: usize) -> Decimal {
+    mathlib::at_precision(&Decimal::from(value), precision)
+}
+
+fn pow10(exponent: u32) -> IBig {
+    IBig::from(10).pow(exponent as usize)
+}
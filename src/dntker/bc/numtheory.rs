@@ -0,0 +1,425 @@
+use std::convert::TryFrom;
+
+use dashu::base::{BitTest, Gcd, Sign, UnsignedAbs};
+use dashu::integer::fast_div::ConstDivisor;
+use dashu::integer::modular::Reduced;
+use dashu::integer::{IBig, UBig};
+use dashu::Decimal;
+
+use super::error::BcError;
+
+/// The integer builtins, whose results are exact.
+pub(super) const NUMBER_THEORY: [&str; 11] = [
+    "gcd",
+    "lcm",
+    "factorial",
+    "binomial",
+    "nCr",
+    "nPr",
+    "modpow",
+    "modinv",
+    "isprime",
+    "nextprime",
+    "totient",
+];
+
+/// Largest argument `factorial()` accepts, and the most factors `binomial()`
+/// and `nPr()` multiply together.
+const MAX_FACTORIAL_ARGUMENT: usize = 100_000;
+
+/// Primes used both for trial division and as Miller–Rabin witnesses. With
+/// the first thirteen as witnesses the test is exact below 3.3·10^24.
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Trial division runs up to this bound before `factor()` switches to
+/// Pollard's rho.
+const TRIAL_DIVISION_LIMIT: u32 = 10_000;
+
+impl super::BcExecuter {
+    /// `factor(n)` prints a factorisation such as `2^3 * 3^2 * 5`, so it is
+    /// only understood as a statement of its own.
+    pub(super) fn try_eval_factorization(
+        &mut self,
+        statement: &str,
+    ) -> Result<Option<String>, BcError> {
+        let inner = match Self::strip_wrapped_function(statement, "factor")? {
+            Some(inner) => inner,
+            None => return Ok(None),
+        };
+        let value = self.eval_expression(inner)?;
+        let n = integer_arg("factor", &value)?;
+        if n == IBig::ZERO {
+            return Err(BcError::Error(
+                "factor() expects a non-zero integer".to_string(),
+            ));
+        }
+
+        let mut parts = Vec::new();
+        if n.sign() == Sign::Negative {
+            parts.push("-1".to_string());
+        }
+        for (prime, power) in factorize(&n.unsigned_abs()) {
+            if power == 1 {
+                parts.push(prime.to_string());
+            } else {
+                parts.push(format!("{prime}^{power}"));
+            }
+        }
+        if parts.is_empty() {
+            parts.push("1".to_string());
+        }
+        Ok(Some(parts.join(" * ")))
+    }
+}
+
+pub(super) fn gcd(args: &[Decimal]) -> Result<Decimal, BcError> {
+    let values = integer_args("gcd", args)?;
+    let result = values.iter().fold(UBig::ZERO, |acc, value| {
+        gcd_ubig(&acc, &value.unsigned_abs())
+    });
+    Ok(Decimal::from(IBig::from(result)))
+}
+
+pub(super) fn lcm(args: &[Decimal]) -> Result<Decimal, BcError> {
+    let values = integer_args("lcm", args)?;
+    let mut result = UBig::ONE;
+    for value in values {
+        let value = value.unsigned_abs();
+        if value == UBig::ZERO {
+            return Ok(Decimal::ZERO);
+        }
+        let common = gcd_ubig(&result, &value);
+        result = result / common * value;
+    }
+    Ok(Decimal::from(IBig::from(result)))
+}
+
+pub(super) fn factorial(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("factorial", args, 1)?;
+    let n = count_arg("factorial", &args[0])?;
+    Ok(Decimal::from(IBig::from(product(&UBig::ONE, n))))
+}
+
+/// `binomial(n, k)`; a negative `n` follows `C(n, k) = (-1)^k C(k - n - 1, k)`.
+pub(super) fn binomial(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("binomial", args, 2)?;
+    let n = integer_arg("binomial", &args[0])?;
+    let k = integer_arg("binomial", &args[1])?;
+    if k.sign() == Sign::Negative {
+        return Ok(Decimal::ZERO);
+    }
+    let (top, negate) = if n.sign() == Sign::Negative {
+        (&k - &n - IBig::ONE, k.bit(0))
+    } else {
+        (n, false)
+    };
+    if k > top {
+        return Ok(Decimal::ZERO);
+    }
+    let k = (&top - &k).min(k);
+    let count = factor_count("binomial", &k)?;
+    let low = (&top - &k + IBig::ONE).unsigned_abs();
+    let value = IBig::from(product(&low, count) / product(&UBig::ONE, count));
+    Ok(Decimal::from(if negate { -value } else { value }))
+}
+
+/// `nPr(n, k)`, the number of ordered selections of `k` out of `n`.
+pub(super) fn permutations(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("nPr", args, 2)?;
+    let n = integer_arg("nPr", &args[0])?;
+    let k = integer_arg("nPr", &args[1])?;
+    if n.sign() == Sign::Negative {
+        return Err(BcError::Error("nPr() expects n >= 0".to_string()));
+    }
+    if k.sign() == Sign::Negative || k > n {
+        return Ok(Decimal::ZERO);
+    }
+    let count = factor_count("nPr", &k)?;
+    let low = (&n - &k + IBig::ONE).unsigned_abs();
+    Ok(Decimal::from(IBig::from(product(&low, count))))
+}
+
+/// `modpow(b, e, m)`; a negative exponent raises the inverse of `b`.
+pub(super) fn modpow(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("modpow", args, 3)?;
+    let base = integer_arg("modpow", &args[0])?;
+    let exponent = integer_arg("modpow", &args[1])?;
+    let ring = modulus_arg("modpow", &args[2])?;
+    let mut reduced = ring.reduce(base);
+    if exponent.sign() == Sign::Negative {
+        reduced = reduced.inv().ok_or_else(|| {
+            BcError::Error("modpow() base has no inverse for a negative exponent".to_string())
+        })?;
+    }
+    let residue = reduced.pow(&exponent.unsigned_abs()).residue();
+    Ok(Decimal::from(IBig::from(residue)))
+}
+
+pub(super) fn modinv(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("modinv", args, 2)?;
+    let value = integer_arg("modinv", &args[0])?;
+    let ring = modulus_arg("modinv", &args[1])?;
+    let inverse = ring
+        .reduce(value)
+        .inv()
+        .ok_or_else(|| BcError::Error("modinv() arguments are not coprime".to_string()))?;
+    Ok(Decimal::from(IBig::from(inverse.residue())))
+}
+
+pub(super) fn isprime(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("isprime", args, 1)?;
+    let n = integer_arg("isprime", &args[0])?;
+    let prime = n.sign() == Sign::Positive && is_prime(&n.unsigned_abs());
+    Ok(if prime { Decimal::ONE } else { Decimal::ZERO })
+}
+
+/// The smallest prime strictly greater than the argument.
+pub(super) fn nextprime(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("nextprime", args, 1)?;
+    let n = integer_arg("nextprime", &args[0])?;
+    if n < IBig::from(2) {
+        return Ok(Decimal::from(2));
+    }
+    let mut candidate = n.unsigned_abs() + UBig::ONE;
+    if !candidate.bit(0) && candidate != UBig::from(2u8) {
+        candidate += UBig::ONE;
+    }
+    while !is_prime(&candidate) {
+        candidate += UBig::from(2u8);
+    }
+    Ok(Decimal::from(IBig::from(candidate)))
+}
+
+/// Euler's totient, from the prime factorisation of the argument.
+pub(super) fn totient(args: &[Decimal]) -> Result<Decimal, BcError> {
+    expect_args("totient", args, 1)?;
+    let n = integer_arg("totient", &args[0])?;
+    if n.sign() != Sign::Positive || n == IBig::ZERO {
+        return Err(BcError::Error(
+            "totient() expects a positive integer".to_string(),
+        ));
+    }
+    let n = n.unsigned_abs();
+    let mut result = n.clone();
+    for (prime, _) in factorize(&n) {
+        result = result / &prime * (prime - UBig::ONE);
+    }
+    Ok(Decimal::from(IBig::from(result)))
+}
+
+pub(super) fn expect_args(name: &str, args: &[Decimal], count: usize) -> Result<(), BcError> {
+    if args.len() != count {
+        let plural = if count == 1 { "argument" } else { "arguments" };
+        return Err(BcError::Error(format!(
+            "{}() expects {} {}, got {}",
+            name,
+            count,
+            plural,
+            args.len()
+        )));
+    }
+    Ok(())
+}
+
+fn integer_args(name: &str, args: &[Decimal]) -> Result<Vec<IBig>, BcError> {
+    if args.len() < 2 {
+        return Err(BcError::Error(format!(
+            "{name}() expects at least 2 arguments"
+        )));
+    }
+    args.iter().map(|arg| integer_arg(name, arg)).collect()
+}
+
+pub(super) fn integer_arg(name: &str, value: &Decimal) -> Result<IBig, BcError> {
+    if value.trunc() != *value {
+        return Err(BcError::Error(format!(
+            "{name}() expects integer arguments"
+        )));
+    }
+    let repr = value.repr();
+    let exponent = repr.exponent();
+    let ten = IBig::from(10);
+    if exponent >= 0 {
+        Ok(repr.significand() * ten.pow(exponent as usize))
+    } else {
+        Ok(repr.significand() / ten.pow(exponent.unsigned_abs()))
+    }
+}
+
+fn count_arg(name: &str, value: &Decimal) -> Result<usize, BcError> {
+    let n = integer_arg(name, value)?;
+    if n.sign() == Sign::Negative {
+        return Err(BcError::Error(format!(
+            "{name}() expects a non-negative integer"
+        )));
+    }
+    factor_count(name, &n)
+}
+
+fn factor_count(name: &str, n: &IBig) -> Result<usize, BcError> {
+    usize::try_from(n)
+        .ok()
+        .filter(|count| *count <= MAX_FACTORIAL_ARGUMENT)
+        .ok_or_else(|| BcError::Error(format!("{name}() argument is too large")))
+}
+
+fn modulus_arg(name: &str, value: &Decimal) -> Result<ConstDivisor, BcError> {
+    let m = integer_arg(name, value)?;
+    if m.sign() == Sign::Negative || m == IBig::ZERO {
+        return Err(BcError::Error(format!(
+            "{name}() expects a positive modulus"
+        )));
+    }
+    Ok(ConstDivisor::new(m.unsigned_abs()))
+}
+
+fn gcd_ubig(a: &UBig, b: &UBig) -> UBig {
+    if *a == UBig::ZERO {
+        return b.clone();
+    }
+    if *b == UBig::ZERO {
+        return a.clone();
+    }
+    a.gcd(b)
+}
+
+/// `low * (low + 1) * ... * (low + count - 1)`, split in halves so the
+/// multiplications stay balanced.
+fn product(low: &UBig, count: usize) -> UBig {
+    match count {
+        0 => UBig::ONE,
+        1 => low.clone(),
+        _ => {
+            let half = count / 2;
+            product(low, half) * product(&(low + UBig::from(half)), count - half)
+        }
+    }
+}
+
+fn is_prime(n: &UBig) -> bool {
+    if *n < UBig::from(2u8) {
+        return false;
+    }
+    for &prime in &SMALL_PRIMES {
+        let prime = UBig::from(prime);
+        if *n == prime {
+            return true;
+        }
+        if n % &prime == UBig::ZERO {
+            return false;
+        }
+    }
+
+    let below = n - UBig::ONE;
+    let shift = below.trailing_zeros().unwrap_or(0);
+    let odd = &below >> shift;
+    let ring = ConstDivisor::new(n.clone());
+    let one = ring.reduce(UBig::ONE);
+    let minus_one = ring.reduce(below.clone());
+    SMALL_PRIMES.iter().all(|&witness| {
+        let mut x = ring.reduce(UBig::from(witness)).pow(&odd);
+        if x == one || x == minus_one {
+            return true;
+        }
+        for _ in 1..shift {
+            x = &x * &x;
+            if x == minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Prime factors of `n` with their multiplicities, smallest first.
+fn factorize(n: &UBig) -> Vec<(UBig, usize)> {
+    let mut factors: Vec<(UBig, usize)> = Vec::new();
+    let mut rest = n.clone();
+    let mut divisor = 2u32;
+    while divisor <= TRIAL_DIVISION_LIMIT {
+        let candidate = UBig::from(divisor);
+        if &candidate * &candidate > rest {
+            break;
+        }
+        let mut power = 0;
+        while &rest % &candidate == UBig::ZERO {
+            rest /= &candidate;
+            power += 1;
+        }
+        if power > 0 {
+            factors.push((candidate, power));
+        }
+        divisor += if divisor == 2 { 1 } else { 2 };
+    }
+
+    let mut pending = vec![rest];
+    while let Some(value) = pending.pop() {
+        if value == UBig::ONE {
+            continue;
+        }
+        if is_prime(&value) {
+            match factors.iter_mut().find(|(prime, _)| *prime == value) {
+                Some((_, power)) => *power += 1,
+                None => factors.push((value, 1)),
+            }
+            continue;
+        }
+        let divisor = pollard_rho(&value);
+        pending.push(&value / &divisor);
+        pending.push(divisor);
+    }
+    factors.sort();
+    factors
+}
+
+fn rho_step<'a>(x: &Reduced<'a>, c: &Reduced<'a>) -> Reduced<'a> {
+    x * x + c
+}
+
+/// A non-trivial divisor of the odd composite `n`, by Brent's variant of
+/// Pollard's rho.
+fn pollard_rho(n: &UBig) -> UBig {
+    const BATCH: usize = 128;
+    let ring = ConstDivisor::new(n.clone());
+    for offset in 1u32.. {
+        let c = ring.reduce(UBig::from(offset));
+        let mut y = ring.reduce(UBig::from(2u8));
+        let mut x = y.clone();
+        let mut saved = y.clone();
+        let mut product = ring.reduce(UBig::ONE);
+        let mut divisor = UBig::ONE;
+        let mut length = 1usize;
+        while divisor == UBig::ONE {
+            x = y.clone();
+            for _ in 0..length {
+                y = rho_step(&y, &c);
+            }
+            let mut done = 0;
+            while done < length && divisor == UBig::ONE {
+                saved = y.clone();
+                for _ in 0..BATCH.min(length - done) {
+                    y = rho_step(&y, &c);
+                    product *= &x - &y;
+                }
+                divisor = gcd_ubig(&product.residue(), n);
+                done += BATCH;
+            }
+            length *= 2;
+        }
+        if divisor == *n {
+            loop {
+                saved = rho_step(&saved, &c);
+                divisor = gcd_ubig(&(&x - &saved).residue(), n);
+                if divisor != UBig::ONE {
+                    break;
+                }
+            }
+        }
+        if divisor != *n {
+            return divisor;
+        }
+    }
+    unreachable!("some offset splits every composite")
+}
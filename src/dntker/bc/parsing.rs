@@ -22,29 +22,19 @@ impl super::BcExecuter {
             while idx < chars.len() {
                 match chars[idx] {
                     '(' => depth_round += 1,
-                    ')' => {
-                        if depth_round > 0 {
-                            depth_round -= 1;
-                        }
-                    }
+                    ')' if depth_round > 0 => depth_round -= 1,
                     '[' => depth_square += 1,
-                    ']' => {
-                        if depth_square > 0 {
-                            depth_square -= 1;
-                        }
-                    }
+                    ']' if depth_square > 0 => depth_square -= 1,
                     '{' => depth_curly += 1,
-                    '}' => {
-                        if depth_curly > 0 {
-                            depth_curly -= 1;
-                        }
-                    }
-                    'e' | 'E' if depth_round == 0 && depth_square == 0 && depth_curly == 0 => {
-                        if trimmed[idx..].starts_with("else")
-                            && Self::is_keyword_boundary(trimmed, idx, idx + 4)
-                        {
-                            break;
-                        }
+                    '}' if depth_curly > 0 => depth_curly -= 1,
+                    'e' | 'E'
+                        if depth_round == 0
+                            && depth_square == 0
+                            && depth_curly == 0
+                            && trimmed[idx..].starts_with("else")
+                            && Self::is_keyword_boundary(trimmed, idx, idx + 4) =>
+                    {
+                        break;
                     }
                     ';' if depth_round == 0 && depth_square == 0 && depth_curly == 0 => {
                         idx += 1;
@@ -71,28 +61,23 @@ impl super::BcExecuter {
         let mut depth_round = 0;
         let mut depth_square = 0;
         let mut depth_curly = 0;
+        let mut in_string = false;
 
         let mut start = 0;
         for (idx, ch) in input.char_indices() {
+            if ch == '"' {
+                in_string = !in_string;
+            }
+            if in_string {
+                continue;
+            }
             match ch {
                 '(' => depth_round += 1,
-                ')' => {
-                    if depth_round > 0 {
-                        depth_round -= 1;
-                    }
-                }
+                ')' if depth_round > 0 => depth_round -= 1,
                 '[' => depth_square += 1,
-                ']' => {
-                    if depth_square > 0 {
-                        depth_square -= 1;
-                    }
-                }
+                ']' if depth_square > 0 => depth_square -= 1,
                 '{' => depth_curly += 1,
-                '}' => {
-                    if depth_curly > 0 {
-                        depth_curly -= 1;
-                    }
-                }
+                '}' if depth_curly > 0 => depth_curly -= 1,
                 ';' | '\n' if depth_round == 0 && depth_square == 0 && depth_curly == 0 => {
                     let trimmed = input[start..idx].trim();
                     if !trimmed.is_empty() {
@@ -117,30 +102,25 @@ impl super::BcExecuter {
         let mut depth_round = 0;
         let mut depth_square = 0;
         let mut depth_curly = 0;
+        let mut in_string = false;
         let mut prev_char: Option<char> = None;
 
         for (index, ch) in stmt.char_indices() {
+            if ch == '"' {
+                in_string = !in_string;
+            }
+            if in_string {
+                continue;
+            }
             match ch {
                 '(' => depth_round += 1,
-                ')' => {
-                    if depth_round > 0 {
-                        depth_round -= 1;
-                    }
-                }
+                ')' if depth_round > 0 => depth_round -= 1,
                 '[' => depth_square += 1,
-                ']' => {
-                    if depth_square > 0 {
-                        depth_square -= 1;
-                    }
-                }
+                ']' if depth_square > 0 => depth_square -= 1,
                 '{' => depth_curly += 1,
-                '}' => {
-                    if depth_curly > 0 {
-                        depth_curly -= 1;
-                    }
-                }
+                '}' if depth_curly > 0 => depth_curly -= 1,
                 '=' if depth_round == 0 && depth_square == 0 && depth_curly == 0 => {
-                    if matches!(prev_char, Some('<') | Some('>') | Some('!')) {
+                    if matches!(prev_char, Some('<' | '>' | '!' | '=')) {
                         prev_char = Some('=');
                         continue;
                     }
@@ -169,28 +149,23 @@ impl super::BcExecuter {
         let mut depth_round = 0;
         let mut depth_square = 0;
         let mut depth_curly = 0;
+        let mut in_string = false;
 
         let mut start = 0;
         for (idx, ch) in input.char_indices() {
+            if ch == '"' {
+                in_string = !in_string;
+            }
+            if in_string {
+                continue;
+            }
             match ch {
                 '(' => depth_round += 1,
-                ')' => {
-                    if depth_round > 0 {
-                        depth_round -= 1;
-                    }
-                }
+                ')' if depth_round > 0 => depth_round -= 1,
                 '[' => depth_square += 1,
-                ']' => {
-                    if depth_square > 0 {
-                        depth_square -= 1;
-                    }
-                }
+                ']' if depth_square > 0 => depth_square -= 1,
                 '{' => depth_curly += 1,
-                '}' => {
-                    if depth_curly > 0 {
-                        depth_curly -= 1;
-                    }
-                }
+                '}' if depth_curly > 0 => depth_curly -= 1,
                 _ => {}
             }
 
@@ -210,8 +185,9 @@ impl super::BcExecuter {
     }
 
     pub(super) fn lookup_keyword(bytes: &str, expected: &str) -> bool {
-        bytes.len() >= expected.len()
-            && bytes[..expected.len()].eq_ignore_ascii_case(expected)
+        bytes
+            .get(..expected.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(expected))
             && Self::is_keyword_boundary(bytes, 0, expected.len())
     }
 
@@ -237,7 +213,14 @@ impl super::BcExecuter {
         close: char,
     ) -> Result<usize, BcError> {
         let mut depth = 0;
+        let mut in_string = false;
         for (index, ch) in input.char_indices().skip(start) {
+            if ch == '"' {
+                in_string = !in_string;
+            }
+            if in_string {
+                continue;
+            }
             if ch == open {
                 depth += 1;
             } else if ch == close {
@@ -270,12 +253,210 @@ impl super::BcExecuter {
                     continue;
                 }
             }
-            result.push(bytes[i] as char);
-            i += 1;
+            let ch = statement[i..].chars().next().unwrap_or_default();
+            result.push(ch);
+            i += ch.len_utf8();
+        }
+        Self::rewrite_degree_suffix(&Self::rewrite_postfix_factorial(&result))
+    }
+
+    /// Turns `30°` into `deg(30)`, the same angle in the current `angle`
+    /// unit. The operand is found as for postfix `!`.
+    fn rewrite_degree_suffix(statement: &str) -> String {
+        if !statement.contains('°') {
+            return statement.to_string();
+        }
+        let mut result = String::with_capacity(statement.len());
+        for ch in statement.chars() {
+            if ch != '°' {
+                result.push(ch);
+                continue;
+            }
+            let end = result.trim_end().len();
+            let start = Self::postfix_operand_start(&result[..end]);
+            let operand = result[start..end].to_string();
+            result.truncate(start);
+            result.push_str("deg(");
+            result.push_str(&operand);
+            result.push(')');
+        }
+        result
+    }
+
+    /// Turns postfix `n!` into `factorial(n)`. The operand is the number,
+    /// name, call or parenthesised group right before the `!`; `!=` and a
+    /// prefix `!` are left alone.
+    fn rewrite_postfix_factorial(statement: &str) -> String {
+        let mut result = String::with_capacity(statement.len());
+        for (idx, ch) in statement.char_indices() {
+            let postfix = ch == '!'
+                && !statement[idx + 1..].starts_with('=')
+                && result.trim_end().ends_with(|c: char| {
+                    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == ')'
+                });
+            if !postfix {
+                result.push(ch);
+                continue;
+            }
+            let end = result.trim_end().len();
+            let start = Self::postfix_operand_start(&result[..end]);
+            let operand = result[start..end].to_string();
+            result.truncate(start);
+            result.push_str("factorial(");
+            result.push_str(&operand);
+            result.push(')');
+        }
+        result
+    }
+
+    fn postfix_operand_start(text: &str) -> usize {
+        let bytes = text.as_bytes();
+        let mut start = bytes.len();
+        if text.ends_with(')') {
+            let mut depth = 0;
+            for (idx, byte) in bytes.iter().enumerate().rev() {
+                match byte {
+                    b')' => depth += 1,
+                    b'(' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    start = idx;
+                    break;
+                }
+            }
+        }
+        while start > 0 && (Self::is_ident_char(bytes[start - 1]) || bytes[start - 1] == b'.') {
+            start -= 1;
+        }
+        start
+    }
+
+    /// Reads `,` as the decimal point for decimal-comma locales. Arguments
+    /// are then separated by `;`, which only splits statements outside
+    /// parentheses: `max(1,5; 2)` becomes `max(1.5, 2)`. Only the `;` of a
+    /// call's argument list is rewritten, so `for (i = 0; i < 3; i = i + 1)`
+    /// keeps its header. The items of a
+    /// `print` stay separated by commas; there only a comma between two
+    /// digits is a decimal point: `print 1,5, "\n"`. The entries of a matrix
+    /// row are separated by `;` too: `[[1,5; 2]; [3; 4]]`.
+    pub(super) fn rewrite_decimal_commas(statement: &str) -> String {
+        let bytes = statement.as_bytes();
+        let mut open = Vec::new();
+        let mut in_string = false;
+        // How many brackets were open where the current `print` started.
+        let mut print_depth = None;
+        let mut result = String::with_capacity(statement.len());
+        for (idx, ch) in statement.char_indices() {
+            if ch == '"' {
+                in_string = !in_string;
+            }
+            if in_string || ch == '"' {
+                result.push(ch);
+                continue;
+            }
+            let in_print = print_depth == Some(open.len());
+            match ch {
+                // `c` marks the argument list of a call.
+                '(' if Self::opens_call(bytes, idx) => open.push('c'),
+                '(' | '[' | '{' => open.push(ch),
+                ')' | ']' | '}' => {
+                    open.pop();
+                    if print_depth.is_some_and(|depth| open.len() < depth) {
+                        print_depth = None;
+                    }
+                }
+                ',' if in_print && !Self::between_digits(bytes, idx) => {}
+                ',' => {
+                    result.push('.');
+                    continue;
+                }
+                ';' if open.last() == Some(&'c') || open.ends_with(&['[', '[']) => {
+                    result.push(',');
+                    continue;
+                }
+                ';' | '\n' if in_print => print_depth = None,
+                'p' if statement[idx..].starts_with("print")
+                    && !Self::has_ident_before(bytes, idx)
+                    && !bytes
+                        .get(idx + "print".len())
+                        .is_some_and(|&byte| Self::is_ident_char(byte)) =>
+                {
+                    print_depth = Some(open.len());
+                }
+                _ => {}
+            }
+            result.push(ch);
         }
         result
     }
 
+    /// Drops the grouping separators of numbers written the locale's way:
+    /// `1.234.567,5` reads as `1234567,5` with `group` set to `.`. Only one
+    /// to three leading digits followed by whole three-digit groups count,
+    /// so `1.5` and `192.168.0.1` are left alone.
+    pub(super) fn strip_digit_groups(statement: &str, group: char) -> String {
+        let bytes = statement.as_bytes();
+        let digits_at = |idx: usize| {
+            statement[idx..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(statement.len() - idx)
+        };
+        let mut in_string = false;
+        let mut result = String::with_capacity(statement.len());
+        let mut idx = 0;
+        while let Some(ch) = statement[idx..].chars().next() {
+            if ch == '"' {
+                in_string = !in_string;
+            }
+            let starts_number = !in_string
+                && ch.is_ascii_digit()
+                && (idx == 0 || !(Self::is_ident_char(bytes[idx - 1]) || bytes[idx - 1] == b'.'));
+            if !starts_number {
+                result.push(ch);
+                idx += ch.len_utf8();
+                continue;
+            }
+            let lead = digits_at(idx);
+            let mut end = idx + lead;
+            let mut groups = 0;
+            while statement[end..].starts_with(group) && digits_at(end + group.len_utf8()) == 3 {
+                end += group.len_utf8() + 3;
+                groups += 1;
+            }
+            let number = &statement[idx..end];
+            if groups > 0 && lead <= 3 && !statement[end..].starts_with(group) {
+                result.extend(number.chars().filter(|&c| c != group));
+            } else {
+                result.push_str(number);
+            }
+            idx = end;
+        }
+        result
+    }
+
+    /// Whether the `(` at `idx` follows a function name, rather than a
+    /// keyword such as `for` or nothing at all.
+    fn opens_call(bytes: &[u8], idx: usize) -> bool {
+        let end = bytes[..idx]
+            .iter()
+            .rposition(|byte| !byte.is_ascii_whitespace())
+            .map_or(0, |last| last + 1);
+        let start = bytes[..end]
+            .iter()
+            .rposition(|&byte| !Self::is_ident_char(byte))
+            .map_or(0, |last| last + 1);
+        let name = &bytes[start..end];
+        name.first().is_some_and(|byte| !byte.is_ascii_digit())
+            && !matches!(name, b"for" | b"while" | b"if")
+    }
+
+    fn between_digits(bytes: &[u8], idx: usize) -> bool {
+        idx > 0
+            && bytes[idx - 1].is_ascii_digit()
+            && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit)
+    }
+
     pub(super) fn is_valid_identifier(name: &str) -> bool {
         let mut chars = name.chars();
         match chars.next() {
@@ -0,0 +1,665 @@
+use dashu::base::Abs;
+use dashu::Decimal;
+
+use super::datetime::Temporal;
+use super::error::BcError;
+use super::literals::LiteralTable;
+use super::mathlib;
+use super::network::IpAddress;
+use super::number::BcNumber;
+
+impl super::BcExecuter {
+    /// Evaluates `expr` with POSIX bc's grammar, tracking the scale of every
+    /// intermediate result instead of carrying full precision through.
+    pub(super) fn eval_posix_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let tree = PosixParser::parse_expression(expr, self.runtime.ibase())?;
+        self.eval_posix_node(&tree)
+    }
+
+    pub(super) fn eval_posix_node(&mut self, node: &PosixExpr) -> Result<BcNumber, BcError> {
+        let scale = self.runtime.scale();
+        match node {
+            PosixExpr::Number(number) => Ok(number.clone()),
+            PosixExpr::Quantity(..) => Err(units_unsupported()),
+            PosixExpr::Time(_) => Err(time_unsupported()),
+            PosixExpr::Address(_) => Err(addresses_unsupported()),
+            node @ (PosixExpr::Element(..) | PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                self.eval_array_node(node, |exec, node| exec.eval_posix_node(node))
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => Ok(number),
+                None => self.eval_posix_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_posix_node(arg)?);
+                }
+                self.eval_posix_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => Ok(self.eval_posix_node(operand)?.negate()),
+            PosixExpr::Not(operand) => {
+                let value = self.eval_posix_node(operand)?;
+                Ok(BcNumber::from_bool(value.is_zero()))
+            }
+            node @ (PosixExpr::Complement(_) | PosixExpr::Bitwise(..)) => {
+                self.eval_bitwise_node(node, |exec, node| exec.eval_posix_node(node))
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_posix_node(lhs)?.is_zero() {
+                    return Ok(BcNumber::from_bool(false));
+                }
+                let right = self.eval_posix_node(rhs)?;
+                Ok(BcNumber::from_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_posix_node(lhs)?.is_zero() {
+                    return Ok(BcNumber::from_bool(true));
+                }
+                let right = self.eval_posix_node(rhs)?;
+                Ok(BcNumber::from_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let left = self.eval_posix_node(lhs)?;
+                let right = self.eval_posix_node(rhs)?;
+                match op {
+                    PosixOp::Add => Ok(left.add(&right)),
+                    PosixOp::Sub => Ok(left.sub(&right)),
+                    PosixOp::Mul => Ok(left.mul(&right, scale)),
+                    PosixOp::Div => left.div(&right, scale),
+                    PosixOp::Mod => left.rem(&right, scale),
+                    PosixOp::Pow => left.pow(&right, scale),
+                    PosixOp::Lt => Ok(BcNumber::from_bool(left.value < right.value)),
+                    PosixOp::Le => Ok(BcNumber::from_bool(left.value <= right.value)),
+                    PosixOp::Gt => Ok(BcNumber::from_bool(left.value > right.value)),
+                    PosixOp::Ge => Ok(BcNumber::from_bool(left.value >= right.value)),
+                    PosixOp::Eq => Ok(BcNumber::from_bool(left.value == right.value)),
+                    PosixOp::Ne => Ok(BcNumber::from_bool(left.value != right.value)),
+                    _ => unreachable!("handled above"),
+                }
+            }
+        }
+    }
+
+    fn eval_posix_call(&mut self, name: &str, args: Vec<BcNumber>) -> Result<BcNumber, BcError> {
+        let scale = self.runtime.scale();
+        let arg_scale = args.iter().map(|arg| arg.scale).max().unwrap_or(0);
+        match (name, args.as_slice()) {
+            ("scale", [arg]) => return Ok(BcNumber::new(Decimal::from(arg.scale), 0)),
+            ("length", [arg]) => {
+                return Ok(BcNumber::new(Decimal::from(Self::posix_length(arg)), 0))
+            }
+            ("sqrt", [arg]) => {
+                let root =
+                    self.precise_unary("sqrt", std::slice::from_ref(&arg.value), mathlib::sqrt)?;
+                return Ok(BcNumber::at_scale(root, scale.max(arg.scale)));
+            }
+            _ => {}
+        }
+
+        let builtin = if name == "int" { "trunc" } else { name };
+        let values: Vec<Decimal> = args
+            .iter()
+            .map(|arg| self.promote_precision(arg.value.clone()))
+            .collect();
+        if let Some(result) = self.call_builtin_function(builtin, &values) {
+            return Ok(BcNumber::natural(result?).truncate(scale.max(arg_scale)));
+        }
+
+        if let Some(result) = self.call_function(name, args)? {
+            return Ok(result);
+        }
+
+        Err(BcError::Error(format!("Undefined identifier: {name}")))
+    }
+
+    /// Significant digits of `number` written at its own scale, the way bc
+    /// counts them: `length(1.50)` is 3 and `length(.05)` is 1.
+    fn posix_length(number: &BcNumber) -> i64 {
+        let written = Self::format_decimal_at_scale(&number.value.clone().abs(), number.scale);
+        let (integer, fraction) = written.split_once('.').unwrap_or((&written, ""));
+        let padding = (number.scale as usize).saturating_sub(fraction.len());
+        let digits = format!("{integer}{fraction}{}", "0".repeat(padding));
+        digits.trim_start_matches('0').len().max(1) as i64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PosixOp {
+    Or,
+    And,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shl,
+    Shr,
+}
+
+impl PosixOp {
+    /// `|`, `xor`, `&`, `<<` and `>>`, which only make sense on integers.
+    pub(super) fn is_bitwise(self) -> bool {
+        matches!(
+            self,
+            Self::BitOr | Self::BitXor | Self::BitAnd | Self::Shl | Self::Shr
+        )
+    }
+}
+
+/// What the fractional evaluators answer for bitwise operators; `eval_number`
+/// sends every expression using one to the integer evaluator instead.
+pub(super) fn integer_only() -> BcError {
+    BcError::Error("Bitwise operators need integer operands".to_string())
+}
+
+/// What evaluators other than `eval_unit_expression` answer for a number
+/// with a unit suffix, which only programmer mode keeps them from seeing.
+pub(super) fn units_unsupported() -> BcError {
+    BcError::Error("Units cannot be used in this mode".to_string())
+}
+
+/// The same for date and duration literals, which only
+/// `eval_time_expression` understands.
+pub(super) fn time_unsupported() -> BcError {
+    BcError::Error("Dates and durations cannot be used in this mode".to_string())
+}
+
+/// The same for IP address literals, which only `eval_network_expression`
+/// understands.
+pub(super) fn addresses_unsupported() -> BcError {
+    BcError::Error("IP addresses cannot be used in this mode".to_string())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum PosixToken {
+    Number(String),
+    Time(String),
+    Address(String),
+    Unit(String),
+    Ident(String),
+    Op(PosixOp),
+    Not,
+    Complement,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+#[derive(Debug)]
+pub(super) enum PosixExpr {
+    Number(BcNumber),
+    Quantity(BcNumber, String),
+    Time(Temporal),
+    Address(IpAddress),
+    Name(String),
+    Element(String, Box<PosixExpr>),
+    /// A whole array, `a[]`, which only a function argument may be.
+    Array(String),
+    Call(String, Vec<PosixExpr>),
+    /// A call handed at least one whole array, which only user functions
+    /// and the statistics builtins take.
+    ArrayCall(String, Vec<PosixExpr>),
+    Negate(Box<PosixExpr>),
+    Not(Box<PosixExpr>),
+    Complement(Box<PosixExpr>),
+    /// `&`, `|`, `xor`, `<<` or `>>`, whose operands have to be integers.
+    Bitwise(PosixOp, Box<PosixExpr>, Box<PosixExpr>),
+    Binary(PosixOp, Box<PosixExpr>, Box<PosixExpr>),
+}
+
+pub(super) struct PosixParser {
+    tokens: Vec<PosixToken>,
+    position: usize,
+    ibase: u32,
+}
+
+impl PosixParser {
+    /// Parses a whole bc expression, reading literals in `ibase`.
+    pub(super) fn parse_expression(expr: &str, ibase: u32) -> Result<PosixExpr, BcError> {
+        let tokens = Self::tokenize(expr, ibase)?;
+        let mut parser = Self::new(tokens, ibase);
+        let tree = parser.parse_or()?;
+        parser.expect_end()?;
+        Ok(tree)
+    }
+
+    /// Whether `expr` uses a bitwise operator or `~`; expressions that do
+    /// not tokenize are left for the regular evaluator to report.
+    pub(super) fn mentions_bitwise(expr: &str, ibase: u32) -> bool {
+        if !expr.contains(['&', '|', '~', '<', '>', 'x']) {
+            return false;
+        }
+        Self::tokenize(expr, ibase).is_ok_and(|tokens| {
+            tokens.iter().any(|token| match token {
+                PosixToken::Op(op) => op.is_bitwise(),
+                PosixToken::Complement => true,
+                _ => false,
+            })
+        })
+    }
+
+    /// Whether `expr` has a number with a unit suffix such as `3 km`.
+    pub(super) fn mentions_units(expr: &str, ibase: u32) -> bool {
+        Self::tokenize(expr, ibase).is_ok_and(|tokens| {
+            tokens
+                .iter()
+                .any(|token| matches!(token, PosixToken::Unit(_)))
+        })
+    }
+
+    /// Whether `expr` has a date or duration literal, or calls one of the
+    /// functions that make a date out of plain numbers.
+    pub(super) fn mentions_time(expr: &str, ibase: u32) -> bool {
+        Self::tokenize(expr, ibase).is_ok_and(|tokens| {
+            tokens.iter().any(|token| match token {
+                PosixToken::Time(_) => true,
+                PosixToken::Ident(name) => name == "now" || name == "fromepoch",
+                _ => false,
+            })
+        })
+    }
+
+    /// Whether `expr` indexes an array or passes one whole.
+    pub(super) fn mentions_arrays(expr: &str, ibase: u32) -> bool {
+        Self::tokenize(expr, ibase).is_ok_and(|tokens| tokens.contains(&PosixToken::LBracket))
+    }
+
+    /// Whether `expr` has an IPv4 or IPv6 address literal.
+    pub(super) fn mentions_addresses(expr: &str, ibase: u32) -> bool {
+        Self::tokenize(expr, ibase).is_ok_and(|tokens| {
+            tokens
+                .iter()
+                .any(|token| matches!(token, PosixToken::Address(_)))
+        })
+    }
+
+    fn new(tokens: Vec<PosixToken>, ibase: u32) -> Self {
+        Self {
+            tokens,
+            position: 0,
+            ibase,
+        }
+    }
+
+    fn tokenize(expr: &str, ibase: u32) -> Result<Vec<PosixToken>, BcError> {
+        let chars: Vec<char> = expr.chars().collect();
+        let mut tokens = Vec::new();
+        let mut index = 0;
+        while index < chars.len() {
+            let ch = chars[index];
+            let next = chars.get(index + 1).copied();
+            let after_number = matches!(tokens.last(), Some(PosixToken::Number(_)));
+            let unit = (ibase == 10 && after_number)
+                .then(|| LiteralTable::scan_unit_suffix(&chars, index))
+                .flatten();
+            if let Some(consumed) = unit {
+                tokens.push(PosixToken::Unit(
+                    chars[index..index + consumed].iter().collect(),
+                ));
+                index += consumed;
+                continue;
+            }
+            if ch.is_whitespace() {
+                index += 1;
+                continue;
+            }
+            let address = (ibase == 10)
+                .then(|| LiteralTable::scan_ip_literal(&chars, index))
+                .flatten();
+            if let Some(consumed) = address {
+                tokens.push(PosixToken::Address(
+                    chars[index..index + consumed].iter().collect(),
+                ));
+                index += consumed;
+                continue;
+            }
+            let time = (ibase == 10)
+                .then(|| {
+                    LiteralTable::scan_date_literal(&chars, index)
+                        .or_else(|| LiteralTable::scan_duration_literal(&chars, index))
+                })
+                .flatten();
+            if let Some(consumed) = time {
+                tokens.push(PosixToken::Time(
+                    chars[index..index + consumed].iter().collect(),
+                ));
+                index += consumed;
+                continue;
+            }
+            if let Some(consumed) = LiteralTable::scan_measurement_literal(&chars, index) {
+                tokens.push(PosixToken::Number(
+                    chars[index..index + consumed].iter().collect(),
+                ));
+                index += consumed;
+                continue;
+            }
+            let scientific = (ibase == 10)
+                .then(|| LiteralTable::scan_scientific_literal(&chars, index))
+                .flatten();
+            if let Some(consumed) = scientific {
+                tokens.push(PosixToken::Number(
+                    chars[index..index + consumed].iter().collect(),
+                ));
+                index += consumed;
+                continue;
+            }
+            if let Some(consumed) = LiteralTable::scan_radix_literal(&chars, index, ibase) {
+                tokens.push(PosixToken::Number(
+                    chars[index..index + consumed].iter().collect(),
+                ));
+                index += consumed;
+                continue;
+            }
+            if ch.is_ascii_digit() || (ch == '.' && next.is_some_and(|c| c.is_ascii_digit())) {
+                if ibase != 10 {
+                    return Err(BcError::Error(format!(
+                        "Failed to parse literal in ibase {ibase}"
+                    )));
+                }
+                let start = index;
+                while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.')
+                {
+                    index += 1;
+                }
+                tokens.push(PosixToken::Number(chars[start..index].iter().collect()));
+                continue;
+            }
+            if ch.is_ascii_alphabetic() || ch == '_' {
+                let start = index;
+                while index < chars.len()
+                    && (chars[index].is_ascii_alphanumeric() || chars[index] == '_')
+                {
+                    index += 1;
+                }
+                let word: String = chars[start..index].iter().collect();
+                tokens.push(if word == "xor" {
+                    PosixToken::Op(PosixOp::BitXor)
+                } else {
+                    PosixToken::Ident(word)
+                });
+                continue;
+            }
+            let (token, width) = match (ch, next) {
+                ('|', Some('|')) => (PosixToken::Op(PosixOp::Or), 2),
+                ('&', Some('&')) => (PosixToken::Op(PosixOp::And), 2),
+                ('<', Some('=')) => (PosixToken::Op(PosixOp::Le), 2),
+                ('>', Some('=')) => (PosixToken::Op(PosixOp::Ge), 2),
+                ('=', Some('=')) => (PosixToken::Op(PosixOp::Eq), 2),
+                ('!', Some('=')) => (PosixToken::Op(PosixOp::Ne), 2),
+                ('<', Some('<')) => (PosixToken::Op(PosixOp::Shl), 2),
+                ('>', Some('>')) => (PosixToken::Op(PosixOp::Shr), 2),
+                ('|', _) => (PosixToken::Op(PosixOp::BitOr), 1),
+                ('&', _) => (PosixToken::Op(PosixOp::BitAnd), 1),
+                ('~', _) => (PosixToken::Complement, 1),
+                ('<', _) => (PosixToken::Op(PosixOp::Lt), 1),
+                ('>', _) => (PosixToken::Op(PosixOp::Gt), 1),
+                ('+', _) => (PosixToken::Op(PosixOp::Add), 1),
+                ('-', _) => (PosixToken::Op(PosixOp::Sub), 1),
+                ('*', _) => (PosixToken::Op(PosixOp::Mul), 1),
+                ('/', _) => (PosixToken::Op(PosixOp::Div), 1),
+                ('%', _) => (PosixToken::Op(PosixOp::Mod), 1),
+                ('^', _) => (PosixToken::Op(PosixOp::Pow), 1),
+                ('!', _) => (PosixToken::Not, 1),
+                ('(', _) => (PosixToken::LParen, 1),
+                (')', _) => (PosixToken::RParen, 1),
+                ('[', _) => (PosixToken::LBracket, 1),
+                (']', _) => (PosixToken::RBracket, 1),
+                (',', _) => (PosixToken::Comma, 1),
+                _ => return Err(BcError::Error(format!("Unexpected character '{ch}'"))),
+            };
+            tokens.push(token);
+            index += width;
+        }
+        Ok(tokens)
+    }
+
+    fn parse_or(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_and()?;
+        while self.eat_op(&[PosixOp::Or]).is_some() {
+            let rhs = self.parse_and()?;
+            lhs = PosixExpr::Binary(PosixOp::Or, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_not()?;
+        while self.eat_op(&[PosixOp::And]).is_some() {
+            let rhs = self.parse_not()?;
+            lhs = PosixExpr::Binary(PosixOp::And, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_not(&mut self) -> Result<PosixExpr, BcError> {
+        if self.peek() == Some(&PosixToken::Not) {
+            self.position += 1;
+            return Ok(PosixExpr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_relational()
+    }
+
+    fn parse_relational(&mut self) -> Result<PosixExpr, BcError> {
+        const RELATIONAL: &[PosixOp] = &[
+            PosixOp::Lt,
+            PosixOp::Le,
+            PosixOp::Gt,
+            PosixOp::Ge,
+            PosixOp::Eq,
+            PosixOp::Ne,
+        ];
+        let mut lhs = self.parse_bit_or()?;
+        while let Some(op) = self.eat_op(RELATIONAL) {
+            let rhs = self.parse_bit_or()?;
+            lhs = PosixExpr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /// `|` binds loosest of the bitwise operators, then `xor`, `&` and the
+    /// shifts, all below arithmetic and above comparisons, as in C's
+    /// successors that fixed its precedence.
+    fn parse_bit_or(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_bit_xor()?;
+        while self.eat_op(&[PosixOp::BitOr]).is_some() {
+            let rhs = self.parse_bit_xor()?;
+            lhs = PosixExpr::Bitwise(PosixOp::BitOr, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_bit_xor(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_bit_and()?;
+        while self.eat_op(&[PosixOp::BitXor]).is_some() {
+            let rhs = self.parse_bit_and()?;
+            lhs = PosixExpr::Bitwise(PosixOp::BitXor, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_bit_and(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_shift()?;
+        while self.eat_op(&[PosixOp::BitAnd]).is_some() {
+            let rhs = self.parse_shift()?;
+            lhs = PosixExpr::Bitwise(PosixOp::BitAnd, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_shift(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_additive()?;
+        while let Some(op) = self.eat_op(&[PosixOp::Shl, PosixOp::Shr]) {
+            let rhs = self.parse_additive()?;
+            lhs = PosixExpr::Bitwise(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_additive(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_multiplicative()?;
+        while let Some(op) = self.eat_op(&[PosixOp::Add, PosixOp::Sub]) {
+            let rhs = self.parse_multiplicative()?;
+            lhs = PosixExpr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<PosixExpr, BcError> {
+        let mut lhs = self.parse_power()?;
+        while let Some(op) = self.eat_op(&[PosixOp::Mul, PosixOp::Div, PosixOp::Mod]) {
+            let rhs = self.parse_power()?;
+            lhs = PosixExpr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_power(&mut self) -> Result<PosixExpr, BcError> {
+        let base = self.parse_unary()?;
+        if self.eat_op(&[PosixOp::Pow]).is_some() {
+            let exponent = self.parse_power()?;
+            return Ok(PosixExpr::Binary(
+                PosixOp::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn parse_unary(&mut self) -> Result<PosixExpr, BcError> {
+        match self.peek() {
+            Some(PosixToken::Op(PosixOp::Sub)) => {
+                self.position += 1;
+                Ok(PosixExpr::Negate(Box::new(self.parse_unary()?)))
+            }
+            Some(PosixToken::Op(PosixOp::Add)) => {
+                self.position += 1;
+                self.parse_unary()
+            }
+            Some(PosixToken::Not) => {
+                self.position += 1;
+                Ok(PosixExpr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(PosixToken::Complement) => {
+                self.position += 1;
+                Ok(PosixExpr::Complement(Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<PosixExpr, BcError> {
+        match self.next() {
+            Some(PosixToken::Number(literal)) => {
+                let number = if LiteralTable::split_measurement(&literal).is_some() {
+                    BcNumber::parse_measurement(&literal, self.ibase)?
+                } else {
+                    BcNumber::parse_in_base(&literal, self.ibase)?
+                };
+                if let Some(PosixToken::Unit(unit)) = self.peek() {
+                    let unit = unit.trim().to_string();
+                    self.position += 1;
+                    return Ok(PosixExpr::Quantity(number, unit));
+                }
+                Ok(PosixExpr::Number(number))
+            }
+            Some(PosixToken::Time(literal)) => Temporal::parse(&literal).map(PosixExpr::Time),
+            Some(PosixToken::Address(literal)) => {
+                IpAddress::parse(&literal).map(PosixExpr::Address)
+            }
+            Some(PosixToken::Ident(name)) => {
+                if self.peek() == Some(&PosixToken::LBracket) {
+                    self.position += 1;
+                    if self.peek() == Some(&PosixToken::RBracket) {
+                        self.position += 1;
+                        return Ok(PosixExpr::Array(name));
+                    }
+                    let index = self.parse_or()?;
+                    if self.next() != Some(PosixToken::RBracket) {
+                        return Err(BcError::Error(format!(
+                            "Expected ']' after index into {name}"
+                        )));
+                    }
+                    return Ok(PosixExpr::Element(name, Box::new(index)));
+                }
+                if self.peek() != Some(&PosixToken::LParen) {
+                    return Ok(PosixExpr::Name(name));
+                }
+                self.position += 1;
+                let mut args = Vec::new();
+                if self.peek() == Some(&PosixToken::RParen) {
+                    self.position += 1;
+                    return Ok(PosixExpr::Call(name, args));
+                }
+                loop {
+                    args.push(self.parse_or()?);
+                    match self.next() {
+                        Some(PosixToken::Comma) => continue,
+                        Some(PosixToken::RParen) => break,
+                        _ => {
+                            return Err(BcError::Error(format!(
+                                "Expected ')' after arguments to {name}"
+                            )))
+                        }
+                    }
+                }
+                if args.iter().any(|arg| matches!(arg, PosixExpr::Array(_))) {
+                    return Ok(PosixExpr::ArrayCall(name, args));
+                }
+                Ok(PosixExpr::Call(name, args))
+            }
+            Some(PosixToken::LParen) => {
+                let inner = self.parse_or()?;
+                if self.next() != Some(PosixToken::RParen) {
+                    return Err(BcError::Error("Expected ')'".to_string()));
+                }
+                Ok(inner)
+            }
+            Some(_) => Err(BcError::Error("Unexpected token in expression".to_string())),
+            None => Err(BcError::Error("Unexpected end of expression".to_string())),
+        }
+    }
+
+    fn eat_op(&mut self, ops: &[PosixOp]) -> Option<PosixOp> {
+        match self.peek() {
+            Some(PosixToken::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.position += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn peek(&self) -> Option<&PosixToken> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<PosixToken> {
+        let token = self.tokens.get(self.position).cloned();
+        if token.is_some() {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn expect_end(&mut self) -> Result<(), BcError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(BcError::Error("Unexpected token in expression".to_string())),
+        }
+    }
+}
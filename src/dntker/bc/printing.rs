@@ -0,0 +1,133 @@
+use std::io::BufRead;
+
+use dashu::Decimal;
+
+use super::error::BcError;
+use super::number::BcNumber;
+
+impl super::BcExecuter {
+    /// `print "x = ", x, "\n"`: writes each string with its escapes expanded
+    /// and each value as a result would be written, with nothing in between
+    /// and no newline of its own. Decimal-comma locales keep the separating
+    /// commas as they are; see `rewrite_decimal_commas`.
+    pub(super) fn eval_print(&mut self, stmt: &str) -> Result<(), BcError> {
+        let list = stmt.trim_start()["print".len()..].trim();
+        if list.is_empty() {
+            return Err(BcError::Error("print needs something to print".to_string()));
+        }
+        for item in Self::split_top_level(list, ',') {
+            if item.starts_with('"') {
+                let text = unescape(string_literal(item)?);
+                self.printed.push_str(&text);
+            } else {
+                let value = self.eval_number(item)?;
+                let text = self.format_result(value);
+                self.printed.push_str(&text);
+            }
+        }
+        Ok(())
+    }
+
+    /// A statement that is only a string prints it as written: bc expands
+    /// escapes in `print` alone.
+    pub(super) fn eval_string(&mut self, stmt: &str) -> Result<(), BcError> {
+        let text = string_literal(stmt)?;
+        self.printed.push_str(text);
+        Ok(())
+    }
+
+    /// Writes the text a statement such as `factor(n)` answers with on a
+    /// line of its own.
+    pub(super) fn write_line(&mut self, text: &str) {
+        self.printed.push_str(text);
+        self.printed.push('\n');
+    }
+
+    /// What `exec` gives back once something was printed: the printed text,
+    /// then the value of a final expression statement the way bc would show
+    /// it. A newline ending the text is left to the caller.
+    pub(super) fn printed_output(&mut self, value: Option<BcNumber>) -> String {
+        let mut output = std::mem::take(&mut self.printed);
+        match value {
+            Some(value) => {
+                self.last_result = Some(value.clone());
+                output.push_str(&self.format_result(value));
+            }
+            None => {
+                if output.ends_with('\n') {
+                    output.pop();
+                }
+            }
+        }
+        output
+    }
+
+    /// Whether bc would show the value of `stmt`: an expression, not an
+    /// assignment, definition, control statement or string.
+    pub(super) fn shows_value(stmt: &str) -> bool {
+        let stmt = stmt.trim();
+        !stmt.starts_with('"')
+            && Self::detect_assignment(stmt).is_none()
+            && !["define", "return", "if", "while", "for", "auto", "print"]
+                .iter()
+                .any(|keyword| Self::starts_with_keyword(stmt, keyword))
+    }
+
+    /// `read()`: the next line of input as a number in `ibase`.
+    pub(super) fn builtin_read(&mut self, args: &[Decimal]) -> Result<Decimal, BcError> {
+        if !args.is_empty() {
+            return Err(BcError::Error("read() takes no arguments".to_string()));
+        }
+        let input = self
+            .input
+            .as_mut()
+            .ok_or_else(|| BcError::Error("read(): no input is attached".to_string()))?;
+        let mut line = String::new();
+        let read = input
+            .read_line(&mut line)
+            .map_err(|e| BcError::Error(format!("read(): {e}")))?;
+        if read == 0 {
+            return Err(BcError::Error("read(): no more input".to_string()));
+        }
+        let literal = line.trim();
+        let number = match literal.strip_prefix('-') {
+            Some(digits) => BcNumber::parse_in_base(digits.trim(), self.runtime.ibase())?.negate(),
+            None => BcNumber::parse_in_base(literal, self.runtime.ibase())?,
+        };
+        Ok(number.value)
+    }
+}
+
+/// The text between the quotes of a string literal.
+fn string_literal(item: &str) -> Result<&str, BcError> {
+    item.strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|text| !text.contains('"'))
+        .ok_or_else(|| BcError::Error(format!("Invalid string: {item}")))
+}
+
+/// Expands bc's escapes: `\a`, `\b`, `\f`, `\n`, `\r`, `\t`, `\q` for a
+/// double quote and `\e` for a backslash. A backslash before anything else
+/// is dropped along with that character, as GNU bc ignores it.
+fn unescape(text: &str) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            expanded.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('a') => expanded.push('\x07'),
+            Some('b') => expanded.push('\x08'),
+            Some('f') => expanded.push('\x0c'),
+            Some('n') => expanded.push('\n'),
+            Some('r') => expanded.push('\r'),
+            Some('t') => expanded.push('\t'),
+            Some('q') => expanded.push('"'),
+            Some('e') => expanded.push('\\'),
+            _ => {}
+        }
+    }
+    expanded
+}
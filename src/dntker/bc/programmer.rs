@@ -0,0 +1,290 @@
+use std::convert::TryFrom;
+
+use dashu::base::Sign;
+use dashu::integer::IBig;
+use dashu::Decimal;
+
+use super::error::BcError;
+use super::number::BcNumber;
+use super::posix::{
+    addresses_unsupported, integer_only, time_unsupported, units_unsupported, PosixExpr, PosixOp,
+    PosixParser,
+};
+use super::runtime::WordType;
+
+/// Longest left shift allowed outside programmer mode, where nothing wraps
+/// the result back into a word.
+const MAX_UNBOUNDED_SHIFT: usize = 1 << 16;
+
+impl super::BcExecuter {
+    /// Whether `expr` has to go through `eval_integer_expression`, which
+    /// everything does in programmer mode.
+    pub(super) fn needs_integer_evaluation(&self) -> bool {
+        self.runtime.word().is_some()
+    }
+
+    /// Whether `expr` uses `&`, `|`, `xor`, `~`, `<<` or `>>`.
+    pub(super) fn mentions_bitwise(&self, expr: &str) -> bool {
+        PosixParser::mentions_bitwise(expr, self.runtime.ibase())
+    }
+
+    /// Evaluates a bitwise operator for any of the evaluators other than
+    /// programmer mode's; `eval` evaluates the operands the way that
+    /// evaluator does. The operands have to be plain integers: truncating a
+    /// fraction or dropping a unit would change the answer without a word.
+    pub(super) fn eval_bitwise_node(
+        &mut self,
+        node: &PosixExpr,
+        mut eval: impl FnMut(&mut Self, &PosixExpr) -> Result<BcNumber, BcError>,
+    ) -> Result<BcNumber, BcError> {
+        let value = match node {
+            PosixExpr::Complement(operand) => !bitwise_operand(&eval(self, operand)?)?,
+            PosixExpr::Bitwise(op, lhs, rhs) => {
+                let a = bitwise_operand(&eval(self, lhs)?)?;
+                let b = bitwise_operand(&eval(self, rhs)?)?;
+                self.bitwise(*op, a, &b)?
+            }
+            _ => return eval(self, node),
+        };
+        Ok(BcNumber::new(Decimal::from(value), 0))
+    }
+
+    /// Evaluates `expr` on exact integers. Operands are truncated toward
+    /// zero, and in programmer mode every intermediate result wraps to the
+    /// word as two's complement.
+    pub(super) fn eval_integer_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let tree = PosixParser::parse_expression(expr, self.runtime.ibase())?;
+        let value = self.eval_integer_node(&tree)?;
+        Ok(BcNumber::new(Decimal::from(value), 0))
+    }
+
+    fn eval_integer_node(&mut self, node: &PosixExpr) -> Result<IBig, BcError> {
+        match node {
+            PosixExpr::Number(number) => Ok(self.wrap_word(number.units(0))),
+            PosixExpr::Quantity(..) => Err(units_unsupported()),
+            PosixExpr::Time(_) => Err(time_unsupported()),
+            PosixExpr::Address(_) => Err(addresses_unsupported()),
+            // The word bounds the values stored, not the subscripts, so
+            // `a[300]` is evaluated unwrapped and stays apart from `a[44]`.
+            node @ PosixExpr::Element(..) => {
+                let number = self.eval_array_node(node, |exec, node| exec.eval_posix_node(node))?;
+                self.word_operand(&number)
+            }
+            node @ (PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                let number = self.eval_array_node(node, |exec, node| {
+                    exec.eval_integer_node(node)
+                        .map(|value| BcNumber::new(Decimal::from(value), 0))
+                })?;
+                self.word_operand(&number)
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => self.word_operand(&number),
+                None => self.eval_integer_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_integer_node(arg)?);
+                }
+                self.eval_integer_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => {
+                let value = self.eval_integer_node(operand)?;
+                Ok(self.wrap_word(-value))
+            }
+            PosixExpr::Not(operand) => {
+                let value = self.eval_integer_node(operand)?;
+                Ok(Self::integer_bool(value.is_zero()))
+            }
+            PosixExpr::Complement(operand) => {
+                let value = self.eval_integer_node(operand)?;
+                Ok(self.wrap_word(!value))
+            }
+            PosixExpr::Bitwise(op, lhs, rhs) => {
+                let a = self.eval_integer_node(lhs)?;
+                let b = self.eval_integer_node(rhs)?;
+                let value = self.bitwise(*op, a, &b)?;
+                Ok(self.wrap_word(value))
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_integer_node(lhs)?.is_zero() {
+                    return Ok(Self::integer_bool(false));
+                }
+                let right = self.eval_integer_node(rhs)?;
+                Ok(Self::integer_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_integer_node(lhs)?.is_zero() {
+                    return Ok(Self::integer_bool(true));
+                }
+                let right = self.eval_integer_node(rhs)?;
+                Ok(Self::integer_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let a = self.eval_integer_node(lhs)?;
+                let b = self.eval_integer_node(rhs)?;
+                let value = match op {
+                    PosixOp::Add => a + b,
+                    PosixOp::Sub => a - b,
+                    PosixOp::Mul => a * b,
+                    PosixOp::Div => {
+                        if b.is_zero() {
+                            return Err(BcError::Error("Division by zero".to_string()));
+                        }
+                        a / b
+                    }
+                    PosixOp::Mod => {
+                        if b.is_zero() {
+                            return Err(BcError::Error("Modulo by zero".to_string()));
+                        }
+                        a % b
+                    }
+                    PosixOp::Pow => self.integer_power(a, &b)?,
+                    PosixOp::Lt => Self::integer_bool(a < b),
+                    PosixOp::Le => Self::integer_bool(a <= b),
+                    PosixOp::Gt => Self::integer_bool(a > b),
+                    PosixOp::Ge => Self::integer_bool(a >= b),
+                    PosixOp::Eq => Self::integer_bool(a == b),
+                    PosixOp::Ne => Self::integer_bool(a != b),
+                    _ => unreachable!("handled above"),
+                };
+                Ok(self.wrap_word(value))
+            }
+        }
+    }
+
+    /// `a op b` for the operators `PosixExpr::Bitwise` holds.
+    fn bitwise(&self, op: PosixOp, a: IBig, b: &IBig) -> Result<IBig, BcError> {
+        Ok(match op {
+            PosixOp::BitOr => a | b,
+            PosixOp::BitXor => a ^ b,
+            PosixOp::BitAnd => a & b,
+            PosixOp::Shl => {
+                let count = shift_count(b)?;
+                match self.runtime.word() {
+                    Some(word) => a << count.min(word.bits as usize),
+                    None if count > MAX_UNBOUNDED_SHIFT => {
+                        return Err(BcError::Error(
+                            "Shift count out of supported range".to_string(),
+                        ))
+                    }
+                    None => a << count,
+                }
+            }
+            PosixOp::Shr => a >> shift_count(b)?,
+            _ => unreachable!("not a bitwise operator"),
+        })
+    }
+
+    /// A variable's value in programmer mode, truncated and wrapped to the
+    /// word; units, dates and addresses have no place there.
+    fn word_operand(&self, number: &BcNumber) -> Result<IBig, BcError> {
+        if number.unit.is_some() {
+            return Err(units_unsupported());
+        }
+        if number.time.is_some() {
+            return Err(time_unsupported());
+        }
+        if number.address.is_some() {
+            return Err(addresses_unsupported());
+        }
+        Ok(self.wrap_word(number.units(0)))
+    }
+
+    /// Square-and-multiply, wrapping after every step so the intermediate
+    /// powers stay within the word.
+    fn integer_power(&self, base: IBig, exponent: &IBig) -> Result<IBig, BcError> {
+        if exponent.sign() == Sign::Negative {
+            return Err(BcError::Error(
+                "Negative exponent in integer arithmetic".to_string(),
+            ));
+        }
+        if self.runtime.word().is_none() {
+            let exponent = u32::try_from(exponent)
+                .map_err(|_| BcError::Error("Exponent out of supported range".to_string()))?;
+            return Ok(base.pow(exponent as usize));
+        }
+        let mut result = IBig::ONE;
+        let mut square = base;
+        let mut remaining = exponent.clone();
+        while !remaining.is_zero() {
+            if &remaining % 2u8 == 1 {
+                result = self.wrap_word(result * &square);
+            }
+            square = self.wrap_word(square.clone() * &square);
+            remaining >>= 1;
+        }
+        Ok(result)
+    }
+
+    fn eval_integer_call(&mut self, name: &str, args: Vec<IBig>) -> Result<IBig, BcError> {
+        let values: Vec<Decimal> = args.iter().cloned().map(Decimal::from).collect();
+        if let Some(result) = self.call_builtin_function(name, &values) {
+            return Ok(self.wrap_word(BcNumber::natural(result?).units(0)));
+        }
+
+        let numbers = values
+            .into_iter()
+            .map(|value| BcNumber::new(value, 0))
+            .collect();
+        if let Some(result) = self.call_function(name, numbers)? {
+            return Ok(self.wrap_word(result.units(0)));
+        }
+
+        Err(BcError::Error(format!("Undefined identifier: {name}")))
+    }
+
+    /// `value` reduced to the current word; unchanged outside programmer mode.
+    fn wrap_word(&self, value: IBig) -> IBig {
+        match self.runtime.word() {
+            Some(word) => wrap(value, word),
+            None => value,
+        }
+    }
+
+    fn integer_bool(value: bool) -> IBig {
+        if value {
+            IBig::ONE
+        } else {
+            IBig::ZERO
+        }
+    }
+}
+
+/// `value` modulo `2^bits`, read back as negative when the word is signed
+/// and its top bit is set.
+pub(super) fn wrap(value: IBig, word: WordType) -> IBig {
+    let modulus = IBig::ONE << word.bits as usize;
+    let pattern = value & (modulus.clone() - IBig::ONE);
+    if word.signed && pattern >= (&modulus >> 1) {
+        pattern - modulus
+    } else {
+        pattern
+    }
+}
+
+fn shift_count(count: &IBig) -> Result<usize, BcError> {
+    if count.sign() == Sign::Negative {
+        return Err(BcError::Error("Negative shift count".to_string()));
+    }
+    usize::try_from(count)
+        .map_err(|_| BcError::Error("Shift count out of supported range".to_string()))
+}
+
+/// An operand of a bitwise operator outside programmer mode, which has to be
+/// a plain integer.
+fn bitwise_operand(number: &BcNumber) -> Result<IBig, BcError> {
+    let exact = number.to_rational();
+    let plain = number.unit.is_none()
+        && number.time.is_none()
+        && number.address.is_none()
+        && number.uncertainty.is_none()
+        && number
+            .bounds
+            .as_ref()
+            .is_none_or(|bounds| bounds.lo == bounds.hi);
+    if !plain || !exact.denominator().is_one() {
+        return Err(integer_only());
+    }
+    Ok(exact.numerator().clone())
+}
@@ -0,0 +1,189 @@
+use std::convert::TryFrom;
+
+use dashu::base::{Abs, BitTest};
+use dashu::integer::IBig;
+use dashu::rational::RBig;
+use dashu::Decimal;
+use num_traits::ToPrimitive;
+
+use super::error::BcError;
+use super::expression::{power_too_large, EXACT_POWER_DIGITS};
+use super::mathlib;
+use super::number::BcNumber;
+use super::posix::{
+    addresses_unsupported, time_unsupported, units_unsupported, PosixExpr, PosixOp, PosixParser,
+};
+
+impl super::BcExecuter {
+    /// Evaluates `expr` over exact fractions. Only builtins without a closed
+    /// form (`sin`, `exp`, `sqrt`, ...) round-trip through `Decimal`.
+    pub(super) fn eval_rational_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let tree = PosixParser::parse_expression(expr, self.runtime.ibase())?;
+        let exact = self.eval_rational_node(&tree)?;
+        Ok(self.rational_number(exact))
+    }
+
+    fn rational_number(&self, exact: RBig) -> BcNumber {
+        BcNumber::rational(self.rational_to_decimal(&exact), exact)
+    }
+
+    pub(super) fn rational_to_decimal(&self, exact: &RBig) -> Decimal {
+        let numerator = self.promote_precision(Decimal::from(exact.numerator().clone()));
+        numerator / Decimal::from(exact.denominator().clone())
+    }
+
+    fn eval_rational_node(&mut self, node: &PosixExpr) -> Result<RBig, BcError> {
+        match node {
+            PosixExpr::Number(number) => Ok(number.to_rational()),
+            PosixExpr::Quantity(..) => Err(units_unsupported()),
+            PosixExpr::Time(_) => Err(time_unsupported()),
+            PosixExpr::Address(_) => Err(addresses_unsupported()),
+            node @ (PosixExpr::Element(..) | PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                let number = self.eval_array_node(node, |exec, node| {
+                    let value = exec.eval_rational_node(node)?;
+                    Ok(exec.rational_number(value))
+                })?;
+                Ok(number.to_rational())
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => Ok(number.to_rational()),
+                None => self.eval_rational_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_rational_node(arg)?);
+                }
+                self.eval_rational_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => Ok(-self.eval_rational_node(operand)?),
+            PosixExpr::Not(operand) => {
+                let value = self.eval_rational_node(operand)?;
+                Ok(Self::rational_bool(value.is_zero()))
+            }
+            node @ (PosixExpr::Complement(_) | PosixExpr::Bitwise(..)) => {
+                let number = self.eval_bitwise_node(node, |exec, node| {
+                    let value = exec.eval_rational_node(node)?;
+                    Ok(exec.rational_number(value))
+                })?;
+                Ok(number.to_rational())
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_rational_node(lhs)?.is_zero() {
+                    return Ok(Self::rational_bool(false));
+                }
+                let right = self.eval_rational_node(rhs)?;
+                Ok(Self::rational_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_rational_node(lhs)?.is_zero() {
+                    return Ok(Self::rational_bool(true));
+                }
+                let right = self.eval_rational_node(rhs)?;
+                Ok(Self::rational_bool(!right.is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let left = self.eval_rational_node(lhs)?;
+                let right = self.eval_rational_node(rhs)?;
+                match op {
+                    PosixOp::Add => Ok(left + right),
+                    PosixOp::Sub => Ok(left - right),
+                    PosixOp::Mul => Ok(left * right),
+                    PosixOp::Div => {
+                        if right.is_zero() {
+                            return Err(BcError::Error("Division by zero".to_string()));
+                        }
+                        Ok(left / right)
+                    }
+                    PosixOp::Mod => {
+                        if right.is_zero() {
+                            return Err(BcError::Error("Modulo by zero".to_string()));
+                        }
+                        let quotient = RBig::from((&left / &right).trunc());
+                        Ok(left - quotient * right)
+                    }
+                    PosixOp::Pow => self.rational_power(left, right),
+                    PosixOp::Lt => Ok(Self::rational_bool(left < right)),
+                    PosixOp::Le => Ok(Self::rational_bool(left <= right)),
+                    PosixOp::Gt => Ok(Self::rational_bool(left > right)),
+                    PosixOp::Ge => Ok(Self::rational_bool(left >= right)),
+                    PosixOp::Eq => Ok(Self::rational_bool(left == right)),
+                    PosixOp::Ne => Ok(Self::rational_bool(left != right)),
+                    _ => unreachable!("handled above"),
+                }
+            }
+        }
+    }
+
+    /// Integer powers stay exact; any other exponent goes through `Decimal`.
+    fn rational_power(&self, base: RBig, exponent: RBig) -> Result<RBig, BcError> {
+        if !exponent.denominator().is_one() {
+            let result = self.power_decimal(
+                &self.rational_to_decimal(&base),
+                &self.rational_to_decimal(&exponent),
+            )?;
+            return Ok(Self::decimal_to_rational(&result));
+        }
+        let power = ToPrimitive::to_i64(exponent.numerator())
+            .ok_or_else(|| BcError::Error("Exponent out of supported range".to_string()))?;
+        let magnitude = usize::try_from(power.unsigned_abs())
+            .map_err(|_| BcError::Error("Exponent out of supported range".to_string()))?;
+        // Roughly 10 bits make 3 digits; powers of 0 and ±1 stay small.
+        let bits = base.numerator().bit_len().saturating_sub(1)
+            + base.denominator().bit_len().saturating_sub(1);
+        if bits.saturating_mul(magnitude).saturating_mul(3) / 10 > EXACT_POWER_DIGITS {
+            return Err(power_too_large());
+        }
+        let result = base.pow(magnitude);
+        if power >= 0 {
+            return Ok(result);
+        }
+        if result.is_zero() {
+            return Err(BcError::Error("Division by zero".to_string()));
+        }
+        Ok(RBig::ONE / result)
+    }
+
+    fn eval_rational_call(&mut self, name: &str, args: Vec<RBig>) -> Result<RBig, BcError> {
+        match (name, args.as_slice()) {
+            ("abs", [arg]) => return Ok(arg.clone().abs()),
+            ("floor", [arg]) => return Ok(RBig::from(arg.floor())),
+            ("ceil", [arg]) => return Ok(RBig::from(arg.ceil())),
+            ("trunc" | "int", [arg]) => return Ok(RBig::from(arg.trunc())),
+            _ => {}
+        }
+
+        let values: Vec<Decimal> = args
+            .iter()
+            .map(|arg| self.rational_to_decimal(arg))
+            .collect();
+        if let Some(result) = self.call_builtin_function(name, &values) {
+            return Ok(Self::decimal_to_rational(&result?));
+        }
+
+        let numbers = args
+            .into_iter()
+            .map(|arg| self.rational_number(arg))
+            .collect();
+        if let Some(result) = self.call_function(name, numbers)? {
+            return Ok(result.to_rational());
+        }
+
+        Err(BcError::Error(format!("Undefined identifier: {name}")))
+    }
+
+    pub(super) fn decimal_to_rational(value: &Decimal) -> RBig {
+        BcNumber::natural(value.clone()).to_rational()
+    }
+
+    fn rational_bool(value: bool) -> RBig {
+        RBig::from(IBig::from(u8::from(value)))
+    }
+}
+
+/// `exact` as a decimal with `precision` significant digits, for code that
+/// works at a precision of its own rather than `scale`.
+pub(super) fn rational_at_precision(exact: &RBig, precision: usize) -> Decimal {
+    let numerator = mathlib::at_precision(&Decimal::from(exact.numerator().clone()), precision);
+    numerator / Decimal::from(exact.denominator().clone())
+}
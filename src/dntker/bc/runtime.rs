@@ -1,51 +1,390 @@
+use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
 use std::sync::Arc;
 
 use dashu::Decimal;
 use rand::{rngs::SmallRng, SeedableRng};
 
+use super::number::BcNumber;
+
+/// A bc array: its elements by index, where unset ones read as 0. Shared
+/// so that a `*a[]` parameter writes through to the caller's array.
+pub type Array = Rc<RefCell<BTreeMap<usize, BcNumber>>>;
+
+/// How a function takes each argument: a number, an array copied as `a[]`,
+/// or an array by reference as GNU bc's `*a[]`.
+#[derive(Clone, Debug)]
+pub enum Param {
+    Scalar(String),
+    Array(String),
+    ArrayRef(String),
+}
+
+impl Param {
+    pub fn name(&self) -> &str {
+        match self {
+            Param::Scalar(name) | Param::Array(name) | Param::ArrayRef(name) => name,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FunctionDef {
-    pub params: Arc<[String]>,
+    pub params: Arc<[Param]>,
     pub body: Arc<[String]>,
 }
 
 #[derive(Clone, Debug)]
 pub enum StatementOutcome {
     None,
-    Value(Decimal),
-    Return(Decimal),
+    Value(BcNumber),
+    Return(BcNumber),
 }
 
 impl StatementOutcome {
-    pub fn value(value: Decimal) -> Self {
+    pub fn value(value: BcNumber) -> Self {
         StatementOutcome::Value(value)
     }
 
-    pub fn ret(value: Decimal) -> Self {
+    pub fn ret(value: BcNumber) -> Self {
         StatementOutcome::Return(value)
     }
 }
 
+/// How rational mode keeps and prints values; the level is what the
+/// `rational` variable holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RationalMode {
+    Off,
+    Fraction,
+    Decimal,
+}
+
+impl RationalMode {
+    pub fn from_level(level: u32) -> Option<Self> {
+        match level {
+            0 => Some(RationalMode::Off),
+            1 => Some(RationalMode::Fraction),
+            2 => Some(RationalMode::Decimal),
+            _ => None,
+        }
+    }
+
+    pub fn level(self) -> u32 {
+        match self {
+            RationalMode::Off => 0,
+            RationalMode::Fraction => 1,
+            RationalMode::Decimal => 2,
+        }
+    }
+}
+
+/// How interval mode prints its bounds; the level is what the `interval`
+/// variable holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalMode {
+    Off,
+    Bounds,
+    Midpoint,
+}
+
+impl IntervalMode {
+    pub fn from_level(level: u32) -> Option<Self> {
+        match level {
+            0 => Some(IntervalMode::Off),
+            1 => Some(IntervalMode::Bounds),
+            2 => Some(IntervalMode::Midpoint),
+            _ => None,
+        }
+    }
+
+    pub fn level(self) -> u32 {
+        match self {
+            IntervalMode::Off => 0,
+            IntervalMode::Bounds => 1,
+            IntervalMode::Midpoint => 2,
+        }
+    }
+}
+
+/// How values are cut down to `scale` digits; the level is what the `round`
+/// variable holds. `Truncate` is bc's own behaviour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    Truncate,
+    HalfEven,
+    HalfUp,
+    Floor,
+    Ceiling,
+}
+
+impl RoundingMode {
+    pub fn from_level(level: u32) -> Option<Self> {
+        match level {
+            0 => Some(RoundingMode::Truncate),
+            1 => Some(RoundingMode::HalfEven),
+            2 => Some(RoundingMode::HalfUp),
+            3 => Some(RoundingMode::Floor),
+            4 => Some(RoundingMode::Ceiling),
+            _ => None,
+        }
+    }
+
+    pub fn level(self) -> u32 {
+        match self {
+            RoundingMode::Truncate => 0,
+            RoundingMode::HalfEven => 1,
+            RoundingMode::HalfUp => 2,
+            RoundingMode::Floor => 3,
+            RoundingMode::Ceiling => 4,
+        }
+    }
+
+    /// Parses the spelling the `--round` option takes.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truncate" => Some(RoundingMode::Truncate),
+            "half-even" => Some(RoundingMode::HalfEven),
+            "half-up" => Some(RoundingMode::HalfUp),
+            "floor" => Some(RoundingMode::Floor),
+            "ceiling" => Some(RoundingMode::Ceiling),
+            _ => None,
+        }
+    }
+}
+
+/// How decimal results are written out; the level is what the `notation`
+/// variable holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    Plain,
+    Scientific,
+    Engineering,
+    SiPrefix,
+}
+
+impl Notation {
+    pub fn from_level(level: u32) -> Option<Self> {
+        match level {
+            0 => Some(Notation::Plain),
+            1 => Some(Notation::Scientific),
+            2 => Some(Notation::Engineering),
+            3 => Some(Notation::SiPrefix),
+            _ => None,
+        }
+    }
+
+    pub fn level(self) -> u32 {
+        match self {
+            Notation::Plain => 0,
+            Notation::Scientific => 1,
+            Notation::Engineering => 2,
+            Notation::SiPrefix => 3,
+        }
+    }
+
+    /// Parses the spelling the `--notation` option takes.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "plain" => Some(Notation::Plain),
+            "sci" => Some(Notation::Scientific),
+            "eng" => Some(Notation::Engineering),
+            "si" => Some(Notation::SiPrefix),
+            _ => None,
+        }
+    }
+}
+
+/// The unit trig functions read angles in and inverse trig functions give
+/// them back in; the level is what the `angle` variable holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AngleUnit {
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl AngleUnit {
+    pub fn from_level(level: u32) -> Option<Self> {
+        match level {
+            0 => Some(AngleUnit::Radians),
+            1 => Some(AngleUnit::Degrees),
+            2 => Some(AngleUnit::Gradians),
+            _ => None,
+        }
+    }
+
+    pub fn level(self) -> u32 {
+        match self {
+            AngleUnit::Radians => 0,
+            AngleUnit::Degrees => 1,
+            AngleUnit::Gradians => 2,
+        }
+    }
+
+    /// Parses the spelling the `--angle` option takes.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rad" => Some(AngleUnit::Radians),
+            "deg" => Some(AngleUnit::Degrees),
+            "grad" => Some(AngleUnit::Gradians),
+            _ => None,
+        }
+    }
+
+    /// Half a turn counted in this unit, or `None` for radians, where it
+    /// is pi.
+    pub fn half_turn(self) -> Option<u32> {
+        match self {
+            AngleUnit::Radians => None,
+            AngleUnit::Degrees => Some(180),
+            AngleUnit::Gradians => Some(200),
+        }
+    }
+}
+
+/// How results group their digits and which decimal separator input and
+/// output use; the level is what the `locale` variable holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Locale {
+    Plain,
+    English,
+    French,
+    German,
+    Indian,
+}
+
+impl Locale {
+    pub fn from_level(level: u32) -> Option<Self> {
+        match level {
+            0 => Some(Locale::Plain),
+            1 => Some(Locale::English),
+            2 => Some(Locale::French),
+            3 => Some(Locale::German),
+            4 => Some(Locale::Indian),
+            _ => None,
+        }
+    }
+
+    pub fn level(self) -> u32 {
+        match self {
+            Locale::Plain => 0,
+            Locale::English => 1,
+            Locale::French => 2,
+            Locale::German => 3,
+            Locale::Indian => 4,
+        }
+    }
+
+    /// Parses the spelling the `--locale` option takes.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "plain" => Some(Locale::Plain),
+            "en" => Some(Locale::English),
+            "fr" => Some(Locale::French),
+            "de" => Some(Locale::German),
+            "in" => Some(Locale::Indian),
+            _ => None,
+        }
+    }
+
+    pub fn group_separator(self) -> Option<char> {
+        match self {
+            Locale::Plain => None,
+            Locale::English | Locale::Indian => Some(','),
+            Locale::French => Some(' '),
+            Locale::German => Some('.'),
+        }
+    }
+
+    /// Whether `,` is the decimal separator, on input as well as output.
+    pub fn decimal_comma(self) -> bool {
+        matches!(self, Locale::French | Locale::German)
+    }
+}
+
+/// The fixed-width integer programmer mode wraps every result to; `bits` is
+/// what the `word` variable holds and `signed` what the `signed` one does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordType {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl WordType {
+    pub const WIDTHS: [u32; 5] = [8, 16, 32, 64, 128];
+
+    pub fn new(bits: u32, signed: bool) -> Option<Self> {
+        Self::WIDTHS
+            .contains(&bits)
+            .then_some(WordType { bits, signed })
+    }
+
+    /// Parses the spelling the `--word` option takes: `i32`, `u8` and so on.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let signed = match name.get(..1)? {
+            "i" => true,
+            "u" => false,
+            _ => return None,
+        };
+        Self::new(name[1..].parse().ok()?, signed)
+    }
+}
+
 #[derive(Debug)]
 pub struct Runtime {
-    namespaces: Vec<BTreeMap<String, Decimal>>,
+    namespaces: Vec<BTreeMap<String, BcNumber>>,
+    arrays: Vec<BTreeMap<String, Array>>,
     functions: HashMap<String, FunctionDef>,
     scale: u32,
+    ibase: u32,
     obase: u32,
+    posix_scale: bool,
+    rational: RationalMode,
+    interval: IntervalMode,
+    round: RoundingMode,
+    notation: Notation,
+    locale: Locale,
+    angle: AngleUnit,
+    word: u32,
+    signed: bool,
+    multibase: bool,
     rng: SmallRng,
 }
 
 impl Runtime {
     pub fn with_defaults(scale: u32) -> Self {
         let mut namespaces = vec![BTreeMap::new()];
-        namespaces[0].insert("scale".to_string(), Decimal::from(scale));
-        namespaces[0].insert("obase".to_string(), Decimal::from(10));
+        namespaces[0].insert("scale".to_string(), BcNumber::new(Decimal::from(scale), 0));
+        namespaces[0].insert("ibase".to_string(), BcNumber::new(Decimal::from(10), 0));
+        namespaces[0].insert("obase".to_string(), BcNumber::new(Decimal::from(10), 0));
+        namespaces[0].insert("rational".to_string(), BcNumber::new(Decimal::ZERO, 0));
+        namespaces[0].insert("interval".to_string(), BcNumber::new(Decimal::ZERO, 0));
+        namespaces[0].insert("round".to_string(), BcNumber::new(Decimal::ZERO, 0));
+        namespaces[0].insert("notation".to_string(), BcNumber::new(Decimal::ZERO, 0));
+        namespaces[0].insert("locale".to_string(), BcNumber::new(Decimal::ZERO, 0));
+        namespaces[0].insert("angle".to_string(), BcNumber::new(Decimal::ZERO, 0));
+        namespaces[0].insert("word".to_string(), BcNumber::new(Decimal::ZERO, 0));
+        namespaces[0].insert("signed".to_string(), BcNumber::new(Decimal::ONE, 0));
+        namespaces[0].insert("multibase".to_string(), BcNumber::new(Decimal::ZERO, 0));
         Self {
             namespaces,
+            arrays: vec![BTreeMap::new()],
             functions: HashMap::new(),
             scale,
+            ibase: 10,
             obase: 10,
+            posix_scale: false,
+            rational: RationalMode::Off,
+            interval: IntervalMode::Off,
+            round: RoundingMode::Truncate,
+            notation: Notation::Plain,
+            locale: Locale::Plain,
+            angle: AngleUnit::Radians,
+            word: 0,
+            signed: true,
+            multibase: false,
             rng: SmallRng::seed_from_u64(0x5eed_5eed_5eed_5eed),
         }
     }
@@ -57,7 +396,18 @@ impl Runtime {
     pub fn set_scale(&mut self, scale: u32) {
         self.scale = scale;
         if let Some(scope) = self.namespaces.last_mut() {
-            scope.insert("scale".to_string(), Decimal::from(scale));
+            scope.insert("scale".to_string(), BcNumber::new(Decimal::from(scale), 0));
+        }
+    }
+
+    pub fn ibase(&self) -> u32 {
+        self.ibase
+    }
+
+    pub fn set_ibase(&mut self, ibase: u32) {
+        self.ibase = ibase;
+        if let Some(scope) = self.namespaces.last_mut() {
+            scope.insert("ibase".to_string(), BcNumber::new(Decimal::from(ibase), 0));
         }
     }
 
@@ -68,7 +418,123 @@ impl Runtime {
     pub fn set_obase(&mut self, obase: u32) {
         self.obase = obase;
         if let Some(scope) = self.namespaces.last_mut() {
-            scope.insert("obase".to_string(), Decimal::from(obase));
+            scope.insert("obase".to_string(), BcNumber::new(Decimal::from(obase), 0));
+        }
+    }
+
+    /// Whether every intermediate result follows POSIX bc's scale rules.
+    pub fn posix_scale(&self) -> bool {
+        self.posix_scale
+    }
+
+    pub fn set_posix_scale(&mut self, enabled: bool) {
+        self.posix_scale = enabled;
+    }
+
+    pub fn rational(&self) -> RationalMode {
+        self.rational
+    }
+
+    pub fn set_rational(&mut self, mode: RationalMode) {
+        self.rational = mode;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let level = BcNumber::new(Decimal::from(mode.level()), 0);
+            scope.insert("rational".to_string(), level);
+        }
+    }
+
+    pub fn interval(&self) -> IntervalMode {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, mode: IntervalMode) {
+        self.interval = mode;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let level = BcNumber::new(Decimal::from(mode.level()), 0);
+            scope.insert("interval".to_string(), level);
+        }
+    }
+
+    pub fn round(&self) -> RoundingMode {
+        self.round
+    }
+
+    pub fn set_round(&mut self, mode: RoundingMode) {
+        self.round = mode;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let level = BcNumber::new(Decimal::from(mode.level()), 0);
+            scope.insert("round".to_string(), level);
+        }
+    }
+
+    pub fn notation(&self) -> Notation {
+        self.notation
+    }
+
+    pub fn set_notation(&mut self, notation: Notation) {
+        self.notation = notation;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let level = BcNumber::new(Decimal::from(notation.level()), 0);
+            scope.insert("notation".to_string(), level);
+        }
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let level = BcNumber::new(Decimal::from(locale.level()), 0);
+            scope.insert("locale".to_string(), level);
+        }
+    }
+
+    pub fn angle(&self) -> AngleUnit {
+        self.angle
+    }
+
+    pub fn set_angle(&mut self, angle: AngleUnit) {
+        self.angle = angle;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let level = BcNumber::new(Decimal::from(angle.level()), 0);
+            scope.insert("angle".to_string(), level);
+        }
+    }
+
+    /// The word programmer mode wraps to, or `None` while `word` is 0.
+    pub fn word(&self) -> Option<WordType> {
+        WordType::new(self.word, self.signed)
+    }
+
+    /// Sets the word width in bits; 0 leaves programmer mode.
+    pub fn set_word(&mut self, bits: u32) {
+        self.word = bits;
+        if let Some(scope) = self.namespaces.last_mut() {
+            scope.insert("word".to_string(), BcNumber::new(Decimal::from(bits), 0));
+        }
+    }
+
+    pub fn set_signed(&mut self, signed: bool) {
+        self.signed = signed;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let flag = BcNumber::new(Decimal::from(u32::from(signed)), 0);
+            scope.insert("signed".to_string(), flag);
+        }
+    }
+
+    /// Whether integer results are previewed in decimal, hex, octal and
+    /// binary at once.
+    pub fn multibase(&self) -> bool {
+        self.multibase
+    }
+
+    pub fn set_multibase(&mut self, enabled: bool) {
+        self.multibase = enabled;
+        if let Some(scope) = self.namespaces.last_mut() {
+            let flag = BcNumber::new(Decimal::from(u32::from(enabled)), 0);
+            scope.insert("multibase".to_string(), flag);
         }
     }
 
@@ -76,34 +542,79 @@ impl Runtime {
         &mut self.rng
     }
 
-    pub fn push_scope(&mut self, scope: BTreeMap<String, Decimal>) {
+    pub fn push_scope(
+        &mut self,
+        scope: BTreeMap<String, BcNumber>,
+        arrays: BTreeMap<String, Array>,
+    ) {
         self.namespaces.push(scope);
+        self.arrays.push(arrays);
     }
 
     pub fn pop_scope(&mut self) {
         if self.namespaces.len() > 1 {
             self.namespaces.pop();
+            self.arrays.pop();
         }
     }
 
-    pub fn current_scope_mut(&mut self) -> Option<&mut BTreeMap<String, Decimal>> {
+    /// Whether a function body is running, so `auto` has a scope to fill.
+    pub fn in_function(&self) -> bool {
+        self.namespaces.len() > 1
+    }
+
+    pub fn current_scope_mut(&mut self) -> Option<&mut BTreeMap<String, BcNumber>> {
         self.namespaces.last_mut()
     }
 
-    pub fn find_scope_mut(&mut self, name: &str) -> Option<&mut BTreeMap<String, Decimal>> {
+    pub fn find_scope_mut(&mut self, name: &str) -> Option<&mut BTreeMap<String, BcNumber>> {
         self.namespaces
             .iter_mut()
             .rev()
             .find(|scope| scope.contains_key(name))
     }
 
-    pub fn get_variable(&self, name: &str) -> Option<Decimal> {
+    pub fn get_variable(&self, name: &str) -> Option<BcNumber> {
         self.namespaces
             .iter()
             .rev()
             .find_map(|scope| scope.get(name).cloned())
     }
 
+    /// Whether the variable `name`, or any element of the array `name`,
+    /// passes `test`.
+    pub fn holds(&self, name: &str, test: impl Fn(&BcNumber) -> bool) -> bool {
+        self.get_variable(name).is_some_and(|number| test(&number))
+            || self
+                .get_array(name)
+                .is_some_and(|array| array.borrow().values().any(&test))
+    }
+
+    pub fn get_array(&self, name: &str) -> Option<Array> {
+        self.arrays
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    }
+
+    /// The array `name`, created empty in the innermost scope the way an
+    /// assignment creates a variable when no scope has it yet.
+    pub fn array(&mut self, name: &str) -> Array {
+        if let Some(array) = self.get_array(name) {
+            return array;
+        }
+        let array = Array::default();
+        self.declare_array(name, array.clone());
+        array
+    }
+
+    /// Binds `name` to `array` in the innermost scope, hiding any outer one.
+    pub fn declare_array(&mut self, name: &str, array: Array) {
+        if let Some(scope) = self.arrays.last_mut() {
+            scope.insert(name.to_string(), array);
+        }
+    }
+
     pub fn define_function(&mut self, name: String, def: FunctionDef) {
         self.functions.insert(name, def);
     }
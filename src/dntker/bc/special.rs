@@ -0,0 +1,546 @@
+use dashu::base::{Abs, Sign};
+use dashu::integer::{IBig, UBig};
+use dashu::rational::RBig;
+use dashu::Decimal;
+use num_traits::{ToPrimitive, Zero};
+
+use super::error::BcError;
+use super::mathlib::{self, at_precision, domain_error, epsilon, WORK_DIGITS};
+use super::number::number;
+use super::rational::rational_at_precision;
+
+/// Positive integers up to this bound get `gamma(n) = (n - 1)!` exactly.
+const EXACT_GAMMA_LIMIT: i64 = 1000;
+
+/// Iteration cap for the series and continued fractions of the incomplete
+/// gamma and beta functions.
+const MAX_ITERATIONS: usize = 100_000;
+
+/// Largest |n| the Bessel functions take as their order.
+const MAX_BESSEL_ORDER: i64 = 10_000;
+
+/// Largest |x| the Bessel functions take: their series cancel terms as
+/// large as e^|x|, so they carry about 0.43|x| more digits.
+const MAX_BESSEL_ARGUMENT: i64 = 1_000;
+
+pub(super) fn gamma(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if is_pole(x) {
+        return Err(domain_error("gamma"));
+    }
+    if is_integer(x) {
+        if let Some(n) = x.to_i64().filter(|n| *n <= EXACT_GAMMA_LIMIT) {
+            let factorial = (1..n).fold(IBig::ONE, |acc, k| acc * IBig::from(k));
+            return Ok(Decimal::from(factorial));
+        }
+    }
+    let work = precision + WORK_DIGITS;
+    if x.sign() == Sign::Negative {
+        // Reflection: gamma(x) = pi / (sin(pi x) gamma(1 - x)).
+        let x = at_precision(x, work);
+        let pi = mathlib::pi(work);
+        let sine = mathlib::sin(&(pi.clone() * &x), work)?;
+        let reflected = gamma(&(number(1, work) - x), work)?;
+        return Ok(pi / (sine * at_precision(&reflected, work)));
+    }
+    mathlib::exp(&ln_gamma_positive(x, work), work)
+}
+
+/// ln|gamma(x)|, which stays representable long after `gamma` itself has
+/// grown past any sensible scale.
+pub(super) fn lgamma(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if is_pole(x) {
+        return Err(domain_error("lgamma"));
+    }
+    let work = precision + WORK_DIGITS;
+    if x.sign() == Sign::Negative {
+        let x = at_precision(x, work);
+        let pi = mathlib::pi(work);
+        let sine = mathlib::sin(&(pi.clone() * &x), work)?.abs();
+        let reflected = ln_gamma_positive(&(number(1, work) - x), work);
+        return Ok(mathlib::ln(&pi, work)? - mathlib::ln(&sine, work)? - reflected);
+    }
+    Ok(ln_gamma_positive(x, work))
+}
+
+pub(super) fn beta(a: &Decimal, b: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if is_pole(a) || is_pole(b) {
+        return Err(domain_error("beta"));
+    }
+    let sum = a.clone() + b;
+    if is_pole(&sum) {
+        return Ok(Decimal::ZERO);
+    }
+    let work = precision + WORK_DIGITS;
+    if a.sign() == Sign::Positive && b.sign() == Sign::Positive {
+        return mathlib::exp(&ln_beta(a, b, work), work);
+    }
+    let numerator = at_precision(&gamma(a, work)?, work) * gamma(b, work)?;
+    Ok(numerator / at_precision(&gamma(&sum, work)?, work))
+}
+
+/// The error function, from the series
+/// `erf(x) = 2/sqrt(pi) e^(-x^2) sum (2x^2)^n x / (1*3*...*(2n+1))`, whose
+/// terms are all positive.
+pub(super) fn erf(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if x.is_zero() {
+        return Ok(Decimal::ZERO);
+    }
+    let work = precision + WORK_DIGITS;
+    let magnitude = at_precision(&x.clone().abs(), work);
+    let square = magnitude.clone() * &magnitude;
+    // Past this point 1 - erf(|x|) < e^(-x^2) is below the working precision.
+    let cutoff = Decimal::from(work as i64 * 23 / 10 + 1);
+    let value = if square > cutoff {
+        number(1, work)
+    } else {
+        let eps = epsilon(work);
+        let ratio = square.clone() * Decimal::from(2);
+        let mut term = magnitude;
+        let mut sum = term.clone();
+        let mut n = 0i64;
+        loop {
+            n += 1;
+            term = term * &ratio / Decimal::from(2 * n + 1);
+            sum += &term;
+            if term < sum.clone() * &eps {
+                break;
+            }
+        }
+        let root_pi = mathlib::sqrt(&mathlib::pi(work), work)?;
+        Decimal::from(2) * sum * mathlib::exp(&-square, work)? / root_pi
+    };
+    Ok(if x.sign() == Sign::Negative {
+        -value
+    } else {
+        value
+    })
+}
+
+pub(super) fn erfc(x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let work = precision + WORK_DIGITS;
+    Ok(number(1, work) - erf(x, work)?)
+}
+
+/// Riemann zeta by Borwein's alternating-series acceleration, with the
+/// functional equation below `s = 1/2`.
+pub(super) fn zeta(s: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if *s == Decimal::ONE {
+        return Err(domain_error("zeta"));
+    }
+    let work = precision + WORK_DIGITS;
+    if s.is_zero() {
+        return Ok(number(-1, work) / Decimal::from(2));
+    }
+    let s = at_precision(s, work);
+    if s < number(1, work) / Decimal::from(2) {
+        if is_integer(&s) && (s.clone() / Decimal::from(2)).trunc() * Decimal::from(2) == s {
+            return Ok(Decimal::ZERO);
+        }
+        // zeta(s) = 2^s pi^(s-1) sin(pi s / 2) gamma(1 - s) zeta(1 - s)
+        let pi = mathlib::pi(work);
+        let reflected = number(1, work) - &s;
+        let sine = mathlib::sin(&(pi.clone() * &s / Decimal::from(2)), work)?;
+        let factor = power(&number(2, work), &s, work)?
+            * power(&pi, &(s.clone() - Decimal::ONE), work)?
+            * sine;
+        return Ok(factor * gamma(&reflected, work)? * zeta(&reflected, work)?);
+    }
+    // Past this point zeta(s) - 1 < 2^(1-s) is below the working precision.
+    if s > Decimal::from(work as i64 * 10 / 3 + 2) {
+        return Ok(number(1, work));
+    }
+
+    // (3 + sqrt 8)^n has to outgrow 10^work.
+    let n = work * 4 / 3 + 2;
+    let mut weights = Vec::with_capacity(n + 1);
+    let mut term = RBig::ONE;
+    let mut total = RBig::ONE;
+    weights.push(total.clone());
+    for i in 1..=n {
+        let grow = IBig::from(4 * (n + i - 1) * (n - i + 1));
+        let shrink = UBig::from((2 * i - 1) * (2 * i));
+        term *= RBig::from_parts(grow, shrink);
+        total += &term;
+        weights.push(total.clone());
+    }
+    let last = &weights[n];
+    let mut sum = number(0, work);
+    for (k, weight) in weights[..n].iter().enumerate() {
+        let numerator = rational_at_precision(&(weight - last), work);
+        let base = number(k as i64 + 1, work);
+        let term = numerator / power(&base, &s, work)?;
+        if k % 2 == 0 {
+            sum += term;
+        } else {
+            sum -= term;
+        }
+    }
+    let halving = number(1, work) - power(&number(2, work), &(Decimal::ONE - &s), work)?;
+    Ok(-sum / (rational_at_precision(last, work) * halving))
+}
+
+/// Regularised lower incomplete gamma `P(s, x)`.
+pub(super) fn gammainc(s: &Decimal, x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    Ok(incomplete_gamma("gammainc", s, x, precision)?.0)
+}
+
+/// Regularised upper incomplete gamma `Q(s, x) = 1 - P(s, x)`.
+pub(super) fn gammaincc(s: &Decimal, x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    Ok(incomplete_gamma("gammaincc", s, x, precision)?.1)
+}
+
+/// Regularised incomplete beta `I_x(a, b)`.
+pub(super) fn betainc(
+    a: &Decimal,
+    b: &Decimal,
+    x: &Decimal,
+    precision: usize,
+) -> Result<Decimal, BcError> {
+    if a.sign() == Sign::Negative || a.is_zero() || b.sign() == Sign::Negative || b.is_zero() {
+        return Err(domain_error("betainc"));
+    }
+    if x.sign() == Sign::Negative || *x > Decimal::ONE {
+        return Err(domain_error("betainc"));
+    }
+    if x.is_zero() || *x == Decimal::ONE {
+        return Ok(x.clone());
+    }
+    let work = precision + WORK_DIGITS;
+    let (a, b, x) = (
+        at_precision(a, work),
+        at_precision(b, work),
+        at_precision(x, work),
+    );
+    let one = number(1, work);
+    let complement = one.clone() - &x;
+    let log_front = a.clone() * mathlib::ln(&x, work)?
+        + b.clone() * mathlib::ln(&complement, work)?
+        - ln_beta(&a, &b, work);
+    let front = mathlib::exp(&log_front, work)?;
+    // The continued fraction converges fast below (a + 1) / (a + b + 2);
+    // above it, I_x(a, b) = 1 - I_(1-x)(b, a).
+    let pivot = (a.clone() + Decimal::ONE) / (a.clone() + &b + Decimal::from(2));
+    if x < pivot {
+        Ok(front * beta_fraction(&a, &b, &x, work)? / a)
+    } else {
+        Ok(one - front * beta_fraction(&b, &a, &complement, work)? / b)
+    }
+}
+
+/// Bessel function of the first kind of integer order, from the series
+/// `J_n(x) = sum (-1)^k (x/2)^(2k+n) / (k! (n+k)!)` as GNU bc's `j` sums it.
+pub(super) fn bessel_j(n: &Decimal, x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let order = bessel_order(n)?;
+    let work = bessel_precision(x, precision)?;
+    let half = at_precision(x, work) / Decimal::from(2);
+    let value = bessel_terms(order.unsigned_abs() as usize, &half, work)
+        .iter()
+        .fold(Decimal::ZERO, |sum, term| sum + term);
+    // J_-n(x) = (-1)^n J_n(x)
+    Ok(if order < 0 && order % 2 != 0 {
+        -value
+    } else {
+        value
+    })
+}
+
+/// Bessel function of the second kind of integer order, for x > 0, from
+/// `pi Y_n(x) = 2 (ln(x/2) + gamma) J_n(x) - sum_(k<n) (n-k-1)!/k! (x/2)^(2k-n)
+/// - sum_k (H_k + H_(n+k)) t_k`, where `t_k` are the terms of the series
+/// for `J_n(x)`, `gamma` is Euler's constant and `H_k` the harmonic numbers.
+pub(super) fn bessel_y(n: &Decimal, x: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    let order = bessel_order(n)?;
+    if x.is_zero() || x.sign() == Sign::Negative {
+        return Err(domain_error("y"));
+    }
+    let work = bessel_precision(x, precision)?;
+    let size = order.unsigned_abs() as usize;
+    let half = at_precision(x, work) / Decimal::from(2);
+
+    let mut finite = Decimal::ZERO;
+    if size > 0 {
+        let factorial = (1..size).fold(IBig::ONE, |acc, k| acc * IBig::from(k));
+        let square = half.clone() * &half;
+        let mut term = at_precision(&Decimal::from(factorial), work) / half.powi(size.into());
+        for k in 0..size {
+            finite += &term;
+            if k + 1 < size {
+                term = term * &square / Decimal::from((k + 1) * (size - k - 1));
+            }
+        }
+    }
+
+    let one = number(1, work);
+    let mut low = Decimal::ZERO;
+    let mut high = (1..=size).fold(Decimal::ZERO, |sum, k| sum + one.clone() / Decimal::from(k));
+    let mut first_kind = Decimal::ZERO;
+    let mut weighted = Decimal::ZERO;
+    for (k, term) in bessel_terms(size, &half, work).iter().enumerate() {
+        if k > 0 {
+            low += one.clone() / Decimal::from(k);
+            high += one.clone() / Decimal::from(size + k);
+        }
+        first_kind += term;
+        weighted += (low.clone() + &high) * term;
+    }
+
+    let log = mathlib::ln(&half, work)? + euler_gamma(work)?;
+    let value = (Decimal::from(2) * log * first_kind - finite - weighted) / mathlib::pi(work);
+    // Y_-n(x) = (-1)^n Y_n(x)
+    Ok(if order < 0 && order % 2 != 0 {
+        -value
+    } else {
+        value
+    })
+}
+
+fn bessel_order(n: &Decimal) -> Result<i64, BcError> {
+    if !is_integer(n) {
+        return Err(BcError::Error(
+            "Bessel function order must be an integer".to_string(),
+        ));
+    }
+    n.to_i64()
+        .filter(|order| order.abs() <= MAX_BESSEL_ORDER)
+        .ok_or_else(|| BcError::Error("Bessel order out of range".to_string()))
+}
+
+/// The digits a Bessel series for `x` is summed to.
+fn bessel_precision(x: &Decimal, precision: usize) -> Result<usize, BcError> {
+    let magnitude = x
+        .clone()
+        .abs()
+        .trunc()
+        .to_i64()
+        .filter(|magnitude| *magnitude <= MAX_BESSEL_ARGUMENT)
+        .ok_or_else(|| BcError::Error("Bessel argument out of range".to_string()))?;
+    Ok(precision + WORK_DIGITS + (magnitude as usize + 1) * 10 / 23 + 1)
+}
+
+/// The terms `(-1)^k (x/2)^(2k+n) / (k! (n+k)!)` of the series for `J_n(x)`,
+/// given `half = x/2`, up to where they no longer matter.
+fn bessel_terms(n: usize, half: &Decimal, work: usize) -> Vec<Decimal> {
+    let eps = epsilon(work);
+    let step = -(half.clone() * half);
+    let factorial = (1..=n).fold(IBig::ONE, |acc, k| acc * IBig::from(k));
+    let mut term = at_precision(half, work).powi(n.into()) / Decimal::from(factorial);
+    let mut terms = vec![term.clone()];
+    let mut k = 0;
+    loop {
+        k += 1;
+        term = term * &step / Decimal::from(k * (n + k));
+        // The terms only shrink for good once k is past |x/2|.
+        let done = term.clone().abs() < eps && Decimal::from(k) > half.clone().abs();
+        terms.push(term.clone());
+        if done {
+            return terms;
+        }
+    }
+}
+
+/// Euler's constant by Brent and McMillan: `U/V` with
+/// `U = sum (N^k/k!)^2 (H_k - ln N)` and `V = sum (N^k/k!)^2` is within
+/// e^(-4N) of it.
+fn euler_gamma(precision: usize) -> Result<Decimal, BcError> {
+    let n = precision as i64 * 10 / 17 + 1;
+    // The terms peak near e^(2N), which costs about N more digits.
+    let work = precision + n as usize + WORK_DIGITS;
+    let eps = epsilon(work);
+    let square = Decimal::from(n * n);
+    let mut a = -mathlib::ln(&number(n, work), work)?;
+    let mut b = number(1, work);
+    let mut u = a.clone();
+    let mut v = b.clone();
+    let mut k = 0;
+    loop {
+        k += 1;
+        let index = Decimal::from(k);
+        b = b * &square / Decimal::from(k * k);
+        a = (a * &square / &index + &b) / &index;
+        u += &a;
+        v += &b;
+        if k > n && a.clone().abs() + &b < v.clone() * &eps {
+            return Ok(u / v);
+        }
+    }
+}
+
+fn is_integer(x: &Decimal) -> bool {
+    x.trunc() == *x
+}
+
+/// Zero and the negative integers, where gamma has its poles.
+fn is_pole(x: &Decimal) -> bool {
+    is_integer(x) && (x.is_zero() || x.sign() == Sign::Negative)
+}
+
+/// `base^exponent`, exactly repeated multiplication for integer exponents.
+fn power(base: &Decimal, exponent: &Decimal, precision: usize) -> Result<Decimal, BcError> {
+    if is_integer(exponent) {
+        if let Some(n) = exponent.to_i64() {
+            return Ok(at_precision(base, precision).powi(IBig::from(n)));
+        }
+    }
+    mathlib::powf(base, exponent, precision)
+}
+
+/// ln gamma(x) for x > 0: the recurrence lifts x past `precision`, where
+/// Stirling's series converges to well beyond it.
+fn ln_gamma_positive(x: &Decimal, precision: usize) -> Decimal {
+    let work = precision + WORK_DIGITS;
+    let threshold = Decimal::from(work as i64);
+    let mut z = at_precision(x, work);
+    let mut shift = number(1, work);
+    while z < threshold {
+        shift *= &z;
+        z += Decimal::ONE;
+    }
+
+    let pi = mathlib::pi(work);
+    let ln = |value: &Decimal| at_precision(value, work).ln();
+    let half = number(1, work) / Decimal::from(2);
+    let mut sum = (z.clone() - &half) * ln(&z) - &z + ln(&(pi * Decimal::from(2))) * half;
+    let eps = epsilon(work);
+    let square = z.clone() * &z;
+    let mut power = z;
+    for (index, bernoulli) in even_bernoulli(work / 2 + 2).iter().enumerate() {
+        let k = 2 * index as i64 + 2;
+        let term =
+            rational_at_precision(bernoulli, work) / (power.clone() * Decimal::from(k * (k - 1)));
+        if term.clone().abs() < eps {
+            break;
+        }
+        sum += term;
+        power *= &square;
+    }
+    sum - ln(&shift)
+}
+
+fn ln_beta(a: &Decimal, b: &Decimal, precision: usize) -> Decimal {
+    ln_gamma_positive(a, precision) + ln_gamma_positive(b, precision)
+        - ln_gamma_positive(&(a.clone() + b), precision)
+}
+
+/// B_2, B_4, ..., B_2count as exact fractions, by the Akiyama–Tanigawa
+/// algorithm.
+fn even_bernoulli(count: usize) -> Vec<RBig> {
+    let top = 2 * count;
+    let mut row: Vec<RBig> = Vec::with_capacity(top + 1);
+    let mut numbers = Vec::with_capacity(count);
+    for m in 0..=top {
+        row.push(RBig::from_parts(IBig::ONE, UBig::from(m + 1)));
+        for j in (1..=m).rev() {
+            row[j - 1] = RBig::from(UBig::from(j)) * (&row[j - 1] - &row[j]);
+        }
+        if m >= 2 && m % 2 == 0 {
+            numbers.push(row[0].clone());
+        }
+    }
+    numbers
+}
+
+/// `(P(s, x), Q(s, x))`: the power series below `x = s + 1`, Legendre's
+/// continued fraction above it.
+fn incomplete_gamma(
+    name: &str,
+    s: &Decimal,
+    x: &Decimal,
+    precision: usize,
+) -> Result<(Decimal, Decimal), BcError> {
+    if s.sign() == Sign::Negative || s.is_zero() || x.sign() == Sign::Negative {
+        return Err(domain_error(name));
+    }
+    if x.is_zero() {
+        return Ok((Decimal::ZERO, Decimal::ONE));
+    }
+    let work = precision + WORK_DIGITS;
+    let (s, x) = (at_precision(s, work), at_precision(x, work));
+    let one = number(1, work);
+    let eps = epsilon(work);
+    let log_front = s.clone() * mathlib::ln(&x, work)? - &x - ln_gamma_positive(&s, work);
+    let front = mathlib::exp(&log_front, work)?;
+
+    if x < s.clone() + Decimal::ONE {
+        let mut denominator = s.clone();
+        let mut term = one.clone() / &s;
+        let mut sum = term.clone();
+        for _ in 0..MAX_ITERATIONS {
+            denominator += Decimal::ONE;
+            term = term * &x / &denominator;
+            sum += &term;
+            if term < sum.clone() * &eps {
+                let lower = front * sum;
+                return Ok((lower.clone(), one - lower));
+            }
+        }
+        return Err(convergence_error(name));
+    }
+
+    let tiny = epsilon(2 * work);
+    let mut b = x.clone() + Decimal::ONE - &s;
+    let mut c = one.clone() / &tiny;
+    let mut d = one.clone() / &b;
+    let mut h = d.clone();
+    for i in 1..=MAX_ITERATIONS as i64 {
+        let an = -(Decimal::from(i) * (Decimal::from(i) - &s));
+        b += Decimal::from(2);
+        d = lentz_guard(an.clone() * d + &b, &tiny);
+        c = lentz_guard(b.clone() + an / c, &tiny);
+        d = one.clone() / d;
+        let delta = d.clone() * &c;
+        h *= &delta;
+        if (delta - &one).abs() < eps {
+            let upper = front * h;
+            return Ok((one - &upper, upper));
+        }
+    }
+    Err(convergence_error(name))
+}
+
+/// The continued fraction for the incomplete beta function, by the
+/// modified Lentz method.
+fn beta_fraction(a: &Decimal, b: &Decimal, x: &Decimal, work: usize) -> Result<Decimal, BcError> {
+    let one = number(1, work);
+    let eps = epsilon(work);
+    let tiny = epsilon(2 * work);
+    let sum = a.clone() + b;
+    let mut c = one.clone();
+    let mut d = one.clone()
+        / lentz_guard(
+            one.clone() - sum.clone() * x / (a.clone() + Decimal::ONE),
+            &tiny,
+        );
+    let mut h = d.clone();
+    for m in 1..=MAX_ITERATIONS as i64 {
+        let m = Decimal::from(m);
+        let twice = m.clone() * Decimal::from(2);
+        let even = m.clone() * (b.clone() - &m) * x
+            / ((a.clone() + &twice - Decimal::ONE) * (a.clone() + &twice));
+        d = one.clone() / lentz_guard(one.clone() + even.clone() * d, &tiny);
+        c = lentz_guard(one.clone() + even / c, &tiny);
+        h *= d.clone() * &c;
+        let odd = -((a.clone() + &m) * (sum.clone() + &m) * x)
+            / ((a.clone() + &twice) * (a.clone() + &twice + Decimal::ONE));
+        d = one.clone() / lentz_guard(one.clone() + odd.clone() * d, &tiny);
+        c = lentz_guard(one.clone() + odd / c, &tiny);
+        let delta = d.clone() * &c;
+        h *= &delta;
+        if (delta - &one).abs() < eps {
+            return Ok(h);
+        }
+    }
+    Err(convergence_error("betainc"))
+}
+
+/// Keeps a Lentz denominator away from zero.
+fn lentz_guard(value: Decimal, tiny: &Decimal) -> Decimal {
+    if value.clone().abs() < *tiny {
+        tiny.clone()
+    } else {
+        value
+    }
+}
+
+fn convergence_error(name: &str) -> BcError {
+    BcError::Error(format!("{name}() did not converge"))
+}
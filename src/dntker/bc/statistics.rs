@@ -0,0 +1,168 @@
+use std::convert::TryFrom;
+
+use dashu::integer::IBig;
+use dashu::rational::RBig;
+use dashu::Decimal;
+
+use super::error::BcError;
+use super::mathlib::{self, WORK_DIGITS};
+use super::rational::rational_at_precision;
+
+/// The aggregate builtins `call_builtin_function` hands to `builtin_statistic`.
+pub(super) const STATISTICS: [&str; 12] = [
+    "sum",
+    "mean",
+    "median",
+    "mode",
+    "var",
+    "pvar",
+    "stddev",
+    "pstddev",
+    "percentile",
+    "geomean",
+    "harmean",
+    "range",
+];
+
+impl super::BcExecuter {
+    /// Sums, means, spreads and percentiles over every argument. Everything
+    /// but the square roots and logarithms behind `stddev` and `geomean` is
+    /// computed on exact fractions before it is written out as a decimal.
+    pub(super) fn builtin_statistic(
+        &self,
+        name: &str,
+        args: &[Decimal],
+    ) -> Result<Decimal, BcError> {
+        let (percent, args) = match (name, args) {
+            ("percentile", [percent, rest @ ..]) => (Some(percent), rest),
+            ("percentile", []) => {
+                return Err(BcError::Error(
+                    "percentile() expects a percentage and at least 1 value".to_string(),
+                ))
+            }
+            _ => (None, args),
+        };
+        let minimum = if matches!(name, "var" | "stddev") {
+            2
+        } else {
+            1
+        };
+        if args.len() < minimum {
+            return Err(BcError::Error(format!(
+                "{name}() expects at least {minimum} value{}",
+                if minimum == 1 { "" } else { "s" }
+            )));
+        }
+        let mut values: Vec<RBig> = args.iter().map(Self::decimal_to_rational).collect();
+        values.sort();
+        let count = RBig::from(IBig::from(values.len()));
+        let sum = total(values.iter().cloned());
+
+        let result = match name {
+            "sum" => sum,
+            "mean" => sum / count,
+            "median" => median(&values),
+            "mode" => mode(&values),
+            "range" => values[values.len() - 1].clone() - &values[0],
+            "var" | "pvar" | "stddev" | "pstddev" => {
+                let mean = sum / &count;
+                let squares = total(values.iter().map(|value| {
+                    let deviation = value.clone() - &mean;
+                    deviation.clone() * deviation
+                }));
+                let divisor = if name.starts_with('p') {
+                    count
+                } else {
+                    count - RBig::ONE
+                };
+                let variance = squares / divisor;
+                if name.ends_with("var") {
+                    variance
+                } else {
+                    return self.eval_precise(&[], |precision| {
+                        mathlib::sqrt(&rational_at_precision(&variance, precision), precision)
+                    });
+                }
+            }
+            "percentile" => {
+                let percent = percent.map(Self::decimal_to_rational).unwrap_or_default();
+                if percent < RBig::ZERO || percent > RBig::from(100) {
+                    return Err(BcError::Error(
+                        "percentile() needs a percentage between 0 and 100".to_string(),
+                    ));
+                }
+                percentile(&values, &percent)
+            }
+            "harmean" => {
+                if values[0] <= RBig::ZERO {
+                    return Err(BcError::Error(
+                        "harmean() needs positive values".to_string(),
+                    ));
+                }
+                let reciprocals = total(values.iter().map(|value| RBig::ONE / value));
+                count / reciprocals
+            }
+            _ => {
+                if values[0] <= RBig::ZERO {
+                    return Err(BcError::Error(
+                        "geomean() needs positive values".to_string(),
+                    ));
+                }
+                let inputs: Vec<&Decimal> = args.iter().collect();
+                return self.eval_precise(&inputs, |precision| {
+                    let work = precision + WORK_DIGITS;
+                    let mut logs = Decimal::ZERO;
+                    for value in args {
+                        logs += mathlib::ln(&mathlib::at_precision(value, work), work)?;
+                    }
+                    mathlib::exp(&(logs / Decimal::from(args.len())), work)
+                });
+            }
+        };
+        Ok(self.rational_to_decimal(&result))
+    }
+}
+
+fn total(values: impl Iterator<Item = RBig>) -> RBig {
+    values.fold(RBig::ZERO, |sum, value| sum + value)
+}
+
+/// The middle of the sorted `values`, or the mean of the middle two.
+fn median(values: &[RBig]) -> RBig {
+    let middle = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[middle].clone()
+    } else {
+        (values[middle - 1].clone() + &values[middle]) / RBig::from(2)
+    }
+}
+
+/// The most common of the sorted `values`, the smallest one on a tie.
+fn mode(values: &[RBig]) -> RBig {
+    let mut best = (&values[0], 0);
+    let mut run = (&values[0], 0);
+    for value in values {
+        if *value == *run.0 {
+            run.1 += 1;
+        } else {
+            run = (value, 1);
+        }
+        if run.1 > best.1 {
+            best = run;
+        }
+    }
+    best.0.clone()
+}
+
+/// The `percent`th percentile of the sorted `values`, interpolating linearly
+/// between the closest ranks as spreadsheets' `PERCENTILE.INC` does.
+fn percentile(values: &[RBig], percent: &RBig) -> RBig {
+    let rank = percent.clone() * RBig::from(IBig::from(values.len() - 1)) / RBig::from(100);
+    let below = rank.floor();
+    let fraction = rank - RBig::from(below.clone());
+    let index = usize::try_from(&below).unwrap_or(0);
+    match values.get(index + 1) {
+        Some(above) => values[index].clone() + fraction * (above.clone() - &values[index]),
+        None => values[index].clone(),
+    }
+}
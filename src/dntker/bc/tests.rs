@@ -48,7 +48,7 @@ mod bc_tests {
         let input2 = "0.12/atan(123)";
         let result2 = b.exec(input2).unwrap();
         // Check that the result is approximately correct
-        let expected_approx = 0.07679182076851013335;
+        let expected_approx = 0.076_791_820_768_510_13;
         let actual: f64 = result2.parse().unwrap();
         assert!(
             (actual - expected_approx).abs() < 1e-10,
@@ -159,10 +159,10 @@ mod bc_tests {
         let mut b: BcExecuter = Default::default();
         assert_eq!(b.exec("length(123456)").unwrap(), "6");
         assert_eq!(b.exec("scale(12.345)").unwrap(), "3");
-        let bessel = b.exec("j(1, 2)");
-        assert!(bessel.is_ok());
-        let bessel_val: f64 = bessel.unwrap().parse().unwrap();
-        assert!((bessel_val - 0.5767248077568734).abs() < 1e-9);
+        assert_eq!(b.exec("j(1, 2)").unwrap(), ".57672480775687338720");
+        assert_eq!(b.exec("j(-3, -7)").unwrap(), "-.16755558799533423603");
+        assert_eq!(b.exec("j(0, 100)").unwrap(), ".01998585030422312242");
+        assert!(b.exec("j(1.5, 2)").is_err());
 
         assert_eq!(b.exec("sqrt(9)").unwrap(), "3");
         assert_eq!(b.exec("abs(-5)").unwrap(), "5");
@@ -196,7 +196,1027 @@ mod bc_tests {
         let expected = (rng.next_u32() & 0x7fff) as f64;
         assert_eq!(rand_value, expected);
         let bounded: f64 = b.exec("rand(5)").unwrap().parse().unwrap();
-        assert!(bounded >= 0.0 && bounded < 5.0);
+        assert!((0.0..5.0).contains(&bounded));
+    }
+
+    #[test]
+    fn test_math_library_honours_scale() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(
+            exec.exec("scale=50; sqrt(2)").unwrap(),
+            "1.41421356237309504880168872420969807856967187537694"
+        );
+        assert_eq!(
+            exec.exec("scale=40; 4*a(1)").unwrap(),
+            "3.1415926535897932384626433832795028841971"
+        );
+        assert_eq!(
+            exec.exec("scale=50; e(1)").unwrap(),
+            "2.71828182845904523536028747135266249775724709369995"
+        );
+        assert_eq!(
+            exec.exec("scale=50; l(2)").unwrap(),
+            ".69314718055994530941723212145817656807550013436025"
+        );
+        assert_eq!(
+            exec.exec("scale=30; s(1)").unwrap(),
+            ".841470984807896506652502321630"
+        );
+        assert_eq!(
+            exec.exec("scale=30; 2^0.5").unwrap(),
+            "1.414213562373095048801688724209"
+        );
+        assert_eq!(
+            exec.exec("scale=30; log(10, 2)").unwrap(),
+            ".301029995663981195213738894724"
+        );
+        assert_eq!(
+            exec.exec("scale=20; s(10^30)").unwrap(),
+            "-.09011690191213805803"
+        );
+        assert_eq!(
+            exec.exec("scale=20; acos(-1)").unwrap(),
+            "3.14159265358979323846"
+        );
+        assert_eq!(exec.exec("cbrt(-8)").unwrap(), "-2");
+        assert!(exec.exec("sqrt(-1)").is_err());
+        assert!(exec.exec("ln(0)").is_err());
+    }
+
+    #[test]
+    fn test_posix_scale_matches_bc_outputs() {
+        let mut exec: BcExecuter = Default::default();
+        exec.runtime.set_posix_scale(true);
+        assert_eq!(exec.exec("5%3").unwrap(), ".00000000000000000002");
+        assert_eq!(exec.exec("10/3").unwrap(), "3.33333333333333333333");
+        assert_eq!(exec.exec("1.50*2").unwrap(), "3.00");
+        assert_eq!(exec.exec("3.000-1").unwrap(), "2.000");
+        assert_eq!(exec.exec("2^-2").unwrap(), ".25000000000000000000");
+        assert_eq!(exec.exec("-2^2").unwrap(), "4");
+        assert_eq!(exec.exec("scale(1.50)").unwrap(), "2");
+        assert_eq!(exec.exec("length(1.50)").unwrap(), "3");
+        assert_eq!(exec.exec("length(.050)").unwrap(), "2");
+        assert_eq!(exec.exec("length(0)").unwrap(), "1");
+        assert_eq!(exec.exec("0.50-0.5").unwrap(), "0");
+
+        exec.exec("scale=0").unwrap();
+        assert_eq!(exec.exec("2*3/4*4").unwrap(), "4");
+        assert_eq!(exec.exec("2.50*2.50").unwrap(), "6.25");
+        assert_eq!(exec.exec("7.5%2").unwrap(), "1.5");
+        assert_eq!(exec.exec("2^-2").unwrap(), "0");
+        assert_eq!(exec.exec("sqrt(4.0)").unwrap(), "2.0");
+
+        exec.exec("scale=2").unwrap();
+        assert_eq!(exec.exec("1.5^3").unwrap(), "3.37");
+        assert!(exec.exec("2^0.5").is_err());
+        assert!(exec.exec("1/0").is_err());
+    }
+
+    #[test]
+    fn test_posix_scale_tracks_variables_and_functions() {
+        let mut exec: BcExecuter = Default::default();
+        exec.runtime.set_posix_scale(true);
+        exec.exec("scale=0").unwrap();
+        assert_eq!(exec.exec("x=0.10; x*x").unwrap(), ".01");
+        assert_eq!(exec.exec("scale(x)").unwrap(), "2");
+        assert_eq!(
+            exec.exec("define f(v) { return v*2 }; f(1.25)").unwrap(),
+            "2.50"
+        );
+        assert_eq!(exec.exec("if (x < 1 && x > 0) { x+1 }").unwrap(), "1.10");
+    }
+
+    #[test]
+    fn test_prefixed_literals() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("0xFF").unwrap(), "255");
+        assert_eq!(exec.exec("0xff+1").unwrap(), "256");
+        assert_eq!(exec.exec("0b1010").unwrap(), "10");
+        assert_eq!(exec.exec("0o17*2").unwrap(), "30");
+        assert_eq!(exec.exec("x=0x10; x/0b100").unwrap(), "4");
+        assert_eq!(
+            exec.exec("define f(a) { return a+0x1 }; f(0b1)").unwrap(),
+            "2"
+        );
+        assert_eq!(exec.exec("0x2+3i").unwrap(), "2 + 3i");
+        assert_eq!(
+            exec.exec("[[0x1,0b10],[0o7,4]]*2").unwrap(),
+            "[[2, 4]; [14, 8]]"
+        );
+        assert!(exec.exec("0b102").is_err());
+    }
+
+    #[test]
+    fn test_ibase_reads_literals_in_input_base() {
+        let mut exec: BcExecuter = Default::default();
+        exec.exec("ibase=16").unwrap();
+        assert_eq!(exec.exec("FF").unwrap(), "255");
+        assert_eq!(exec.exec("FF.8").unwrap(), "255.50000000000000000000");
+        assert_eq!(exec.exec("x1=10; x1+1").unwrap(), "17");
+        assert_eq!(exec.exec("0b11+A").unwrap(), "13");
+        assert_eq!(exec.exec("[[A,1F]]+[[1,1]]").unwrap(), "[[11, 32]]");
+        assert!(exec.exec("1G").is_err());
+
+        exec.exec("ibase=A").unwrap();
+        assert_eq!(exec.exec("10").unwrap(), "10");
+        exec.exec("ibase=2").unwrap();
+        assert_eq!(exec.exec("1010").unwrap(), "10");
+        assert!(exec.exec("ibase=100101").is_err());
+
+        exec.exec("ibase=1010").unwrap();
+        exec.runtime.set_posix_scale(true);
+        exec.exec("ibase=16").unwrap();
+        assert_eq!(exec.exec("FF*1.8").unwrap(), "382.5");
+    }
+
+    #[test]
+    fn test_obase_handles_large_integers_and_bases() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(
+            exec.exec("2^200").unwrap(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!(exec.exec("length(2^300000)").unwrap(), "90309");
+        assert!(exec.exec("2^1000000").is_err());
+        exec.exec("obase=16").unwrap();
+        assert_eq!(exec.exec("2^200").unwrap(), format!("1{}", "0".repeat(50)));
+        assert_eq!(exec.exec("-255").unwrap(), "-FF");
+        assert_eq!(exec.exec("255.5").unwrap(), "FF.80000000000000000");
+        assert_eq!(exec.exec("0").unwrap(), "0");
+
+        exec.exec("obase=1000").unwrap();
+        assert_eq!(exec.exec("123456789").unwrap(), "123 456 789");
+        assert_eq!(exec.exec("5").unwrap(), "005");
+        exec.exec("scale=3").unwrap();
+        assert_eq!(exec.exec("1.5").unwrap(), "001.500");
+
+        exec.exec("obase=60").unwrap();
+        exec.exec("scale=1").unwrap();
+        assert_eq!(exec.exec("3661.5").unwrap(), "01 01 01.30");
+        assert!(exec.exec("obase=1").is_err());
+    }
+
+    #[test]
+    fn test_rational_mode_keeps_exact_fractions() {
+        let mut exec: BcExecuter = Default::default();
+        exec.exec("rational=1").unwrap();
+        assert_eq!(exec.exec("1/3 + 1/6").unwrap(), "1/2");
+        assert_eq!(exec.exec("0.1 + 0.2").unwrap(), "3/10");
+        assert_eq!(exec.exec("(2/3)^2").unwrap(), "4/9");
+        assert_eq!(exec.exec("2^-3").unwrap(), "1/8");
+        assert_eq!(exec.exec("7 % (2/3)").unwrap(), "1/3");
+        assert_eq!(exec.exec("floor(-7/2)").unwrap(), "-4");
+
+        exec.exec("x = 1/3").unwrap();
+        exec.exec("y = x / 7").unwrap();
+        assert_eq!(exec.exec("y * 21").unwrap(), "1");
+        exec.exec("define third(a) { return a / 3 }").unwrap();
+        assert_eq!(exec.exec("third(1) + third(2)").unwrap(), "1");
+        assert!(exec.exec("1/0").is_err());
+        assert_eq!(exec.exec("1^(10^9)").unwrap(), "1");
+        assert!(exec.exec("2^(10^9)").is_err());
+        assert!(exec.exec("(1/3)^(10^7)").is_err());
+
+        exec.exec("scale=5").unwrap();
+        exec.exec("rational=2").unwrap();
+        assert_eq!(exec.exec("2/3").unwrap(), ".66666");
+        assert!(exec.exec("rational=3").is_err());
+        exec.exec("rational=0").unwrap();
+        assert_eq!(exec.exec("1/3").unwrap(), ".33333");
+    }
+
+    #[test]
+    fn test_interval_mode_encloses_results() {
+        let mut exec: BcExecuter = Default::default();
+        exec.exec("scale=5").unwrap();
+        exec.exec("interval=1").unwrap();
+        assert_eq!(exec.exec("1/3").unwrap(), "[.33333, .33334]");
+        assert_eq!(exec.exec("-1/3").unwrap(), "[-.33334, -.33333]");
+        assert_eq!(exec.exec("sqrt(2)").unwrap(), "[1.41421, 1.41422]");
+
+        exec.exec("x = interval(1, 2)").unwrap();
+        assert_eq!(exec.exec("x*x - x").unwrap(), "[-1, 3]");
+        assert_eq!(exec.exec("interval(-2, 3)^2").unwrap(), "[0, 9]");
+        assert_eq!(exec.exec("abs(interval(-3, 1))").unwrap(), "[0, 3]");
+        assert_eq!(exec.exec("sin(x)").unwrap(), "[.84147, 1]");
+        assert_eq!(exec.exec("cos(interval(3, 3.5))").unwrap(), "[-1, -.93645]");
+        exec.exec("define sq(a) { return a*a }").unwrap();
+        assert_eq!(exec.exec("sq(x)").unwrap(), "[1, 4]");
+        assert!(exec.exec("1/interval(-1, 1)").is_err());
+        assert!(exec.exec("tan(x)").is_err());
+        assert_eq!(exec.exec("interval(2, 3)^3").unwrap(), "[8, 27]");
+        assert_eq!(exec.exec("interval(1, 3) < 4").unwrap(), "1");
+        assert_eq!(exec.exec("x >= interval(3, 4)").unwrap(), "0");
+        assert_eq!(exec.exec("interval(1, 2) <= interval(2, 3)").unwrap(), "1");
+        assert_eq!(exec.exec("x != 5").unwrap(), "1");
+        assert!(exec.exec("interval(1, 3) < 2").is_err());
+        assert!(exec.exec("sqrt(2)^2 == 2").is_err());
+        assert_eq!(exec.exec("gcd(4, 6)").unwrap(), "[2, 2]");
+        assert_eq!(exec.exec("factorial(5) + 1").unwrap(), "[121, 121]");
+        assert_eq!(exec.exec("exp(0)").unwrap(), "[.99999, 1.00001]");
+        assert!(exec.exec("10^(10^9)").is_err());
+
+        exec.exec("interval=2").unwrap();
+        assert_eq!(exec.exec("x*3").unwrap(), "4.50000 ± 1.50000");
+        assert!(exec.exec("interval=5").is_err());
+    }
+
+    #[test]
+    fn test_measurement_uncertainty_propagation() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("(2.0±0.1)*(3.0±0.2)").unwrap(), "6.00 ± .50");
+        assert_eq!(exec.exec("9.81 ± 0.02").unwrap(), "9.810 ± .020");
+        assert_eq!(exec.exec("(2+/-0.1)^2").unwrap(), "4.00 ± .40");
+        assert_eq!(exec.exec("12345±670").unwrap(), "12350 ± 670");
+        assert_eq!(exec.exec("1/(3±0.1)").unwrap(), ".333 ± .011");
+        assert_eq!(exec.exec("sqrt(16±0.4)").unwrap(), "4.000 ± .050");
+
+        exec.exec("x = 5±0.3").unwrap();
+        assert_eq!(exec.exec("x").unwrap(), "5.00 ± .30");
+        assert_eq!(exec.exec("x - x").unwrap(), "0");
+        assert_eq!(exec.exec("x*x").unwrap(), "25.0 ± 3.0");
+        assert_eq!(exec.exec("ln(x)").unwrap(), "1.609 ± .060");
+        exec.exec("define twice(a) { return a*2 }").unwrap();
+        assert_eq!(exec.exec("twice(x)").unwrap(), "10.00 ± .60");
+        assert!(exec.exec("x % 2").is_err());
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let mut exec: BcExecuter = Default::default();
+        exec.exec("scale=2").unwrap();
+        assert_eq!(exec.exec("0.125").unwrap(), ".12");
+        assert_eq!(exec.exec("round=1").unwrap(), "1");
+        assert_eq!(exec.exec("0.125").unwrap(), ".12");
+        assert_eq!(exec.exec("0.135").unwrap(), ".14");
+        assert_eq!(exec.exec("2/3").unwrap(), ".67");
+        exec.exec("round=2").unwrap();
+        assert_eq!(exec.exec("0.125").unwrap(), ".13");
+        assert_eq!(exec.exec("-0.125").unwrap(), "-.13");
+        exec.exec("round=3").unwrap();
+        assert_eq!(exec.exec("-0.121").unwrap(), "-.13");
+        exec.exec("round=4").unwrap();
+        assert_eq!(exec.exec("0.121").unwrap(), ".13");
+        exec.exec("round=1").unwrap();
+        exec.exec("x=1.015").unwrap();
+        exec.exec("scale=5").unwrap();
+        assert_eq!(exec.exec("x").unwrap(), "1.02000");
+        assert_eq!(exec.exec("round(2.5)").unwrap(), "3");
+        assert!(exec.exec("round=5").is_err());
+    }
+
+    #[test]
+    fn test_number_theory_builtins() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("gcd(12, 18, 8)").unwrap(), "2");
+        assert_eq!(exec.exec("lcm(4, 6, 10)").unwrap(), "60");
+        assert_eq!(exec.exec("25!").unwrap(), "15511210043330985984000000");
+        assert_eq!(exec.exec("(2+1)! + 2^3!").unwrap(), "70");
+        assert_eq!(exec.exec("5 != 4").unwrap(), "1");
+        assert_eq!(exec.exec("binomial(50, 25)").unwrap(), "126410606437752");
+        assert_eq!(exec.exec("nCr(-3, 2)").unwrap(), "6");
+        assert_eq!(exec.exec("nPr(5, 2)").unwrap(), "20");
+        assert_eq!(
+            exec.exec("modpow(2, 100, 1000000007)").unwrap(),
+            "976371285"
+        );
+        assert_eq!(exec.exec("modpow(3, -1, 7)").unwrap(), "5");
+        assert_eq!(exec.exec("modinv(3, 7)").unwrap(), "5");
+        assert!(exec.exec("modinv(2, 4)").is_err());
+        assert_eq!(exec.exec("isprime(2^61 - 1)").unwrap(), "1");
+        assert_eq!(exec.exec("isprime(561)").unwrap(), "0");
+        assert_eq!(exec.exec("nextprime(100)").unwrap(), "101");
+        assert_eq!(exec.exec("totient(36)").unwrap(), "12");
+        assert_eq!(exec.exec("factor(360)").unwrap(), "2^3 * 3^2 * 5");
+        assert_eq!(
+            exec.exec("factor(2^64 + 1)").unwrap(),
+            "274177 * 67280421310721"
+        );
+        assert_eq!(
+            exec.exec("10^30 + 1").unwrap(),
+            "1000000000000000000000000000001"
+        );
+        assert!(exec.exec("factorial(2.5)").is_err());
+        assert!(exec.exec("1 + factor(3)").is_err());
+        assert_eq!(exec.exec("x=360; factor(x)").unwrap(), "2^3 * 3^2 * 5");
+        assert_eq!(exec.exec("factor(12); 1").unwrap(), "2^2 * 3\n1");
+        exec.exec("for (i = 2; i < 5; i = i + 1) factor(i)")
+            .unwrap();
+        assert_eq!(
+            exec.exec("for (i = 2; i < 5; i = i + 1) { factor(i) }; 0")
+                .unwrap(),
+            "2\n3\n2^2\n0"
+        );
+    }
+
+    #[test]
+    fn test_special_functions() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("gamma(5)").unwrap(), "24");
+        assert_eq!(exec.exec("gamma(0.5)^2").unwrap(), "3.14159265358979323846");
+        assert_eq!(exec.exec("gamma(-1.5)").unwrap(), "2.36327180120735470306");
+        assert_eq!(
+            exec.exec("lgamma(100)").unwrap(),
+            "359.13420536957539877604"
+        );
+        assert_eq!(exec.exec("erf(0.5)").unwrap(), ".52049987781304653768");
+        assert_eq!(exec.exec("erfc(1)").unwrap(), ".15729920705028513065");
+        assert_eq!(exec.exec("beta(2, 3)").unwrap(), ".08333333333333333333");
+        assert_eq!(exec.exec("zeta(3)").unwrap(), "1.20205690315959428539");
+        assert_eq!(exec.exec("zeta(-1)").unwrap(), "-.08333333333333333333");
+        assert_eq!(
+            exec.exec("gammainc(2, 1)").unwrap(),
+            ".26424111765711535680"
+        );
+        assert_eq!(
+            exec.exec("betainc(2, 3, 0.4)").unwrap(),
+            ".52480000000000000000"
+        );
+        assert_eq!(exec.exec("y(0, 1)").unwrap(), ".08825696421567695798");
+        assert_eq!(exec.exec("y(3, 2.5)").unwrap(), "-.75605549675367099683");
+        assert_eq!(exec.exec("y(-1, 10)").unwrap(), "-.24901542420695388392");
+        assert!(exec.exec("y(0, 0)").is_err());
+        assert!(exec.exec("y(0.5, 1)").is_err());
+        assert!(exec.exec("y(0, 1001)").is_err());
+        exec.exec("scale=40").unwrap();
+        assert_eq!(
+            exec.exec("y(0, 1)").unwrap(),
+            ".0882569642156769579829267660235151628278"
+        );
+        assert_eq!(
+            exec.exec("erf(2)").unwrap(),
+            ".9953222650189527341620692563672529286108"
+        );
+        assert!(exec.exec("gamma(-2)").is_err());
+        assert!(exec.exec("zeta(1)").is_err());
+    }
+
+    #[test]
+    fn test_bitwise_and_programmer_mode() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("12 & 10").unwrap(), "8");
+        assert_eq!(exec.exec("12 | 3").unwrap(), "15");
+        assert_eq!(exec.exec("5 xor 3").unwrap(), "6");
+        assert_eq!(exec.exec("~5").unwrap(), "-6");
+        assert_eq!(exec.exec("1 << 70").unwrap(), "1180591620717411303424");
+        assert_eq!(exec.exec("-16 >> 2").unwrap(), "-4");
+        assert_eq!(exec.exec("1 | 2 + 4 & 6").unwrap(), "7");
+        assert_eq!(exec.exec("1 && 0").unwrap(), "0");
+        assert!(exec.exec("1 << -1").is_err());
+
+        assert_eq!(exec.exec("word=8").unwrap(), "8");
+        assert_eq!(exec.exec("127 + 1").unwrap(), "-128");
+        assert_eq!(exec.exec("300").unwrap(), "44");
+        assert_eq!(exec.exec("-7 / 2").unwrap(), "-3");
+        assert_eq!(exec.exec("3^5").unwrap(), "-13");
+        exec.exec("a[300] = 1; a[44] = 2").unwrap();
+        assert_eq!(exec.exec("a[300]").unwrap(), "1");
+        assert_eq!(exec.exec("a[44] + a[200 + 100]").unwrap(), "3");
+        assert_eq!(exec.exec("a[1] = 300; a[1]").unwrap(), "44");
+        exec.exec("signed=0").unwrap();
+        assert_eq!(exec.exec("~0").unwrap(), "255");
+        assert_eq!(exec.exec("1 << 8").unwrap(), "0");
+        exec.exec("word=32").unwrap();
+        exec.exec("signed=1").unwrap();
+        exec.exec("obase=16").unwrap();
+        assert_eq!(exec.exec("-1").unwrap(), "FFFFFFFF");
+        assert_eq!(exec.exec("0xff").unwrap(), "000000FF");
+        exec.exec("obase=10").unwrap();
+        assert!(exec.exec("word=12").is_err());
+        assert!(exec.exec("signed=2").is_err());
+        exec.exec("word=0").unwrap();
+        assert_eq!(exec.exec("7 / 2").unwrap(), "3.50000000000000000000");
+    }
+
+    #[test]
+    fn test_bitwise_operands_in_decimal_expressions() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("scale=2; 1/3 + (1 << 2)").unwrap(), "4.33");
+        assert_eq!(exec.exec("(12 & 10) + 0.5").unwrap(), "8.50");
+        assert_eq!(exec.exec("x=6; (x | 1) / 2").unwrap(), "3.50");
+        assert_eq!(exec.exec("~(4/2)").unwrap(), "-3");
+        assert!(exec.exec("12 & 10 + 0.5").is_err());
+        assert!(exec.exec("x=2.5; x & 3").is_err());
+        assert!(exec.exec("x=3 km; x & 1").is_err());
+        assert!(exec.exec("~0.5").is_err());
+    }
+
+    #[test]
+    fn test_multibase_view() {
+        let mut exec: BcExecuter = Default::default();
+        exec.exec("255").unwrap();
+        assert_eq!(exec.base_views(), None);
+        assert_eq!(exec.exec("multibase=1").unwrap(), "1");
+        exec.exec("255").unwrap();
+        assert_eq!(
+            exec.base_views().unwrap(),
+            vec!["255", "0xFF", "0o377", "0b1111_1111"]
+        );
+        exec.exec("-6").unwrap();
+        assert_eq!(
+            exec.base_views().unwrap(),
+            vec!["-6", "-0x6", "-0o6", "-0b110"]
+        );
+        exec.exec("1/2").unwrap();
+        assert_eq!(exec.base_views(), None);
+        exec.exec("word=16").unwrap();
+        exec.exec("-2").unwrap();
+        assert_eq!(
+            exec.base_views().unwrap(),
+            vec!["-2", "0xFFFE", "0o177776", "0b1111_1111_1111_1110"]
+        );
+        assert!(exec.exec("multibase=2").is_err());
+    }
+
+    #[test]
+    fn test_ieee754_inspection() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(
+            exec.exec("f64decode(0.1)").unwrap(),
+            "sign 0, exponent -4 (0x3FB), mantissa 0x999999999999A, bits 0x3FB999999999999A"
+        );
+        assert_eq!(
+            exec.exec("f32decode(-2.5)").unwrap(),
+            "sign 1, exponent 1 (0x80), mantissa 0x200000, bits 0xC0200000"
+        );
+        assert_eq!(
+            exec.exec("f64exact(0.1)").unwrap(),
+            ".1000000000000000055511151231257827021181583404541015625"
+        );
+        assert_eq!(
+            exec.exec("f32exact(0.1)").unwrap(),
+            ".100000001490116119384765625"
+        );
+        assert_eq!(exec.exec("f64bits(1)").unwrap(), "4607182418800017408");
+        assert_eq!(
+            exec.exec("f64frombits(0x4009000000000000)").unwrap(),
+            "3.12500000000000000000"
+        );
+        assert_eq!(
+            exec.exec("f64exact(0.1) - 0.1").unwrap(),
+            ".00000000000000000555"
+        );
+        assert!(exec.exec("f32frombits(0x7F800000)").is_err());
+        assert!(exec.exec("f64decode(1) + 1").is_err());
+        assert_eq!(
+            exec.exec("x=0.1; f64decode(x)").unwrap(),
+            "sign 0, exponent -4 (0x3FB), mantissa 0x999999999999A, bits 0x3FB999999999999A"
+        );
+        exec.exec("define show(v) { f32decode(v); return v }")
+            .unwrap();
+        assert_eq!(
+            exec.exec("show(1)").unwrap(),
+            "sign 0, exponent 0 (0x7F), mantissa 0x0, bits 0x3F800000\n1"
+        );
+        assert_eq!(
+            exec.exec("for (i = 1; i < 3; i = i + 1) { f32decode(i) }; 0")
+                .unwrap(),
+            "sign 0, exponent 0 (0x7F), mantissa 0x0, bits 0x3F800000\n\
+             sign 0, exponent 1 (0x80), mantissa 0x0, bits 0x40000000\n0"
+        );
+    }
+
+    #[test]
+    fn test_scientific_notation() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("1.5e-9 * 2").unwrap(), ".00000000300000000000");
+        assert_eq!(exec.exec("4.7k + 330").unwrap(), "5030");
+        assert_eq!(exec.exec("2.2u").unwrap(), ".00000220000000000000");
+        exec.exec("rational=1").unwrap();
+        assert_eq!(exec.exec("1e-3 + 2m").unwrap(), "3/1000");
+        exec.exec("rational=0").unwrap();
+
+        exec.exec("scale=5").unwrap();
+        assert_eq!(exec.exec("notation=1").unwrap(), "1e0");
+        assert_eq!(exec.exec("2^-200").unwrap(), "6.22301e-61");
+        assert_eq!(exec.exec("-123456").unwrap(), "-1.23456e5");
+        assert_eq!(exec.exec("9.999999").unwrap(), "9.99999e0");
+        exec.exec("notation=2").unwrap();
+        assert_eq!(exec.exec("0.000047").unwrap(), "47e-6");
+        assert_eq!(exec.exec("1/3").unwrap(), "333.333e-3");
+        exec.exec("notation=3").unwrap();
+        assert_eq!(exec.exec("4.7k * 2").unwrap(), "9.4k");
+        assert_eq!(exec.exec("1.5e-9").unwrap(), "1.5n");
+        assert_eq!(exec.exec("12").unwrap(), "12");
+        assert_eq!(exec.exec("10^30").unwrap(), "1e30");
+        assert!(exec.exec("notation=4").is_err());
+    }
+
+    #[test]
+    fn test_locale_grouping() {
+        let mut exec: BcExecuter = Default::default();
+        exec.exec("scale=2").unwrap();
+        assert_eq!(exec.exec("locale=1").unwrap(), "1");
+        assert_eq!(exec.exec("1234567.891").unwrap(), "1,234,567.89");
+        assert_eq!(exec.exec("-1000").unwrap(), "-1,000");
+        assert_eq!(exec.exec("999").unwrap(), "999");
+        exec.exec("locale=4").unwrap();
+        assert_eq!(exec.exec("1234567.891").unwrap(), "12,34,567.89");
+        assert_eq!(exec.exec("12345678901").unwrap(), "12,34,56,78,901");
+        exec.exec("locale=2").unwrap();
+        assert_eq!(exec.exec("1234567.891").unwrap(), "1 234 567,89");
+        assert_eq!(exec.exec("1,5 * 2,5").unwrap(), "3,75");
+        assert_eq!(exec.exec("max(1,5; 2,25)").unwrap(), "2,25");
+        exec.exec("define f(a; b) { return a * b }").unwrap();
+        assert_eq!(exec.exec("f(0,5; 3)").unwrap(), "1,50");
+        exec.exec("locale=3").unwrap();
+        assert_eq!(exec.exec("1234567,891").unwrap(), "1.234.567,89");
+        exec.exec("notation=1").unwrap();
+        assert_eq!(exec.exec("1234,5").unwrap(), "1,23e3");
+        assert!(exec.exec("locale=5").is_err());
+
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("locale=2; max(1,5; 2)").unwrap(), "2");
+        assert_eq!(
+            exec.exec("s = 0; for (i = 0; i < 3; i = i + 1) { s = s + max(i; 1) }; s")
+                .unwrap(),
+            "4"
+        );
+        assert_eq!(exec.exec("while (s > 1) s = s - 1; s").unwrap(), "1");
+        assert_eq!(exec.exec("locale=0; max(1,5)").unwrap(), "5");
+
+        exec.exec("scale=2; locale=3").unwrap();
+        assert_eq!(
+            exec.exec("[[1,5; 2000]; [3; 4]] + [[1; 1]; [1; 1]]")
+                .unwrap(),
+            "[[2,50; 2.001]; [4; 5]]"
+        );
+        assert_eq!(
+            exec.exec("amort(0,5; 2; 1000)").unwrap(),
+            "[[1; 900; 500; 400; 600]; [2; 900; 300; 600; 0]]"
+        );
+        assert_eq!(exec.exec("(2,0±0,1)*(3,0±0,2)").unwrap(), "6,00 ± ,50");
+        exec.exec("interval=1").unwrap();
+        assert_eq!(exec.exec("interval(1,5; 1234)").unwrap(), "[1,50; 1.234]");
+        exec.exec("interval=2").unwrap();
+        assert_eq!(exec.exec("interval(1; 2) / 2").unwrap(), ",75 ± ,25");
+
+        exec.exec("interval=0; scale=1").unwrap();
+        assert_eq!(exec.exec("1.234,5 * 2").unwrap(), "2.469");
+        assert_eq!(exec.exec("1.234.567 + 1").unwrap(), "1.234.568");
+        assert_eq!(exec.exec("1.5 + 1").unwrap(), "2,5");
+        assert!(exec.exec("1.234.56").is_err());
+        exec.exec("locale=2").unwrap();
+        assert_eq!(exec.exec("1 234,5 + max(1 000; 2)").unwrap(), "2 234,5");
+    }
+
+    #[test]
+    fn test_constants() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("pi").unwrap(), "3.14159265358979323846");
+        assert_eq!(exec.exec("e").unwrap(), "2.71828182845904523536");
+        assert_eq!(exec.exec("phi").unwrap(), "1.61803398874989484820");
+        assert_eq!(exec.exec("c").unwrap(), "299792458");
+        assert_eq!(exec.exec("2 * c").unwrap(), "599584916");
+        assert_eq!(exec.exec("e(1)").unwrap(), "2.71828182845904523536");
+        assert_eq!(exec.exec("c(0)").unwrap(), "1");
+        exec.exec("scale=40").unwrap();
+        assert_eq!(
+            exec.exec("tau").unwrap(),
+            "6.2831853071795864769252867665590057683943"
+        );
+        exec.exec("scale=60").unwrap();
+        assert_eq!(
+            exec.exec("hbar").unwrap(),
+            ".000000000000000000000000000000000105457181764615639126242800"
+        );
+        assert!(exec.exec("pi = 3").is_err());
+        assert!(exec.exec("define f(c) { return c }").is_err());
+        assert_eq!(exec.exec("x = pi; x - pi").unwrap(), "0");
+    }
+
+    #[test]
+    fn test_physical_units() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("3 km").unwrap(), "3000 m");
+        assert_eq!(exec.exec("3m").unwrap(), ".00300000000000000000");
+        assert_eq!(
+            exec.exec("3 km + 250 m in mi").unwrap(),
+            "2.01945637477133540125 mi"
+        );
+        assert_eq!(
+            exec.exec("9.81 m/s^2 * 70 kg").unwrap(),
+            "686.70000000000000000000 N"
+        );
+        assert_eq!(exec.exec("10 N * 3 m").unwrap(), "30 J");
+        assert_eq!(exec.exec("2 kWh -> J").unwrap(), "7200000 J");
+        assert_eq!(exec.exec("2 h in min").unwrap(), "120 min");
+        assert_eq!(
+            exec.exec("100 km/h in m/s").unwrap(),
+            "27.77777777777777777777 m/s"
+        );
+        assert_eq!(exec.exec("sqrt(9 m^2)").unwrap(), "3 m");
+        assert_eq!(exec.exec("(3 m)^2").unwrap(), "9 m^2");
+        assert_eq!(exec.exec("3 m < 4 m").unwrap(), "1");
+        exec.exec("d = 3 km").unwrap();
+        assert_eq!(
+            exec.exec("d / 2 in km").unwrap(),
+            "1.50000000000000000000 km"
+        );
+        exec.exec("define twice(a) { return a * 2 }").unwrap();
+        assert_eq!(exec.exec("twice(3 m)").unwrap(), "6 m");
+        assert_eq!(exec.exec("c(0)").unwrap(), "1");
+        assert!(exec.exec("3 m + 2 s").is_err());
+        assert!(exec.exec("3 m in s").is_err());
+        assert!(exec.exec("sin(3 m)").is_err());
+        assert!(exec.exec("3 m + 1").is_err());
+    }
+
+    #[test]
+    fn test_dates_and_durations() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("2026-10-18").unwrap(), "2026-10-18T00:00:00Z");
+        assert_eq!(
+            exec.exec("2026-10-18T09:30Z + 1h30m").unwrap(),
+            "2026-10-18T11:00:00Z"
+        );
+        assert_eq!(
+            exec.exec("2026-10-20T00:00Z - 2026-10-18T09:30+09:00")
+                .unwrap(),
+            "1d23h30min"
+        );
+        assert_eq!(
+            exec.exec("2024-02-28T23:59:59.5Z + 1s").unwrap(),
+            "2024-02-29T00:00:00.5Z"
+        );
+        assert_eq!(exec.exec("1h30m in min").unwrap(), "90 min");
+        assert_eq!(exec.exec("1h / 30min").unwrap(), "2");
+        assert_eq!(exec.exec("90s * 3").unwrap(), "4min30s");
+        assert_eq!(exec.exec("x=1h; x/2").unwrap(), "30min");
+        assert_eq!(exec.exec("30min + 1h").unwrap(), "1h30min");
+        assert_eq!(exec.exec("4min30s / 3").unwrap(), "1min30s");
+        assert_eq!(exec.exec("1h - 2h").unwrap(), "-1h");
+        assert_eq!(exec.exec("2m").unwrap(), ".00200000000000000000");
+        assert_eq!(exec.exec("weekday(2026-10-18)").unwrap(), "7");
+        assert_eq!(exec.exec("epoch(1970-01-02)").unwrap(), "86400");
+        assert_eq!(
+            exec.exec("fromepoch(1000000000)").unwrap(),
+            "2001-09-09T01:46:40Z"
+        );
+        assert_eq!(
+            exec.exec("tz(2026-10-18T09:30Z, 0-5.5)").unwrap(),
+            "2026-10-18T04:00:00-05:30"
+        );
+        exec.exec("deploy = 2026-10-18T09:30:00+02:00").unwrap();
+        assert_eq!(exec.exec("hour(deploy)").unwrap(), "9");
+        assert_eq!(
+            exec.exec("deploy + 3d").unwrap(),
+            "2026-10-21T09:30:00+02:00"
+        );
+        assert_eq!(exec.exec("2026-10-18 < 2026-10-19").unwrap(), "1");
+        assert!(exec.exec("2026-02-29").is_err());
+        assert!(exec.exec("2026-10-18 + 2026-10-19").is_err());
+        assert!(exec.exec("2026-10-18 + 1").is_err());
+    }
+
+    #[test]
+    fn test_ip_addresses() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("10.0.0.1").unwrap(), "10.0.0.1");
+        assert_eq!(exec.exec("10.0.0.255 + 2").unwrap(), "10.0.1.1");
+        assert_eq!(exec.exec("2001:db8:0:0::1 + 15").unwrap(), "2001:db8::10");
+        assert_eq!(
+            exec.exec("network(192.168.1.77/22)").unwrap(),
+            "192.168.0.0/22"
+        );
+        assert_eq!(
+            exec.exec("broadcast(192.168.1.77/22)").unwrap(),
+            "192.168.3.255"
+        );
+        assert_eq!(exec.exec("netmask(10.1.2.3/20)").unwrap(), "255.255.240.0");
+        assert_eq!(exec.exec("hosts(192.168.0.0/22)").unwrap(), "1022");
+        assert_eq!(exec.exec("hosts(10.0.0.0/31)").unwrap(), "2");
+        assert_eq!(
+            exec.exec("hosts(2001:db8::/64)").unwrap(),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            exec.exec("contains(192.168.0.0/22, 192.168.3.9)").unwrap(),
+            "1"
+        );
+        assert_eq!(
+            exec.exec("contains(192.168.0.0/22, 192.168.4.0/24)")
+                .unwrap(),
+            "0"
+        );
+        assert_eq!(exec.exec("subnets(192.168.0.0/22, 24)").unwrap(), "4");
+        assert_eq!(
+            exec.exec("subnet(192.168.0.0/22, 24, 2)").unwrap(),
+            "192.168.2.0/24"
+        );
+        assert_eq!(
+            exec.exec("subnet(2001:db8::/32, 48, 255)").unwrap(),
+            "2001:db8:ff::/48"
+        );
+        assert_eq!(exec.exec("10.0.1.0 - 10.0.0.0").unwrap(), "256");
+        assert_eq!(exec.exec("10.0.0.9 > 10.0.0.10").unwrap(), "0");
+        exec.exec("gw = 172.16.0.1").unwrap();
+        assert_eq!(exec.exec("gw + 1").unwrap(), "172.16.0.2");
+        assert_eq!(exec.exec("1.5 + 2").unwrap(), "3.50000000000000000000");
+        assert!(exec.exec("255.255.255.255 + 1").is_err());
+        assert!(exec.exec("10.0.0.0/33").is_err());
+        assert_eq!(exec.exec("::1 - 1").unwrap(), "::");
+        assert_eq!(exec.exec("::").unwrap(), "::");
+        assert_eq!(exec.exec("x = ::; x + 1").unwrap(), "::1");
+        assert!(exec.exec("10.0.0.1 + ::1").is_err());
+        assert!(exec.exec("broadcast(2001:db8::/64)").is_err());
+        assert!(exec.exec("subnet(192.168.0.0/22, 24, 4)").is_err());
+    }
+
+    #[test]
+    fn test_angle_modes() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("90°").unwrap(), "1.57079632679489661923");
+        exec.exec("angle=1").unwrap();
+        assert_eq!(exec.exec("angle").unwrap(), "1");
+        assert_eq!(exec.exec("sin(30)").unwrap(), ".50000000000000000000");
+        assert_eq!(exec.exec("s(90)").unwrap(), "1");
+        assert_eq!(exec.exec("c(180)").unwrap(), "-1");
+        assert_eq!(exec.exec("sin(180)").unwrap(), "0");
+        assert_eq!(exec.exec("tan(45)").unwrap(), "1");
+        assert_eq!(exec.exec("tan(180)").unwrap(), "0");
+        assert!(exec.exec("tan(90)").is_err());
+        assert!(exec.exec("tan(-270)").is_err());
+        assert_eq!(exec.exec("asin(0.5)").unwrap(), "30");
+        assert_eq!(exec.exec("a(1)").unwrap(), "45");
+        assert_eq!(exec.exec("atan2(1, 0-1)").unwrap(), "135");
+        assert_eq!(exec.exec("rad(pi)").unwrap(), "180");
+        assert_eq!(exec.exec("30°").unwrap(), "30");
+        assert_eq!(exec.exec("sin(30+0i)").unwrap(), ".50000000000000000000");
+        assert_eq!(
+            exec.exec("sin([[30, 90]])").unwrap(),
+            "[[.50000000000000000000, 1]]"
+        );
+        assert_eq!(exec.exec("sin(deg(90))").unwrap(), "1");
+        exec.exec("angle=2").unwrap();
+        assert_eq!(exec.exec("cos(200)").unwrap(), "-1");
+        assert_eq!(exec.exec("sin(90°)").unwrap(), "1");
+        assert_eq!(exec.exec("acos(0)").unwrap(), "100");
+        assert!(exec.exec("tan(300)").is_err());
+        assert_eq!(
+            exec.exec("x=1; sin([[100, 50+0i]])").unwrap(),
+            "[[1, .70710678118654752440]]"
+        );
+        assert_eq!(exec.exec("sin(100+0i); 7").unwrap(), "1\n7");
+        exec.exec("angle=1; interval=1").unwrap();
+        assert_eq!(
+            exec.exec("sin(interval(80, 100))").unwrap(),
+            "[.98480775301220805936, 1]"
+        );
+        exec.exec("interval=0; angle=0").unwrap();
+        assert_eq!(exec.exec("sin(30°)").unwrap(), ".50000000000000000000");
+        assert!(exec.exec("angle=3").is_err());
+        assert_eq!(
+            exec.exec("angle=1; sin([[30, 90]])").unwrap(),
+            "[[.50000000000000000000, 1]]"
+        );
+    }
+
+    #[test]
+    fn test_statistics_builtins() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(
+            exec.exec("sum(0.1, 0.2, 0.3)").unwrap(),
+            ".60000000000000000000"
+        );
+        assert_eq!(
+            exec.exec("mean(1, 2, 3, 4)").unwrap(),
+            "2.50000000000000000000"
+        );
+        assert_eq!(exec.exec("mean(1, 2)").unwrap(), "1.50000000000000000000");
+        assert_eq!(exec.exec("median(5, 1, 3)").unwrap(), "3");
+        assert_eq!(
+            exec.exec("median(4, 1, 3, 2)").unwrap(),
+            "2.50000000000000000000"
+        );
+        assert_eq!(exec.exec("mode(3, 1, 3, 2, 1)").unwrap(), "1");
+        assert_eq!(
+            exec.exec("var(2, 4, 4, 4, 5, 5, 7, 9)").unwrap(),
+            "4.57142857142857142857"
+        );
+        assert_eq!(exec.exec("pvar(2, 4, 4, 4, 5, 5, 7, 9)").unwrap(), "4");
+        assert_eq!(exec.exec("pstddev(2, 4, 4, 4, 5, 5, 7, 9)").unwrap(), "2");
+        assert_eq!(
+            exec.exec("stddev(1, 2, 3, 4)").unwrap(),
+            "1.29099444873580562839"
+        );
+        assert_eq!(
+            exec.exec("percentile(50, 1, 2, 3, 4)").unwrap(),
+            "2.50000000000000000000"
+        );
+        assert_eq!(
+            exec.exec("percentile(99, 10, 20, 30, 40, 50)").unwrap(),
+            "49.60000000000000000000"
+        );
+        assert_eq!(exec.exec("geomean(2, 8)").unwrap(), "4");
+        assert_eq!(exec.exec("harmean(1, 4, 4)").unwrap(), "2");
+        assert_eq!(exec.exec("range(3, 9, 1)").unwrap(), "8");
+        assert_eq!(
+            exec.exec("mean(1, 2) + 1").unwrap(),
+            "2.50000000000000000000"
+        );
+        assert!(exec.exec("var(1)").is_err());
+        assert!(exec.exec("percentile(101, 1, 2)").is_err());
+        assert!(exec.exec("geomean(1, 0)").is_err());
+        assert!(exec.exec("mean()").is_err());
+    }
+
+    #[test]
+    fn test_probability_distributions() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("normpdf(0)").unwrap(), ".39894228040143267793");
+        assert_eq!(exec.exec("normcdf(1.96)").unwrap(), ".97500210485177956586");
+        assert_eq!(
+            exec.exec("norminv(0.975)").unwrap(),
+            "1.95996398454005423552"
+        );
+        assert_eq!(exec.exec("norminv(0.5, 100, 15)").unwrap(), "100");
+        assert_eq!(
+            exec.exec("normcdf(115, 100, 15)").unwrap(),
+            ".84134474606854294858"
+        );
+        assert_eq!(exec.exec("tcdf(2, 10)").unwrap(), ".96330597856430620925");
+        assert_eq!(
+            exec.exec("tinv(0.975, 10)").unwrap(),
+            "2.22813886153362446641"
+        );
+        assert_eq!(
+            exec.exec("chi2inv(0.95, 1)").unwrap(),
+            "3.84145882069412595836"
+        );
+        assert_eq!(
+            exec.exec("binopdf(3, 10, 0.5)").unwrap(),
+            ".11718750000000000000"
+        );
+        assert_eq!(
+            exec.exec("binocdf(3, 10, 0.5)").unwrap(),
+            ".17187500000000000000"
+        );
+        assert_eq!(exec.exec("binoinv(0.75, 2, 0.5)").unwrap(), "1");
+        assert_eq!(
+            exec.exec("poisscdf(2, 3)").unwrap(),
+            ".42319008112684351532"
+        );
+        assert_eq!(exec.exec("poissinv(0.5, 3)").unwrap(), "3");
+        assert_eq!(exec.exec("expcdf(1, 2)").unwrap(), ".86466471676338730810");
+        assert_eq!(
+            exec.exec("unifinv(0.25, 0, 2)").unwrap(),
+            ".50000000000000000000"
+        );
+
+        let first = exec.exec("srand(7); normrnd(); binornd(10, 0.5)").unwrap();
+        let second = exec.exec("srand(7); normrnd(); binornd(10, 0.5)").unwrap();
+        assert_eq!(first, second);
+
+        assert!(exec.exec("norminv(1)").is_err());
+        assert!(exec.exec("normcdf(0, 0, -1)").is_err());
+        assert!(exec.exec("binopdf(1, 2.5, 0.5)").is_err());
+        assert!(exec.exec("unifrnd(2, 1)").is_err());
+    }
+
+    #[test]
+    fn test_financial_functions() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(
+            exec.exec("pmt(0.05/12, 360, 200000)").unwrap(),
+            "-1073.64324602427796965698"
+        );
+        assert_eq!(
+            exec.exec("fv(0.06/12, 10, -200, -500, 1)").unwrap(),
+            "2581.40337406017915372500"
+        );
+        assert_eq!(exec.exec("fv(0.1, 2, 0, -100)").unwrap(), "121");
+        assert_eq!(exec.exec("pmt(0, 10, 1000)").unwrap(), "-100");
+        assert_eq!(
+            exec.exec("nper(0.01, -100, 1000)").unwrap(),
+            "10.58864445942323599518"
+        );
+        assert_eq!(
+            exec.exec("npv(0.1, -10000, 3000, 4200, 6800)").unwrap(),
+            "1188.44341233522300389317"
+        );
+        assert_eq!(
+            exec.exec("irr(-70000, 12000, 15000, 18000, 21000, 26000)")
+                .unwrap(),
+            ".08663094803653161429"
+        );
+        assert_eq!(
+            exec.exec(
+                "xnpv(0.09, -10000, 2008-01-01, 2750, 2008-03-01, 4250, 2008-10-30, \
+                 3250, 2009-02-15, 2750, 2009-04-01)"
+            )
+            .unwrap(),
+            "2086.64760203153662166361"
+        );
+
+        exec.exec("scale=2").unwrap();
+        exec.exec("round=2").unwrap();
+        assert_eq!(
+            exec.exec("amort(0.05/12, 4, 1000)").unwrap(),
+            "[[1, 252.61, 4.17, 248.44, 751.56]; [2, 252.61, 3.13, 249.48, 502.08]; \
+             [3, 252.61, 2.09, 250.52, 251.56]; [4, 252.61, 1.05, 251.56, 0]]"
+        );
+
+        assert!(exec.exec("pmt(0.05, 10)").is_err());
+        assert!(exec.exec("pv(0.05, 10, -100, 0, 2)").is_err());
+        assert!(exec.exec("irr(100, 200)").is_err());
+        assert!(exec.exec("amort(0.05, 0, 1000)").is_err());
+        assert!(exec.exec("1 + amort(0.05, 2, 1000)").is_err());
+        assert_eq!(
+            exec.exec("scale=0; amort(0.5, 2, 100)").unwrap(),
+            "[[1, 90, 50, 40, 60]; [2, 90, 30, 60, 0]]"
+        );
+    }
+
+    #[test]
+    fn test_bc_arrays() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(exec.exec("a[3] = 7").unwrap(), "7");
+        assert_eq!(exec.exec("a[2]").unwrap(), "0");
+        assert_eq!(exec.exec("a[1.9] = 4; a[1]").unwrap(), "4");
+        assert_eq!(exec.exec("i = 2; a[i + 1] * 2 + a[i - 1]").unwrap(), "18");
+        assert_eq!(exec.exec("a[3] == 7").unwrap(), "1");
+        assert!(exec.exec("a[-1] = 2").is_err());
+        assert!(exec.exec("a[16777216] = 1").is_err());
+        assert!(exec.exec("a[] + 1").is_err());
+
+        exec.exec("for (i = 0; i < 5; i = i + 1) c[i] = i^2")
+            .unwrap();
+        assert_eq!(exec.exec("c[4] - c[3]").unwrap(), "7");
+        assert_eq!(exec.exec("mean(c[])").unwrap(), "6");
+        assert_eq!(exec.exec("sum(c[], 100)").unwrap(), "130");
+
+        exec.exec("define f(x[]) { x[0] = 99; return x[0] + x[4] }")
+            .unwrap();
+        assert_eq!(exec.exec("f(c[])").unwrap(), "115");
+        assert_eq!(exec.exec("c[0]").unwrap(), "0");
+        exec.exec("define g(*x[], n) { x[n] = 5; return n }")
+            .unwrap();
+        assert_eq!(exec.exec("g(c[], 7)").unwrap(), "7");
+        assert_eq!(exec.exec("c[7]").unwrap(), "5");
+        assert_eq!(exec.exec("g(b[], 0); b[0]").unwrap(), "5");
+        assert!(exec.exec("f(5)").is_err());
+        assert!(exec.exec("define k(x) { return x }; k(c[])").is_err());
+
+        exec.exec(
+            "define fact(n) { auto t[], i; t[0] = 1; for (i = 1; i <= n; i = i + 1) t[i] = t[i-1] * i; return t[n] }",
+        )
+        .unwrap();
+        assert_eq!(exec.exec("t[0] = 3; i = 10; fact(6)").unwrap(), "720");
+        assert_eq!(exec.exec("t[0] + i").unwrap(), "13");
+        assert!(exec.exec("auto q").is_err());
+
+        assert_eq!(
+            exec.exec("d[0] = 2026-01-01; d[0] + 1d").unwrap(),
+            "2026-01-02T00:00:00Z"
+        );
+        assert_eq!(exec.exec("n[0] = 10.0.0.1; n[0] + 5").unwrap(), "10.0.0.6");
+    }
+
+    #[test]
+    fn test_print_strings_and_read() {
+        let mut exec: BcExecuter = Default::default();
+        assert_eq!(
+            exec.exec(r#"x = 5; print "x = ", x, "\n""#).unwrap(),
+            "x = 5"
+        );
+        assert_eq!(
+            exec.exec(r#"print "a\tb\qc\ed\z!\n""#).unwrap(),
+            "a\tb\"c\\d!"
+        );
+        assert_eq!(
+            exec.exec(r#""hello, world; [1,2] { }""#).unwrap(),
+            "hello, world; [1,2] { }"
+        );
+        assert_eq!(exec.exec(r#""no\nescapes""#).unwrap(), "no\\nescapes");
+        assert_eq!(exec.exec(r#"print "sum: "; 2 + 2"#).unwrap(), "sum: 4");
+        assert_eq!(
+            exec.exec(r#"for (i = 0; i < 3; i = i + 1) print i, " ""#)
+                .unwrap(),
+            "0 1 2 "
+        );
+        assert_eq!(
+            exec.exec(r#"define f(n) { print "f(", n, ")\n"; return n * 2 }; f(3)"#)
+                .unwrap(),
+            "f(3)\n6"
+        );
+        assert_eq!(exec.exec(r#"if (x == 5) print "five\n""#).unwrap(), "five");
+        assert!(exec.exec(r#"print "unterminated"#).is_err());
+        assert!(exec.exec(r#"s = "text""#).is_err());
+        assert!(exec.exec("1 + print(2)").is_err());
+
+        assert!(exec.exec("read()").is_err());
+        exec.attach_input(Box::new(std::io::Cursor::new("42\n-1.5\n")));
+        assert_eq!(exec.exec("y = read(); z = read(); y * z").unwrap(), "-63");
+        assert!(exec.exec("read()").is_err());
+
+        exec.exec("scale=1; locale=3").unwrap();
+        assert_eq!(
+            exec.exec(r#"x = 2,5; print x, " ", 1,5,"|"; print "\n""#)
+                .unwrap(),
+            "2,5 1,5|"
+        );
+        assert_eq!(exec.exec(r#"print max(1,5; 2), "\n""#).unwrap(), "2");
     }
 
     #[test]
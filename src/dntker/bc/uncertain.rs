@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use dashu::base::Abs;
+use dashu::integer::IBig;
+use dashu::Decimal;
+use num_traits::Zero;
+
+use super::error::BcError;
+use super::formatting;
+use super::mathlib::{self, GUARD_DIGITS};
+use super::number::BcNumber;
+use super::posix::{
+    addresses_unsupported, time_unsupported, units_unsupported, PosixExpr, PosixOp, PosixParser,
+};
+use super::runtime::RoundingMode;
+
+/// Hands out one id per `±` literal so repeated uses of a measurement stay
+/// correlated while different measurements stay independent.
+static NEXT_SOURCE: AtomicU64 = AtomicU64::new(0);
+
+/// A value with first-order (Gaussian) uncertainty. Each independent
+/// measurement it depends on contributes `∂value/∂x · σx`, so `x - x` is
+/// exact again and `x * x` doubles the relative error.
+#[derive(Clone, Debug)]
+pub struct Uncertain {
+    pub value: Decimal,
+    terms: BTreeMap<u64, Decimal>,
+}
+
+impl Uncertain {
+    pub fn exact(value: Decimal) -> Self {
+        Self {
+            value,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub fn measurement(value: Decimal, sigma: Decimal) -> Self {
+        let mut measured = Self::exact(value);
+        if !sigma.is_zero() {
+            let source = NEXT_SOURCE.fetch_add(1, Ordering::Relaxed);
+            measured.terms.insert(source, sigma.abs());
+        }
+        measured
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Combined standard uncertainty: the root sum of squares of the terms.
+    pub fn sigma(&self, precision: usize) -> Result<Decimal, BcError> {
+        let variance = self
+            .terms
+            .values()
+            .fold(Decimal::ZERO, |acc, term| acc + term.clone() * term);
+        mathlib::sqrt(&mathlib::at_precision(&variance, precision), precision)
+    }
+
+    pub fn negate(&self) -> Self {
+        Self::linear(-self.value.clone(), &[(self, -Decimal::ONE)])
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        Self::linear(
+            self.value.clone() + &other.value,
+            &[(self, Decimal::ONE), (other, Decimal::ONE)],
+        )
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        Self::linear(
+            self.value.clone() - &other.value,
+            &[(self, Decimal::ONE), (other, -Decimal::ONE)],
+        )
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::linear(
+            self.value.clone() * &other.value,
+            &[(self, other.value.clone()), (other, self.value.clone())],
+        )
+    }
+
+    pub fn div(&self, other: &Self) -> Result<Self, BcError> {
+        if other.value.is_zero() {
+            return Err(BcError::Error("Division by zero".to_string()));
+        }
+        let quotient = self.value.clone() / &other.value;
+        let inverse = Decimal::ONE / &other.value;
+        let slope = -(quotient.clone() * &inverse);
+        Ok(Self::linear(quotient, &[(self, inverse), (other, slope)]))
+    }
+
+    /// `value` with each input's terms scaled by the partial derivative
+    /// paired with it.
+    fn linear(value: Decimal, inputs: &[(&Self, Decimal)]) -> Self {
+        let mut result = Self::exact(value);
+        for (input, slope) in inputs {
+            for (source, term) in &input.terms {
+                let entry = result.terms.entry(*source).or_insert(Decimal::ZERO);
+                *entry = entry.clone() + term.clone() * slope;
+            }
+        }
+        result.terms.retain(|_, term| !term.is_zero());
+        result
+    }
+}
+
+impl super::BcExecuter {
+    /// Whether `expr` has a `±` literal or reads a variable that carries an
+    /// uncertainty, and so has to go through `eval_uncertain_expression`.
+    pub(super) fn mentions_uncertain(&self, expr: &str) -> bool {
+        if expr.contains('±') || expr.contains("+/-") {
+            return true;
+        }
+        expr.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|word| !word.is_empty())
+            .any(|word| {
+                self.runtime
+                    .holds(word, |number| number.uncertainty.is_some())
+            })
+    }
+
+    /// Evaluates `expr` propagating first-order uncertainties through every
+    /// operator, builtin and user function.
+    pub(super) fn eval_uncertain_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let tree = PosixParser::parse_expression(expr, self.runtime.ibase())?;
+        let result = self.eval_uncertain_node(&tree)?;
+        Ok(BcNumber::uncertain(result))
+    }
+
+    fn eval_uncertain_node(&mut self, node: &PosixExpr) -> Result<Uncertain, BcError> {
+        match node {
+            PosixExpr::Number(number) => Ok(self.promote_uncertain(number.to_uncertain())),
+            PosixExpr::Quantity(..) => Err(units_unsupported()),
+            PosixExpr::Time(_) => Err(time_unsupported()),
+            PosixExpr::Address(_) => Err(addresses_unsupported()),
+            node @ (PosixExpr::Element(..) | PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                let number = self.eval_array_node(node, |exec, node| {
+                    exec.eval_uncertain_node(node).map(BcNumber::uncertain)
+                })?;
+                Ok(self.promote_uncertain(number.to_uncertain()))
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => Ok(self.promote_uncertain(number.to_uncertain())),
+                None => self.eval_uncertain_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_uncertain_node(arg)?);
+                }
+                self.eval_uncertain_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => Ok(self.eval_uncertain_node(operand)?.negate()),
+            PosixExpr::Not(operand) => {
+                let value = self.eval_uncertain_node(operand)?;
+                Ok(Self::uncertain_bool(value.value.is_zero()))
+            }
+            node @ (PosixExpr::Complement(_) | PosixExpr::Bitwise(..)) => {
+                let number = self.eval_bitwise_node(node, |exec, node| {
+                    exec.eval_uncertain_node(node).map(BcNumber::uncertain)
+                })?;
+                Ok(self.promote_uncertain(number.to_uncertain()))
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_uncertain_node(lhs)?.value.is_zero() {
+                    return Ok(Self::uncertain_bool(false));
+                }
+                let right = self.eval_uncertain_node(rhs)?;
+                Ok(Self::uncertain_bool(!right.value.is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_uncertain_node(lhs)?.value.is_zero() {
+                    return Ok(Self::uncertain_bool(true));
+                }
+                let right = self.eval_uncertain_node(rhs)?;
+                Ok(Self::uncertain_bool(!right.value.is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let left = self.eval_uncertain_node(lhs)?;
+                let right = self.eval_uncertain_node(rhs)?;
+                let (a, b) = (&left.value, &right.value);
+                match op {
+                    PosixOp::Add => Ok(left.add(&right)),
+                    PosixOp::Sub => Ok(left.sub(&right)),
+                    PosixOp::Mul => Ok(left.mul(&right)),
+                    PosixOp::Div => left.div(&right),
+                    PosixOp::Mod => {
+                        if !left.is_exact() || !right.is_exact() {
+                            return Err(BcError::Error(
+                                "% is not defined for uncertain values".to_string(),
+                            ));
+                        }
+                        if b.is_zero() {
+                            return Err(BcError::Error("Modulo by zero".to_string()));
+                        }
+                        Ok(Uncertain::exact(a.clone() % b))
+                    }
+                    PosixOp::Pow => self.uncertain_power(&left, &right),
+                    PosixOp::Lt => Ok(Self::uncertain_bool(a < b)),
+                    PosixOp::Le => Ok(Self::uncertain_bool(a <= b)),
+                    PosixOp::Gt => Ok(Self::uncertain_bool(a > b)),
+                    PosixOp::Ge => Ok(Self::uncertain_bool(a >= b)),
+                    PosixOp::Eq => Ok(Self::uncertain_bool(a == b)),
+                    PosixOp::Ne => Ok(Self::uncertain_bool(a != b)),
+                    _ => unreachable!("handled above"),
+                }
+            }
+        }
+    }
+
+    /// `d(x^y) = y·x^(y-1)·dx + x^y·ln(x)·dy`; the second term only matters
+    /// when the exponent itself is uncertain.
+    fn uncertain_power(
+        &self,
+        base: &Uncertain,
+        exponent: &Uncertain,
+    ) -> Result<Uncertain, BcError> {
+        let value = self.power_decimal(&base.value, &exponent.value)?;
+        let below = self.power_decimal(&base.value, &(exponent.value.clone() - Decimal::ONE))?;
+        let base_slope = exponent.value.clone() * below;
+        let exponent_slope = if exponent.is_exact() {
+            Decimal::ZERO
+        } else {
+            let log = self.precise_unary("ln", std::slice::from_ref(&base.value), mathlib::ln)?;
+            value.clone() * log
+        };
+        Ok(Uncertain::linear(
+            value,
+            &[(base, base_slope), (exponent, exponent_slope)],
+        ))
+    }
+
+    fn eval_uncertain_call(
+        &mut self,
+        name: &str,
+        args: Vec<Uncertain>,
+    ) -> Result<Uncertain, BcError> {
+        let values: Vec<Decimal> = args.iter().map(|arg| arg.value.clone()).collect();
+        if let Some(result) = self.call_builtin_function(name, &values) {
+            let value = result?;
+            let mut slopes = Vec::with_capacity(args.len());
+            for (index, arg) in args.iter().enumerate() {
+                let slope = if arg.is_exact() {
+                    Decimal::ZERO
+                } else {
+                    self.builtin_slope(name, &values, index, &value)?
+                };
+                slopes.push((arg, slope));
+            }
+            return Ok(Uncertain::linear(value, &slopes));
+        }
+
+        let numbers = args.into_iter().map(BcNumber::uncertain).collect();
+        if let Some(result) = self.call_function(name, numbers)? {
+            return Ok(result.to_uncertain());
+        }
+
+        Err(BcError::Error(format!("Undefined identifier: {name}")))
+    }
+
+    /// Partial derivative of builtin `name` in argument `index`, by central
+    /// differences, or one-sided ones at the edge of the domain.
+    fn builtin_slope(
+        &mut self,
+        name: &str,
+        args: &[Decimal],
+        index: usize,
+        at: &Decimal,
+    ) -> Result<Decimal, BcError> {
+        let x = &args[index];
+        let magnitude = if x.clone().abs() > Decimal::ONE {
+            x.clone().abs()
+        } else {
+            Decimal::ONE
+        };
+        let digits = (self.runtime.scale() as usize + GUARD_DIGITS) / 2 + 1;
+        let step = magnitude * Decimal::from_parts(IBig::ONE, -(digits as isize));
+        let mut shifted = |delta: &Decimal| {
+            let mut moved = args.to_vec();
+            moved[index] = x.clone() + delta;
+            self.call_builtin_function(name, &moved)
+                .unwrap_or_else(|| Err(BcError::Error(format!("Undefined identifier: {name}"))))
+        };
+        let ahead = shifted(&step);
+        let behind = shifted(&-step.clone());
+        match (ahead, behind) {
+            (Ok(ahead), Ok(behind)) => Ok((ahead - behind) / (step * Decimal::from(2))),
+            (Ok(ahead), Err(_)) => Ok((ahead - at) / step),
+            (Err(_), Ok(behind)) => Ok((at.clone() - behind) / step),
+            (Err(error), Err(_)) => Err(error),
+        }
+    }
+
+    fn promote_uncertain(&self, mut number: Uncertain) -> Uncertain {
+        number.value = self.promote_precision(number.value);
+        number
+    }
+
+    fn uncertain_bool(value: bool) -> Uncertain {
+        Uncertain::exact(if value { Decimal::ONE } else { Decimal::ZERO })
+    }
+}
+
+/// Rounds half away from zero to `places` fractional digits; a negative
+/// count rounds to tens, hundreds and so on.
+pub(super) fn round_at(value: &Decimal, places: isize) -> Decimal {
+    formatting::round_to_places(value, places, RoundingMode::HalfUp)
+}
@@ -0,0 +1,652 @@
+use std::convert::TryFrom;
+
+use dashu::integer::IBig;
+use dashu::rational::RBig;
+use dashu::Decimal;
+use num_traits::Zero;
+
+use super::error::BcError;
+use super::literals::LiteralTable;
+use super::number::BcNumber;
+use super::posix::{PosixExpr, PosixOp, PosixParser};
+
+/// Symbols of the SI base dimensions, in the order `Dimension` stores them.
+const BASE_SYMBOLS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+/// Named units: symbol, size in SI base units, exponents of
+/// `kg, m, s, A, K, mol, cd`, and whether SI prefixes apply.
+const UNITS: [(&str, &str, [i8; 7], bool); 36] = [
+    ("m", "1", [0, 1, 0, 0, 0, 0, 0], true),
+    ("g", "0.001", [1, 0, 0, 0, 0, 0, 0], true),
+    ("s", "1", [0, 0, 1, 0, 0, 0, 0], true),
+    ("A", "1", [0, 0, 0, 1, 0, 0, 0], true),
+    ("K", "1", [0, 0, 0, 0, 1, 0, 0], true),
+    ("mol", "1", [0, 0, 0, 0, 0, 1, 0], true),
+    ("cd", "1", [0, 0, 0, 0, 0, 0, 1], true),
+    ("Hz", "1", [0, 0, -1, 0, 0, 0, 0], true),
+    ("N", "1", [1, 1, -2, 0, 0, 0, 0], true),
+    ("Pa", "1", [1, -1, -2, 0, 0, 0, 0], true),
+    ("J", "1", [1, 2, -2, 0, 0, 0, 0], true),
+    ("W", "1", [1, 2, -3, 0, 0, 0, 0], true),
+    ("C", "1", [0, 0, 1, 1, 0, 0, 0], true),
+    ("V", "1", [1, 2, -3, -1, 0, 0, 0], true),
+    ("ohm", "1", [1, 2, -3, -2, 0, 0, 0], true),
+    ("Ω", "1", [1, 2, -3, -2, 0, 0, 0], true),
+    ("F", "1", [-1, -2, 4, 2, 0, 0, 0], true),
+    ("T", "1", [1, 0, -2, -1, 0, 0, 0], true),
+    ("L", "0.001", [0, 3, 0, 0, 0, 0, 0], true),
+    ("eV", "1.602176634e-19", [1, 2, -2, 0, 0, 0, 0], true),
+    ("Wh", "3600", [1, 2, -2, 0, 0, 0, 0], true),
+    ("bar", "100000", [1, -1, -2, 0, 0, 0, 0], true),
+    ("cal", "4.184", [1, 2, -2, 0, 0, 0, 0], true),
+    ("min", "60", [0, 0, 1, 0, 0, 0, 0], false),
+    ("h", "3600", [0, 0, 1, 0, 0, 0, 0], false),
+    ("d", "86400", [0, 0, 1, 0, 0, 0, 0], false),
+    ("inch", "0.0254", [0, 1, 0, 0, 0, 0, 0], false),
+    ("ft", "0.3048", [0, 1, 0, 0, 0, 0, 0], false),
+    ("yd", "0.9144", [0, 1, 0, 0, 0, 0, 0], false),
+    ("mi", "1609.344", [0, 1, 0, 0, 0, 0, 0], false),
+    ("nmi", "1852", [0, 1, 0, 0, 0, 0, 0], false),
+    ("lb", "0.45359237", [1, 0, 0, 0, 0, 0, 0], false),
+    ("oz", "0.028349523125", [1, 0, 0, 0, 0, 0, 0], false),
+    ("gal", "0.003785411784", [0, 3, 0, 0, 0, 0, 0], false),
+    ("atm", "101325", [1, -1, -2, 0, 0, 0, 0], false),
+    (
+        "psi",
+        "6894.757293168361336722673445",
+        [1, -1, -2, 0, 0, 0, 0],
+        false,
+    ),
+];
+
+/// Coherent derived units results are shown in when their dimension matches.
+const DERIVED: [&str; 10] = ["N", "J", "W", "Pa", "Hz", "C", "V", "ohm", "F", "T"];
+
+/// Exponents of the SI base dimensions; all zero for a plain number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Dimension([i8; 7]);
+
+impl Dimension {
+    fn is_none(self) -> bool {
+        self.0.iter().all(|power| *power == 0)
+    }
+
+    fn combine(self, other: Self, sign: i8) -> Result<Self, BcError> {
+        let mut powers = self.0;
+        for (power, other) in powers.iter_mut().zip(other.0) {
+            *power = other
+                .checked_mul(sign)
+                .and_then(|other| power.checked_add(other))
+                .ok_or_else(exponent_out_of_range)?;
+        }
+        Ok(Self(powers))
+    }
+
+    fn powi(self, exponent: i64) -> Result<Self, BcError> {
+        let mut powers = self.0;
+        for power in powers.iter_mut() {
+            *power =
+                i8::try_from(i64::from(*power) * exponent).map_err(|_| exponent_out_of_range())?;
+        }
+        Ok(Self(powers))
+    }
+
+    /// The `n`th root, when every exponent divides evenly.
+    fn root(self, n: i64) -> Option<Self> {
+        let mut powers = self.0;
+        for power in powers.iter_mut() {
+            let power_of = i64::from(*power);
+            if power_of % n != 0 {
+                return None;
+            }
+            *power = i8::try_from(power_of / n).ok()?;
+        }
+        Some(Self(powers))
+    }
+
+    /// `kg·m/s^2`: positive powers joined by `·`, then each negative one
+    /// after a `/`; `s^-1` when there is no numerator.
+    fn symbol(self) -> String {
+        let term = |symbol: &str, power: i8| match power {
+            1 => symbol.to_string(),
+            _ => format!("{symbol}^{power}"),
+        };
+        let numerator: Vec<String> = BASE_SYMBOLS
+            .iter()
+            .zip(self.0)
+            .filter(|(_, power)| *power > 0)
+            .map(|(symbol, power)| term(symbol, power))
+            .collect();
+        let negative = BASE_SYMBOLS
+            .iter()
+            .zip(self.0)
+            .filter(|(_, power)| *power < 0);
+        if numerator.is_empty() {
+            return negative
+                .map(|(symbol, power)| term(symbol, power))
+                .collect::<Vec<_>>()
+                .join("·");
+        }
+        let mut symbol = numerator.join("·");
+        for (base, power) in negative {
+            symbol.push('/');
+            symbol.push_str(&term(base, -power));
+        }
+        symbol
+    }
+}
+
+/// The unit a result is expressed in: its symbol, its size in SI base units
+/// and its dimension.
+#[derive(Clone, Debug)]
+pub struct Unit {
+    pub symbol: String,
+    factor: RBig,
+    dimension: Dimension,
+}
+
+impl Unit {
+    /// The coherent SI unit of `dimension`, named after a derived unit when
+    /// one matches.
+    fn coherent(dimension: Dimension) -> Self {
+        let symbol = DERIVED
+            .iter()
+            .find(|name| Self::named(name).is_some_and(|unit| unit.dimension == dimension))
+            .map_or_else(|| dimension.symbol(), |name| name.to_string());
+        Self {
+            symbol,
+            factor: RBig::ONE,
+            dimension,
+        }
+    }
+
+    /// A unit symbol such as `km`, `ms` or `mi`: a named unit, or an SI
+    /// prefix in front of one that takes prefixes.
+    pub(crate) fn named(symbol: &str) -> Option<Self> {
+        let unit = |&(name, factor, powers, _): &(&str, &str, [i8; 7], bool)| Self {
+            symbol: name.to_string(),
+            factor: BcNumber::parse_in_base(factor, 10)
+                .map(|number| number.to_rational())
+                .unwrap_or(RBig::ONE),
+            dimension: Dimension(powers),
+        };
+        if let Some(entry) = UNITS.iter().find(|entry| entry.0 == symbol) {
+            return Some(unit(entry));
+        }
+        let mut chars = symbol.chars();
+        let power = prefix_power(chars.next()?)?;
+        let rest = chars.as_str();
+        let entry = UNITS.iter().find(|entry| entry.3 && entry.0 == rest)?;
+        let scale = RBig::from(IBig::from(10).pow(power.unsigned_abs() as usize));
+        let scale = if power < 0 { RBig::ONE / scale } else { scale };
+        Some(Self {
+            symbol: symbol.to_string(),
+            factor: unit(entry).factor * scale,
+            ..unit(entry)
+        })
+    }
+
+    /// A compound unit such as `m/s^2` or `kW·h`, as `scan_unit_suffix`
+    /// delimits it.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let mut factor = RBig::ONE;
+        let mut dimension = Dimension::default();
+        let mut sign = 1;
+        let mut rest = text;
+        loop {
+            let end = rest.find(['/', '*', '·']).unwrap_or(rest.len());
+            let (name, power) = match rest[..end].trim().split_once('^') {
+                Some((name, power)) => (name, power.parse::<i8>().ok()?),
+                None => (rest[..end].trim(), 1),
+            };
+            let unit = Self::named(name)?;
+            let power = power.checked_mul(sign)?;
+            dimension = dimension
+                .combine(unit.dimension.powi(i64::from(power)).ok()?, 1)
+                .ok()?;
+            let scaled = pow_rational(&unit.factor, power);
+            factor *= scaled;
+            if end == rest.len() {
+                break;
+            }
+            sign = if rest[end..].starts_with('/') { -1 } else { 1 };
+            rest = &rest[end + rest[end..].chars().next()?.len_utf8()..];
+        }
+        Some(Self {
+            symbol: text.to_string(),
+            factor,
+            dimension,
+        })
+    }
+}
+
+/// A value in SI base units together with its dimension.
+#[derive(Clone, Debug)]
+struct Quantity {
+    value: Decimal,
+    dimension: Dimension,
+}
+
+impl Quantity {
+    fn plain(value: Decimal) -> Self {
+        Self {
+            value,
+            dimension: Dimension::default(),
+        }
+    }
+
+    fn same_dimension(&self, other: &Self, what: &str) -> Result<(), BcError> {
+        if self.dimension == other.dimension {
+            return Ok(());
+        }
+        Err(BcError::Error(format!(
+            "Cannot {what} {} and {}",
+            describe(self.dimension),
+            describe(other.dimension)
+        )))
+    }
+}
+
+impl super::BcExecuter {
+    /// Whether `expr` has a unit suffix or conversion, or reads a variable
+    /// that carries a unit, and so has to go through `eval_unit_expression`.
+    pub(super) fn mentions_units(&self, expr: &str) -> bool {
+        if !expr.chars().any(char::is_alphabetic) {
+            return false;
+        }
+        if split_conversion(expr).is_some()
+            || PosixParser::mentions_units(expr, self.runtime.ibase())
+        {
+            return true;
+        }
+        expr.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|word| !word.is_empty())
+            .any(|word| self.runtime.holds(word, |number| number.unit.is_some()))
+    }
+
+    /// Evaluates `expr` tracking the dimension of every value, and converts
+    /// the result to the unit after a trailing `in` (or `->`).
+    pub(super) fn eval_unit_expression(&mut self, expr: &str) -> Result<BcNumber, BcError> {
+        let (source, target) = match split_conversion(expr) {
+            Some((source, target)) => (source, Some(target)),
+            None => (expr, None),
+        };
+        let tree = PosixParser::parse_expression(source, self.runtime.ibase())?;
+        let quantity = self.eval_quantity_node(&tree)?;
+        let unit = match target {
+            Some(target) => target,
+            None if quantity.dimension.is_none() => return Ok(BcNumber::natural(quantity.value)),
+            None => Unit::coherent(quantity.dimension),
+        };
+        self.express(quantity, unit)
+    }
+
+    /// A length of time in `unit`, which has to measure time.
+    pub(super) fn express_seconds(
+        &self,
+        seconds: Decimal,
+        unit: Unit,
+    ) -> Result<BcNumber, BcError> {
+        let second = Unit::named("s")
+            .map(|unit| unit.dimension)
+            .unwrap_or_default();
+        let quantity = Quantity {
+            value: seconds,
+            dimension: second,
+        };
+        self.express(quantity, unit)
+    }
+
+    fn express(&self, quantity: Quantity, unit: Unit) -> Result<BcNumber, BcError> {
+        if unit.dimension != quantity.dimension {
+            return Err(BcError::Error(format!(
+                "Cannot convert {} to {}",
+                describe(quantity.dimension),
+                unit.symbol
+            )));
+        }
+        let value = self.scale_by(quantity.value, &(RBig::ONE / &unit.factor));
+        Ok(BcNumber {
+            unit: Some(unit),
+            ..BcNumber::natural(self.promote_precision(value))
+        })
+    }
+
+    fn eval_quantity_node(&mut self, node: &PosixExpr) -> Result<Quantity, BcError> {
+        match node {
+            PosixExpr::Number(number) => self.number_quantity(number),
+            PosixExpr::Quantity(number, symbol) => {
+                let unit = Unit::parse(symbol)
+                    .ok_or_else(|| BcError::Error(format!("Unknown unit: {symbol}")))?;
+                let amount = self.number_quantity(number)?;
+                Ok(Quantity {
+                    value: self.scale_by(amount.value, &unit.factor),
+                    dimension: unit.dimension,
+                })
+            }
+            PosixExpr::Time(_) => Err(BcError::Error(
+                "Units cannot be combined with dates and durations".to_string(),
+            )),
+            PosixExpr::Address(_) => Err(BcError::Error(
+                "Units cannot be combined with IP addresses".to_string(),
+            )),
+            node @ (PosixExpr::Element(..) | PosixExpr::Array(_) | PosixExpr::ArrayCall(..)) => {
+                let number = self.eval_array_node(node, |exec, node| {
+                    exec.eval_quantity_node(node).map(Self::quantity_number)
+                })?;
+                self.number_quantity(&number)
+            }
+            PosixExpr::Name(name) => match self.runtime.get_variable(name) {
+                Some(number) => self.number_quantity(&number),
+                None => self.eval_quantity_call(name, Vec::new()),
+            },
+            PosixExpr::Call(name, args) => {
+                let mut evaluated = Vec::with_capacity(args.len());
+                for arg in args {
+                    evaluated.push(self.eval_quantity_node(arg)?);
+                }
+                self.eval_quantity_call(name, evaluated)
+            }
+            PosixExpr::Negate(operand) => {
+                let quantity = self.eval_quantity_node(operand)?;
+                Ok(Quantity {
+                    value: -quantity.value,
+                    ..quantity
+                })
+            }
+            PosixExpr::Not(operand) => {
+                let quantity = self.eval_quantity_node(operand)?;
+                Ok(Self::quantity_bool(quantity.value.is_zero()))
+            }
+            node @ (PosixExpr::Complement(_) | PosixExpr::Bitwise(..)) => {
+                let number = self.eval_bitwise_node(node, |exec, node| {
+                    exec.eval_quantity_node(node).map(Self::quantity_number)
+                })?;
+                self.number_quantity(&number)
+            }
+            PosixExpr::Binary(PosixOp::And, lhs, rhs) => {
+                if self.eval_quantity_node(lhs)?.value.is_zero() {
+                    return Ok(Self::quantity_bool(false));
+                }
+                let right = self.eval_quantity_node(rhs)?;
+                Ok(Self::quantity_bool(!right.value.is_zero()))
+            }
+            PosixExpr::Binary(PosixOp::Or, lhs, rhs) => {
+                if !self.eval_quantity_node(lhs)?.value.is_zero() {
+                    return Ok(Self::quantity_bool(true));
+                }
+                let right = self.eval_quantity_node(rhs)?;
+                Ok(Self::quantity_bool(!right.value.is_zero()))
+            }
+            PosixExpr::Binary(op, lhs, rhs) => {
+                let left = self.eval_quantity_node(lhs)?;
+                let right = self.eval_quantity_node(rhs)?;
+                let (a, b) = (&left.value, &right.value);
+                match op {
+                    PosixOp::Add | PosixOp::Sub => {
+                        left.same_dimension(&right, "add")?;
+                        let value = if *op == PosixOp::Add {
+                            a.clone() + b
+                        } else {
+                            a.clone() - b
+                        };
+                        Ok(Quantity { value, ..left })
+                    }
+                    PosixOp::Mul => Ok(Quantity {
+                        value: a.clone() * b,
+                        dimension: left.dimension.combine(right.dimension, 1)?,
+                    }),
+                    PosixOp::Div => {
+                        if b.is_zero() {
+                            return Err(BcError::Error("Division by zero".to_string()));
+                        }
+                        Ok(Quantity {
+                            value: a.clone() / b,
+                            dimension: left.dimension.combine(right.dimension, -1)?,
+                        })
+                    }
+                    PosixOp::Mod => {
+                        left.same_dimension(&right, "take the remainder of")?;
+                        if b.is_zero() {
+                            return Err(BcError::Error("Modulo by zero".to_string()));
+                        }
+                        Ok(Quantity {
+                            value: a.clone() % b,
+                            ..left
+                        })
+                    }
+                    PosixOp::Pow => self.quantity_power(&left, &right),
+                    _ => {
+                        left.same_dimension(&right, "compare")?;
+                        let holds = match op {
+                            PosixOp::Lt => a < b,
+                            PosixOp::Le => a <= b,
+                            PosixOp::Gt => a > b,
+                            PosixOp::Ge => a >= b,
+                            PosixOp::Eq => a == b,
+                            PosixOp::Ne => a != b,
+                            _ => unreachable!("handled above"),
+                        };
+                        Ok(Self::quantity_bool(holds))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Raising a quantity takes a plain exponent: an integer, or the
+    /// reciprocal of one when every dimension divides evenly by it.
+    fn quantity_power(&self, base: &Quantity, exponent: &Quantity) -> Result<Quantity, BcError> {
+        if !exponent.dimension.is_none() {
+            return Err(BcError::Error(format!(
+                "Exponent must be a plain number, not {}",
+                describe(exponent.dimension)
+            )));
+        }
+        let value = self.power_decimal(&base.value, &exponent.value)?;
+        if base.dimension.is_none() {
+            return Ok(Quantity::plain(value));
+        }
+        let invalid = || {
+            BcError::Error(format!(
+                "Cannot raise {} to a fractional power",
+                describe(base.dimension)
+            ))
+        };
+        let exact = RBig::try_from(exponent.value.clone()).map_err(|_| invalid())?;
+        let dimension = if exact.denominator().is_one() {
+            let power = i64::try_from(exact.numerator()).map_err(|_| exponent_out_of_range())?;
+            base.dimension.powi(power)?
+        } else if exact.numerator().is_one() {
+            let root = i64::try_from(exact.denominator()).map_err(|_| invalid())?;
+            base.dimension.root(root).ok_or_else(invalid)?
+        } else {
+            return Err(invalid());
+        };
+        Ok(Quantity { value, dimension })
+    }
+
+    /// `sqrt` and `cbrt` take roots of the dimension; `abs`, the rounding
+    /// helpers, `min` and `max` keep it; every other builtin needs plain
+    /// numbers. User functions see their arguments with units attached.
+    fn eval_quantity_call(&mut self, name: &str, args: Vec<Quantity>) -> Result<Quantity, BcError> {
+        let values: Vec<Decimal> = args.iter().map(|arg| arg.value.clone()).collect();
+        let first = args.first().map(|arg| arg.dimension).unwrap_or_default();
+        let dimension = match name {
+            "sqrt" | "cbrt" => {
+                let n = if name == "sqrt" { 2 } else { 3 };
+                first.root(n).ok_or_else(|| {
+                    BcError::Error(format!("Cannot take {name}() of {}", describe(first)))
+                })?
+            }
+            "abs" | "floor" | "ceil" | "trunc" | "round" | "min" | "max" => {
+                if let Some(mismatch) = args.iter().find(|arg| arg.dimension != first) {
+                    return Err(BcError::Error(format!(
+                        "Cannot compare {} and {}",
+                        describe(first),
+                        describe(mismatch.dimension)
+                    )));
+                }
+                first
+            }
+            _ => {
+                if let Some(arg) = args.iter().find(|arg| !arg.dimension.is_none()) {
+                    if self.runtime.get_function(name).is_none() {
+                        return Err(BcError::Error(format!(
+                            "{name}() needs a plain number, not {}",
+                            describe(arg.dimension)
+                        )));
+                    }
+                }
+                Dimension::default()
+            }
+        };
+        if let Some(result) = self.call_builtin_function(name, &values) {
+            return Ok(Quantity {
+                value: result?,
+                dimension,
+            });
+        }
+
+        let numbers = args.into_iter().map(Self::quantity_number).collect();
+        if let Some(result) = self.call_function(name, numbers)? {
+            return self.number_quantity(&result);
+        }
+
+        Err(BcError::Error(format!("Undefined identifier: {name}")))
+    }
+
+    fn number_quantity(&self, number: &BcNumber) -> Result<Quantity, BcError> {
+        if number.uncertainty.is_some() {
+            return Err(BcError::Error(
+                "Units cannot be combined with uncertain values".to_string(),
+            ));
+        }
+        if number.time.is_some() {
+            return Err(BcError::Error(
+                "Units cannot be combined with dates and durations".to_string(),
+            ));
+        }
+        if number.address.is_some() {
+            return Err(BcError::Error(
+                "Units cannot be combined with IP addresses".to_string(),
+            ));
+        }
+        let value = self.promote_precision(number.value.clone());
+        Ok(match &number.unit {
+            Some(unit) => Quantity {
+                value: self.scale_by(value, &unit.factor),
+                dimension: unit.dimension,
+            },
+            None => Quantity::plain(value),
+        })
+    }
+
+    /// A quantity as a number carrying its coherent SI unit.
+    fn quantity_number(quantity: Quantity) -> BcNumber {
+        if quantity.dimension.is_none() {
+            return BcNumber::natural(quantity.value);
+        }
+        BcNumber {
+            unit: Some(Unit::coherent(quantity.dimension)),
+            ..BcNumber::natural(quantity.value)
+        }
+    }
+
+    /// `value · factor`, exact whenever the factor is a terminating decimal.
+    fn scale_by(&self, value: Decimal, factor: &RBig) -> Decimal {
+        if let Some(factor) = terminating(factor) {
+            return value * factor;
+        }
+        let scaled = value * Decimal::from(factor.numerator().clone());
+        self.promote_precision(scaled) / Decimal::from(factor.denominator().clone())
+    }
+
+    fn quantity_bool(value: bool) -> Quantity {
+        Quantity::plain(if value { Decimal::ONE } else { Decimal::ZERO })
+    }
+}
+
+/// Splits `3 km in mi` (or `3 km -> mi`) at its last top-level conversion,
+/// when what follows is a unit.
+pub(super) fn split_conversion(expr: &str) -> Option<(&str, Unit)> {
+    let mut depth = 0i32;
+    let mut found = None;
+    let bytes = expr.as_bytes();
+    for (index, ch) in expr.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth != 0 => {}
+            '-' if bytes.get(index + 1) == Some(&b'>') => found = Some((index, 2)),
+            'i' if expr[index..].starts_with("in")
+                && index > 0
+                && bytes[index - 1].is_ascii_whitespace()
+                && bytes.get(index + 2).is_some_and(u8::is_ascii_whitespace) =>
+            {
+                found = Some((index, 2))
+            }
+            _ => {}
+        }
+    }
+    let (index, width) = found?;
+    let target = Unit::parse(&expr[index + width..])?;
+    Some((&expr[..index], target))
+}
+
+/// Powers of ten for SI prefixes, plus the centi and deci that only make
+/// sense in front of units.
+fn prefix_power(prefix: char) -> Option<i32> {
+    match prefix {
+        'c' => Some(-2),
+        'd' => Some(-1),
+        _ => LiteralTable::si_power(prefix),
+    }
+}
+
+/// `value` as a decimal, when its denominator has no prime factors other
+/// than 2 and 5.
+fn terminating(value: &RBig) -> Option<Decimal> {
+    let mut rest = value.denominator().clone();
+    let (mut twos, mut fives) = (0usize, 0usize);
+    while (&rest % 2u8) == 0 {
+        rest /= 2u8;
+        twos += 1;
+    }
+    while (&rest % 5u8) == 0 {
+        rest /= 5u8;
+        fives += 1;
+    }
+    if !rest.is_one() {
+        return None;
+    }
+    let places = twos.max(fives);
+    let units =
+        value.numerator() * IBig::from(10).pow(places) / IBig::from(value.denominator().clone());
+    Some(Decimal::from_parts(units, -(places as isize)))
+}
+
+fn pow_rational(base: &RBig, power: i8) -> RBig {
+    let raised = RBig::from_parts(
+        base.numerator().pow(power.unsigned_abs() as usize),
+        base.denominator().pow(power.unsigned_abs() as usize),
+    );
+    if power < 0 {
+        RBig::ONE / raised
+    } else {
+        raised
+    }
+}
+
+fn describe(dimension: Dimension) -> String {
+    if dimension.is_none() {
+        "a plain number".to_string()
+    } else {
+        Unit::coherent(dimension).symbol
+    }
+}
+
+fn exponent_out_of_range() -> BcError {
+    BcError::Error("Unit exponent out of supported range".to_string())
+}
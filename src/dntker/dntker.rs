@@ -41,8 +41,6 @@ pub(crate) enum FilterResult {
     Esc,
     Refresh,
     Delete,
-    CurLeft,
-    CurRight,
     Unknown(u8),
 }
 
@@ -79,8 +77,10 @@ impl Dntker {
             util::ASCII_CODE_NINE => FilterResult::Calculatable(util::ASCII_CODE_NINE),
             util::ASCII_CODE_ROUNDLEFT => FilterResult::Calculatable(util::ASCII_CODE_ROUNDLEFT),
             util::ASCII_CODE_ROUNDRIGHT => FilterResult::Calculatable(util::ASCII_CODE_ROUNDRIGHT),
-            util::ASCII_CODE_SQUARELEFT => FilterResult::CurLeft,
-            util::ASCII_CODE_SQUARERIGHT => FilterResult::CurRight,
+            util::ASCII_CODE_SQUARELEFT => FilterResult::Calculatable(util::ASCII_CODE_SQUARELEFT),
+            util::ASCII_CODE_SQUARERIGHT => {
+                FilterResult::Calculatable(util::ASCII_CODE_SQUARERIGHT)
+            }
             util::ASCII_CODE_BACKSLASH => FilterResult::Calculatable(util::ASCII_CODE_BACKSLASH),
             util::ASCII_CODE_CURLYLEFT => FilterResult::Calculatable(util::ASCII_CODE_CURLYLEFT),
             util::ASCII_CODE_CURLYRIGHT => FilterResult::Calculatable(util::ASCII_CODE_CURLYRIGHT),
@@ -98,7 +98,12 @@ impl Dntker {
             util::ASCII_CODE_EQUAL => FilterResult::Calculatable(util::ASCII_CODE_EQUAL),
             util::ASCII_CODE_PIPE => FilterResult::Calculatable(util::ASCII_CODE_PIPE),
             util::ASCII_CODE_AND => FilterResult::Calculatable(util::ASCII_CODE_AND),
+            util::ASCII_CODE_TILDE => FilterResult::Calculatable(util::ASCII_CODE_TILDE),
+            util::ASCII_CODE_DOUBLEQUOTE => {
+                FilterResult::Calculatable(util::ASCII_CODE_DOUBLEQUOTE)
+            }
             util::ASCII_CODE_SEMICOLON => FilterResult::Calculatable(util::ASCII_CODE_SEMICOLON),
+            util::ASCII_CODE_COLON => FilterResult::Calculatable(util::ASCII_CODE_COLON),
             util::ASCII_CODE_UNDERSCORE => FilterResult::Calculatable(util::ASCII_CODE_UNDERSCORE),
             b'A'..=b'Z' => FilterResult::Calculatable(ascii_char),
             b'a'..=b'z' => FilterResult::Calculatable(ascii_char),
@@ -214,6 +219,10 @@ impl Dntker {
     fn calculate(&mut self, prompt: &str, statement: &str, separator: &str) -> DntkResult {
         match self.executer.exec(statement) {
             Ok(result) => {
+                let result = self
+                    .executer
+                    .base_views()
+                    .map_or(result, |views| views.join(" | "));
                 let rendered = self
                     .prompt
                     .render_success(prompt, statement, separator, &result, self.cursor())
@@ -315,12 +324,6 @@ impl Dntker {
                 FilterResult::Delete => {
                     self.delete_column();
                 }
-                FilterResult::CurLeft => {
-                    self.cursor_move_left();
-                }
-                FilterResult::CurRight => {
-                    self.cursor_move_right();
-                }
                 FilterResult::Calculatable(code) => {
                     self.insert_column(*code);
                 }
@@ -387,8 +390,19 @@ impl Dntker {
         if !std::io::stdin().is_terminal()
             && std::env::var_os("DNTK_ENV") != Some(std::ffi::OsString::from("TEST"))
         {
+            // `get_termattr` made stdin non-blocking for the key loop; piped
+            // input has to wait for the writer instead.
+            #[cfg(not(target_os = "windows"))]
+            unsafe {
+                let flags = libc::fcntl(0, libc::F_GETFL);
+                if flags != -1 {
+                    libc::fcntl(0, libc::F_SETFL, flags & !libc::O_NONBLOCK);
+                }
+            }
             let mut s = String::new();
             std::io::stdin().read_line(&mut s).ok();
+            self.executer
+                .attach_input(Box::new(std::io::stdin().lock()));
             let output = self.executer.exec(&s).unwrap();
             self.write_stdout_ln(&output);
             return;
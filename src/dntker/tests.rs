@@ -17,11 +17,23 @@ fn filter_char_classifies_known_inputs() {
     );
     assert_eq!(
         d.filter_char(util::ASCII_CODE_SQUARELEFT),
-        FilterResult::CurLeft
+        FilterResult::Calculatable(util::ASCII_CODE_SQUARELEFT)
     );
     assert_eq!(
         d.filter_char(util::ASCII_CODE_SQUARERIGHT),
-        FilterResult::CurRight
+        FilterResult::Calculatable(util::ASCII_CODE_SQUARERIGHT)
+    );
+    assert_eq!(
+        d.filter_char(util::ASCII_CODE_TILDE),
+        FilterResult::Calculatable(util::ASCII_CODE_TILDE)
+    );
+    assert_eq!(
+        d.filter_char(util::ASCII_CODE_DOUBLEQUOTE),
+        FilterResult::Calculatable(util::ASCII_CODE_DOUBLEQUOTE)
+    );
+    assert_eq!(
+        d.filter_char(util::ASCII_CODE_COLON),
+        FilterResult::Calculatable(util::ASCII_CODE_COLON)
     );
     assert_eq!(d.filter_char(util::ASCII_CODE_AT), FilterResult::Refresh);
     assert_eq!(d.filter_char(util::ASCII_CODE_ESCAPE), FilterResult::Esc);
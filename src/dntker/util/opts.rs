@@ -17,6 +17,42 @@ pub struct Opts {
     #[arg(short = 's', long = "scale", default_value_t = 20, help = "Number of truncated after the decimal point")]
     pub scale: usize,
 
+    // Apply POSIX bc scale rules to every intermediate result
+    #[arg(long = "posix-scale", help = "Apply POSIX bc scale rules to every intermediate result")]
+    pub posix_scale: bool,
+
+    // Keep values as exact fractions
+    #[arg(long = "rational", help = "Keep values as exact fractions (set rational=2 to print them as decimals)")]
+    pub rational: bool,
+
+    // Carry guaranteed lower and upper bounds through every result
+    #[arg(long = "interval", help = "Carry guaranteed [lo, hi] bounds through every result (set interval=2 to print mid ± radius)")]
+    pub interval: bool,
+
+    // Rounding applied when a value is cut down to scale digits
+    #[arg(long = "round", default_value = "truncate", value_parser = ["truncate", "half-even", "half-up", "floor", "ceiling"], help = "Rounding applied at scale digits: truncate, half-even, half-up, floor or ceiling")]
+    pub round: String,
+
+    // Notation for decimal results
+    #[arg(long = "notation", default_value = "plain", value_parser = ["plain", "sci", "eng", "si"], help = "Notation for results: plain, sci (1.234e-60), eng (exponents in multiples of 3) or si (SI prefixes)")]
+    pub notation: String,
+
+    // Digit grouping and decimal separator
+    #[arg(long = "locale", default_value = "plain", value_parser = ["plain", "en", "fr", "de", "in"], help = "Digit grouping: plain, en (1,234.5), fr (1 234,5), de (1.234,5) or in (12,34,567.5); fr and de read ',' as the decimal point")]
+    pub locale: String,
+
+    // Unit trig functions take and inverse trig functions return
+    #[arg(long = "angle", default_value = "rad", value_parser = ["rad", "deg", "grad"], help = "Angle unit for trig and inverse trig functions: rad, deg or grad")]
+    pub angle: String,
+
+    // Fixed-width integer type for programmer mode
+    #[arg(long = "word", value_parser = ["i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "i128", "u128"], help = "Programmer mode: wrap integers to a fixed-width type such as i32 or u8")]
+    pub word: Option<String>,
+
+    // Preview integer results in decimal, hex, octal and binary at once
+    #[arg(long = "multibase", help = "Preview integer results in decimal, hex, octal and binary at once")]
+    pub multibase: bool,
+
     // Set White color in a output
     #[arg(short = 'w', long = "white", help = "Set White color in a output")]
     pub white: bool,
@@ -1,5 +1,6 @@
 use assert_cmd::prelude::*;
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 #[test]
 fn test_cmd_with_once() {
@@ -127,3 +128,23 @@ fn test_cmd_with_builtin_length_scale() {
     assert!(stdout.contains("length(12345) + scale(12.345) + log(100)"));
     assert!(stdout.contains("= 10"));
 }
+
+#[test]
+fn test_cmd_with_piped_print_and_read() {
+    let mut child = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .env_remove("DNTK_ENV")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"x = read(); print \"x = \", x * 2, \"\\n\"\n21\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "x = 42\n");
+}